@@ -0,0 +1,85 @@
+//! Cost of one process scan, as the monitor did it before and does it now.
+//!
+//! Run with `cargo bench --bench scan`, optionally followed by `-- <ROUNDS>`.
+
+use rest_reminder::core::probe::{ProcessProbe, ScanDetails, SysinfoProbe};
+use std::time::{Duration, Instant};
+use sysinfo::{ProcessesToUpdate, System};
+
+const DEFAULT_ROUNDS: u32 = 50;
+
+// The scan the monitor ran on every check before it knew what the rules look at
+fn full_scan(sys: &mut System) -> usize {
+    sys.refresh_processes(ProcessesToUpdate::All, true);
+    sys.processes().len()
+}
+
+// Average time of `rounds` runs of `scan`, after one run to warm up
+fn measure(rounds: u32, mut scan: impl FnMut() -> usize) -> (Duration, usize) {
+    let processes = scan();
+    let started_at = Instant::now();
+    for _ in 0..rounds {
+        std::hint::black_box(scan());
+    }
+    (started_at.elapsed() / rounds, processes)
+}
+
+fn report(name: &str, per_scan: Duration, baseline: Duration, processes: Option<usize>) {
+    println!(
+        "{:<28} {:>10.3} ms  {:>6.1}%  {}",
+        name,
+        per_scan.as_secs_f64() * 1000.0,
+        per_scan.as_secs_f64() / baseline.as_secs_f64() * 100.0,
+        processes.map_or(String::new(), |count| format!("({} processes)", count))
+    );
+}
+
+fn main() {
+    let rounds = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse::<u32>().ok())
+        .filter(|rounds| *rounds > 0)
+        .unwrap_or(DEFAULT_ROUNDS);
+    println!("{} rounds per scan", rounds);
+
+    let started_at = Instant::now();
+    let mut sys = System::new_all();
+    let new_all = started_at.elapsed();
+    let started_at = Instant::now();
+    let mut probe = SysinfoProbe::new();
+    let new_probe = started_at.elapsed();
+    report("startup: System::new_all", new_all, new_all, None);
+    report("startup: SysinfoProbe::new", new_probe, new_all, None);
+
+    let (baseline, processes) = measure(rounds, || full_scan(&mut sys));
+    report("full scan (before)", baseline, baseline, Some(processes));
+
+    let cases = [
+        ("name only", ScanDetails::default()),
+        (
+            "exe: rules",
+            ScanDetails {
+                exe: true,
+                ..ScanDetails::default()
+            },
+        ),
+        (
+            "activity rules",
+            ScanDetails {
+                usage: true,
+                ..ScanDetails::default()
+            },
+        ),
+        ("everything", ScanDetails::ALL),
+    ];
+    for (name, details) in cases {
+        probe.set_details(details);
+        let (per_scan, processes) = measure(rounds, || probe.processes().len());
+        report(
+            &format!("probe: {}", name),
+            per_scan,
+            baseline,
+            Some(processes),
+        );
+    }
+}
@@ -1,7 +1,18 @@
+use crate::core::budget::DEFAULT_BUDGET_ALERTS;
+use crate::core::engine::{
+    DEFAULT_LONG_BREAK_SECONDS, DEFAULT_POMODORO_CYCLES, DEFAULT_SHORT_BREAK_SECONDS,
+};
+use crate::core::git_task::GitTaskRule;
+use crate::core::matcher::AppRule;
+use crate::core::notify::NotifyRoute;
+use crate::core::poll::{DEFAULT_POLL_RESTING_SECONDS, DEFAULT_POLL_WORKING_SECONDS};
+use crate::core::schedule::Schedule;
+use crate::core::snooze::DEFAULT_SNOOZE_SECONDS;
+use crate::core::task_rules::TaskRules;
 use chrono::{DateTime, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
 use clap::builder::ValueParser;
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 // Platform-specific default paths
 #[cfg(windows)]
@@ -25,6 +36,13 @@ const DEFAULT_PLOT_PATH: &str = "./plot.png";
 #[cfg(not(any(windows, target_os = "macos")))]
 const DEFAULT_LOG_DIR: &str = "./";
 
+#[cfg(windows)]
+const DEFAULT_APPS: &[&str] = &["idea64.exe", "rustrover64.exe", "Code.exe"];
+#[cfg(target_os = "macos")]
+const DEFAULT_APPS: &[&str] = &["IntelliJ IDEA", "RustRover", "Cursor", "Xcode"];
+#[cfg(not(any(windows, target_os = "macos")))]
+const DEFAULT_APPS: &[&str] = &["idea", "rustrover", "code"];
+
 #[derive(Parser, Debug)]
 #[command(
     name = "Rest Reminder",
@@ -35,8 +53,27 @@ const DEFAULT_LOG_DIR: &str = "./";
 pub struct Cli {
     #[command(subcommand)]
     pub cmd: Command,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "LOCALE",
+        help = "Language of the messages, e.g. zh_CN (default: from LC_ALL, LC_MESSAGES or LANG)"
+    )]
+    pub locale: Option<String>,
+
+    #[arg(
+        long,
+        global = true,
+        value_name = "CATALOG",
+        value_parser = clap::value_parser!(PathBuf),
+        help = "JSON message catalog overriding the built-in texts"
+    )]
+    pub messages: Option<PathBuf>,
 }
 
+// Parsed once per command, so the size of `rest` with all its options does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Command {
     // Statistics
@@ -172,6 +209,66 @@ pub enum Command {
         end_day: DateTime<Local>,
     },
 
+    #[command(name = "count-by-app")]
+    CountByApp {
+        #[arg(
+            short,
+            long,
+            value_name = "LOG_PATH",
+            default_value = DEFAULT_LOG_PATH,
+            value_parser = ValueParser::path_buf()
+        )]
+        log_location: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "START",
+            help = "Format: YYYY-MM-DD",
+            value_parser = parse_datetime_local_day,
+        )]
+        start_day: DateTime<Local>,
+
+        #[arg(
+            short,
+            long,
+            value_name = "END",
+            help = "Format: YYYY-MM-DD",
+            value_parser = parse_datetime_local_day,
+        )]
+        end_day: DateTime<Local>,
+    },
+
+    #[command(name = "count-breaks")]
+    CountBreaks {
+        #[arg(
+            short,
+            long,
+            value_name = "LOG_PATH",
+            default_value = DEFAULT_LOG_PATH,
+            value_parser = ValueParser::path_buf()
+        )]
+        log_location: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "START",
+            help = "Format: YYYY-MM-DD",
+            value_parser = parse_datetime_local_day,
+        )]
+        start_day: DateTime<Local>,
+
+        #[arg(
+            short,
+            long,
+            value_name = "END",
+            help = "Format: YYYY-MM-DD",
+            value_parser = parse_datetime_local_day,
+        )]
+        end_day: DateTime<Local>,
+    },
+
     // Rest reminder
     #[command(name = "rest")]
     Rest {
@@ -194,45 +291,182 @@ pub enum Command {
         )]
         time: u64,
 
-        #[cfg(windows)]
         #[arg(
             short,
             long,
             value_name = "APP",
             num_args = 1..,
-            default_values = &["idea64.exe", "rustrover64.exe", "Code.exe"],
-            help = "What software(s) to detect",
+            default_values = DEFAULT_APPS,
+            help = "What software(s) to detect, optionally as a rule like exact:code, regex:..., glob:..., exe:..., cmd:..., cwd:... or mine:..., \"A > B\" for B running below A, with @SECONDS for its own threshold and ~cpu=PERCENT,io=BYTES to count it only while busy",
         )]
-        app: Vec<String>,
+        app: Vec<AppRule>,
 
-        #[cfg(target_os = "macos")]
         #[arg(
-            short,
             long,
-            value_name = "APP",
-            num_args = 1..,
-            default_values = &["IntelliJ IDEA", "RustRover", "Cursor", "Xcode"],
-            help = "What software(s) to detect",
+            value_name = "TASK",
+            help = "Task label to store with new work sessions"
         )]
-        app: Vec<String>,
+        task: Option<String>,
 
-        #[cfg(not(any(windows, target_os = "macos")))]
         #[arg(
-            short,
             long,
-            value_name = "APP",
+            help = "Label sessions repo:branch after the git checkout the monitored apps have open, falling back to --task"
+        )]
+        git_task: bool,
+
+        #[arg(
+            long,
+            value_name = "PATTERN=LABEL",
+            help = "Regex on repo:branch and the label to use instead, e.g. '^[^:]+:feature/([A-Z]+-[0-9]+)=$1'. Implies --git-task"
+        )]
+        git_task_rule: Vec<GitTaskRule>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            value_parser = parse_task_rules,
+            help = "JSON rules picking the task label from running apps, command lines, hours and weekdays"
+        )]
+        task_rules: Option<TaskRules>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "End the session after this many seconds without keyboard or mouse input"
+        )]
+        idle: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = DEFAULT_SNOOZE_SECONDS,
+            help = "How many seconds a snooze postpones the break reminder"
+        )]
+        snooze: u64,
+
+        #[arg(
+            long,
+            value_name = "COUNT",
+            help = "How many times the break reminder can be snoozed before it escalates"
+        )]
+        max_snoozes: Option<u32>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "Breaks shorter than this many seconds are logged as violations"
+        )]
+        min_break: Option<u64>,
+
+        #[arg(
+            long,
+            help = "Work in pomodoro cycles, with --time as the length of one pomodoro"
+        )]
+        pomodoro: bool,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = DEFAULT_SHORT_BREAK_SECONDS,
+            help = "Pomodoro break after each work cycle"
+        )]
+        short_break: u64,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = DEFAULT_LONG_BREAK_SECONDS,
+            help = "Pomodoro break after every --cycles work cycles"
+        )]
+        long_break: u64,
+
+        #[arg(
+            long,
+            value_name = "COUNT",
+            default_value_t = DEFAULT_POMODORO_CYCLES,
+            value_parser = clap::value_parser!(u32).range(1..),
+            help = "How many pomodoros come before a long break"
+        )]
+        cycles: u32,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Repeat an ignored break reminder every this many seconds, keeping the session open"
+        )]
+        remind_every: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "How many seconds of work per day are fine across all sessions"
+        )]
+        daily_budget: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "How many seconds of work per week (from Monday) are fine across all sessions"
+        )]
+        weekly_budget: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "PERCENT",
             num_args = 1..,
-            default_values = &["idea", "rustrover", "code"],
-            help = "What software(s) to detect",
+            value_delimiter = ',',
+            default_values_t = DEFAULT_BUDGET_ALERTS,
+            help = "Percentages of the work budget to alert at"
         )]
-        app: Vec<String>,
+        budget_alerts: Vec<u32>,
 
         #[arg(
             long,
-            value_name = "TASK",
-            help = "Task label to store with new work sessions"
+            value_name = "SECONDS",
+            help = "Only start a session once the apps have been running for this many seconds"
         )]
-        task: Option<String>,
+        start_debounce: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "Keep the session going when the apps disappear for less than this many seconds"
+        )]
+        end_grace: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "FILE",
+            value_parser = parse_schedule,
+            help = "JSON working hours, holidays and quiet hours; work outside them is logged as overtime"
+        )]
+        schedule: Option<Schedule>,
+
+        #[arg(
+            long,
+            value_name = "EVENT=SINKS",
+            help = "Where to show an event, e.g. reminder=terminal,bell or budget=file:/tmp/rest.fifo"
+        )]
+        notify: Vec<NotifyRoute>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = DEFAULT_POLL_RESTING_SECONDS,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Seconds between process checks while no session is open or on a break"
+        )]
+        poll_resting: u64,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = DEFAULT_POLL_WORKING_SECONDS,
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "Seconds between process checks while working; the break reminder still fires on time"
+        )]
+        poll_working: u64,
     },
 
     // Plotting
@@ -290,7 +524,140 @@ pub enum Command {
         name: String,
     },
     #[command(name = "web")]
-    Web {},
+    Web {
+        #[arg(
+            short,
+            long,
+            value_name = "LOG_PATH",
+            default_value = DEFAULT_LOG_DIR,
+            value_parser = clap::value_parser!(PathBuf),
+            help = "Log whose unfinished session is recovered on startup",
+        )]
+        log_to: PathBuf,
+    },
+
+    // Background monitor controlled over a Unix socket
+    #[command(name = "daemon")]
+    Daemon {
+        #[arg(
+            long,
+            value_name = "PATH",
+            value_parser = clap::value_parser!(PathBuf),
+            help = "Unix socket to listen on (default: $XDG_RUNTIME_DIR/rest-reminder.sock)"
+        )]
+        socket: Option<PathBuf>,
+    },
+
+    #[command(name = "ctl")]
+    Ctl {
+        #[arg(
+            long,
+            value_name = "PATH",
+            value_parser = clap::value_parser!(PathBuf),
+            help = "Unix socket of the daemon (default: $XDG_RUNTIME_DIR/rest-reminder.sock)"
+        )]
+        socket: Option<PathBuf>,
+
+        #[command(subcommand)]
+        request: CtlCommand,
+    },
+}
+
+/// Requests `ctl` sends to a running daemon.
+#[derive(Subcommand, Debug)]
+pub enum CtlCommand {
+    #[command(name = "start")]
+    Start {
+        #[arg(
+            short,
+            long,
+            value_name = "LOG_PATH",
+            default_value = DEFAULT_LOG_DIR,
+            value_parser = clap::value_parser!(PathBuf),
+            help = "Where to save the log file",
+        )]
+        log_to: PathBuf,
+
+        #[arg(
+            short,
+            long,
+            value_name = "TIME",
+            default_value_t = 3600,
+            help = "How many seconds to work non stop before reminding"
+        )]
+        time: u64,
+
+        #[arg(
+            short,
+            long,
+            value_name = "APP",
+            num_args = 1..,
+            default_values = DEFAULT_APPS,
+            help = "What software(s) to detect, with the same rules as rest -a",
+        )]
+        app: Vec<AppRule>,
+
+        #[arg(
+            long,
+            value_name = "TASK",
+            help = "Task label to store with new work sessions"
+        )]
+        task: Option<String>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "End the session after this many seconds without keyboard or mouse input"
+        )]
+        idle: Option<u64>,
+
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            default_value_t = DEFAULT_SNOOZE_SECONDS,
+            help = "How many seconds a snooze postpones the break reminder"
+        )]
+        snooze: u64,
+    },
+
+    #[command(name = "stop")]
+    Stop,
+
+    #[command(name = "pause")]
+    Pause,
+
+    #[command(name = "resume")]
+    Resume,
+
+    #[command(name = "status")]
+    Status,
+
+    #[command(name = "set-task")]
+    SetTask {
+        #[arg(
+            value_name = "TASK",
+            help = "Label for the sessions from now on, leave out to clear it"
+        )]
+        task: Option<String>,
+    },
+
+    #[command(name = "snooze")]
+    Snooze {
+        #[arg(
+            value_name = "SECONDS",
+            value_parser = clap::value_parser!(u64).range(1..),
+            help = "How long to postpone the break reminder (default: --snooze of start)"
+        )]
+        seconds: Option<u64>,
+    },
+}
+
+fn parse_task_rules(path: &str) -> Result<TaskRules, String> {
+    TaskRules::load(Path::new(path))
+}
+
+fn parse_schedule(path: &str) -> Result<Schedule, String> {
+    Schedule::load(Path::new(path))
 }
 
 pub fn parse_datetime_local(s: &str) -> Result<DateTime<Local>, String> {
@@ -0,0 +1,342 @@
+use crate::core::matcher::{AppRule, ProcessTree};
+use crate::core::probe::ProcessInfo;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::str::FromStr;
+use tokio::time::{Duration, Instant};
+
+/// Seconds the CPU and disk usage of an app is averaged over by default.
+pub const DEFAULT_ACTIVITY_WINDOW_SECONDS: u64 = 60;
+
+const KEYS: [&str; 5] = ["cpu", "io", "window", "tree", "linger"];
+
+/// When a running app counts as actively used, written after `~` in an app rule,
+/// e.g. `Code~cpu=2,io=64k,window=120,tree` or `tmux > cargo~linger=30`.
+///
+/// The app is active while its average CPU usage or disk I/O rate over the window
+/// exceeds either threshold, and for `linger` seconds after that.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActivityThreshold {
+    /// CPU usage in percent of one core.
+    pub cpu: Option<f64>,
+    /// Bytes read and written per second.
+    pub io: Option<u64>,
+    /// Seconds the usage is averaged over.
+    pub window: u64,
+    /// Whether the child processes of the matched ones count as well.
+    pub tree: bool,
+    /// Seconds the app still counts after it was last seen or busy, so short-lived
+    /// processes such as `cargo` runs keep the session going in between.
+    pub linger: Option<u64>,
+}
+
+impl ActivityThreshold {
+    pub fn met_by(&self, activity: &AppActivity) -> bool {
+        if self.cpu.is_none() && self.io.is_none() {
+            return true;
+        }
+        self.cpu.is_some_and(|cpu| activity.cpu_percent > cpu)
+            || self.io.is_some_and(|io| activity.io_bytes_per_second > io)
+    }
+
+    /// Whether `spec` is meant as an activity criterion, so rules can still end in
+    /// `~something` otherwise.
+    pub fn recognised(spec: &str) -> bool {
+        spec.split(',').all(|part| {
+            let key = part.split_once('=').map_or(part, |(key, _)| key);
+            KEYS.contains(&key.trim())
+        })
+    }
+}
+
+impl FromStr for ActivityThreshold {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let invalid = |part: &str| format!("Invalid activity '{}' in '{}'", part.trim(), spec);
+        let mut threshold = ActivityThreshold {
+            cpu: None,
+            io: None,
+            window: DEFAULT_ACTIVITY_WINDOW_SECONDS,
+            tree: false,
+            linger: None,
+        };
+        for part in spec.split(',') {
+            match part
+                .split_once('=')
+                .map(|(key, value)| (key.trim(), value.trim()))
+            {
+                Some(("cpu", value)) => {
+                    let cpu = value
+                        .trim_end_matches('%')
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|cpu| cpu.is_finite() && *cpu >= 0.0)
+                        .ok_or_else(|| invalid(part))?;
+                    threshold.cpu = Some(cpu);
+                }
+                Some(("io", value)) => {
+                    threshold.io = Some(parse_bytes(value).ok_or_else(|| invalid(part))?);
+                }
+                Some(("window", value)) => {
+                    threshold.window = value
+                        .parse::<u64>()
+                        .ok()
+                        .filter(|window| *window > 0)
+                        .ok_or_else(|| invalid(part))?;
+                }
+                Some(("linger", value)) => {
+                    threshold.linger = Some(
+                        value
+                            .parse::<u64>()
+                            .ok()
+                            .filter(|linger| *linger > 0)
+                            .ok_or_else(|| invalid(part))?,
+                    );
+                }
+                None if part.trim() == "tree" => threshold.tree = true,
+                _ => return Err(invalid(part)),
+            }
+        }
+        if threshold.cpu.is_none() && threshold.io.is_none() && threshold.linger.is_none() {
+            return Err(format!(
+                "Activity '{}' needs a cpu=, io= or linger= value",
+                spec
+            ));
+        }
+        Ok(threshold)
+    }
+}
+
+impl fmt::Display for ActivityThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(cpu) = self.cpu {
+            parts.push(format!("cpu={}", cpu));
+        }
+        if let Some(io) = self.io {
+            parts.push(format!("io={}", io));
+        }
+        if self.window != DEFAULT_ACTIVITY_WINDOW_SECONDS {
+            parts.push(format!("window={}", self.window));
+        }
+        if self.tree {
+            parts.push("tree".to_string());
+        }
+        if let Some(linger) = self.linger {
+            parts.push(format!("linger={}", linger));
+        }
+        f.write_str(&parts.join(","))
+    }
+}
+
+// A byte count with an optional `k` or `m` suffix, in multiples of 1024
+fn parse_bytes(value: &str) -> Option<u64> {
+    let lower = value.to_ascii_lowercase();
+    let (number, unit) = match lower.strip_suffix('k') {
+        Some(number) => (number, 1024),
+        None => match lower.strip_suffix('m') {
+            Some(number) => (number, 1024 * 1024),
+            None => (lower.as_str(), 1),
+        },
+    };
+    number.trim().parse::<u64>().ok()?.checked_mul(unit)
+}
+
+/// CPU and disk usage of the processes of one app rule, averaged over its window.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct AppActivity {
+    pub cpu_percent: f64,
+    pub io_bytes_per_second: u64,
+    /// Whether the app counts as running: it meets its activity threshold, or has none,
+    /// or did so less than `linger` seconds ago.
+    pub active: bool,
+}
+
+// Usage of an app between two process checks
+struct Sample {
+    at: Instant,
+    span: Duration,
+    cpu_time: u64,
+    disk_bytes: u64,
+}
+
+/// Follows the CPU and disk usage of the monitored apps from one process check to
+/// the next.
+pub struct ActivityTracker {
+    // CPU time and disk bytes of every process at the last check
+    totals: HashMap<u32, (u64, u64)>,
+    checked_at: Option<Instant>,
+    samples: HashMap<String, VecDeque<Sample>>,
+    // When each app was last seen running and busy
+    last_active: HashMap<String, Instant>,
+    figures: BTreeMap<String, AppActivity>,
+}
+
+impl ActivityTracker {
+    pub fn new() -> Self {
+        ActivityTracker {
+            totals: HashMap::new(),
+            checked_at: None,
+            samples: HashMap::new(),
+            last_active: HashMap::new(),
+            figures: BTreeMap::new(),
+        }
+    }
+
+    /// Records a process check and returns the names of the rules whose apps are
+    /// running and actively used.
+    pub fn update(
+        &mut self,
+        processes: &[ProcessInfo],
+        rules: &[AppRule],
+        current_user: Option<&str>,
+    ) -> Vec<String> {
+        let now = Instant::now();
+        let span = self.checked_at.replace(now).map(|at| now - at);
+        // A process seen for the first time only sets a baseline: its totals cover its
+        // whole lifetime, which would make a long-idle app look busy
+        let usage: HashMap<u32, (u64, u64)> = processes
+            .iter()
+            .filter_map(|process| {
+                let (cpu_time, disk_bytes) = self.totals.get(&process.pid)?;
+                Some((
+                    process.pid,
+                    (
+                        process.cpu_time.saturating_sub(*cpu_time),
+                        process.disk_bytes.saturating_sub(*disk_bytes),
+                    ),
+                ))
+            })
+            .collect();
+        self.totals = processes
+            .iter()
+            .map(|process| (process.pid, (process.cpu_time, process.disk_bytes)))
+            .collect();
+
+        let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+        for process in processes {
+            if let Some(parent) = process.parent {
+                children.entry(parent).or_default().push(process.pid);
+            }
+        }
+
+        let tree = ProcessTree::new(processes);
+        let mut running = Vec::new();
+        self.figures.clear();
+        for rule in rules {
+            let name = rule.as_str().to_string();
+            let matched: Vec<u32> = processes
+                .iter()
+                .filter(|process| rule.matches_in(process, &tree, current_user))
+                .map(|process| process.pid)
+                .collect();
+            let threshold = rule.activity();
+            let lingering = threshold
+                .and_then(|threshold| threshold.linger)
+                .zip(self.last_active.get(&name))
+                .is_some_and(|(linger, last_active)| {
+                    now - *last_active < Duration::from_secs(linger)
+                });
+            // An app that is started again is judged afresh
+            if matched.is_empty() {
+                self.samples.remove(&name);
+                if lingering {
+                    running.push(name);
+                }
+                continue;
+            }
+
+            let pids = match threshold {
+                Some(threshold) if threshold.tree => descendants(&matched, &children),
+                _ => matched.into_iter().collect(),
+            };
+            let window = Duration::from_secs(
+                threshold
+                    .map(|threshold| threshold.window)
+                    .unwrap_or(DEFAULT_ACTIVITY_WINDOW_SECONDS),
+            );
+            let samples = self.samples.entry(name.clone()).or_default();
+            // Nothing to compare with until the app was seen at a previous check
+            if let Some(span) = span.filter(|_| pids.iter().any(|pid| usage.contains_key(pid))) {
+                let (cpu_time, disk_bytes) = pids
+                    .iter()
+                    .filter_map(|pid| usage.get(pid))
+                    .fold((0, 0), |(cpu, disk), (more_cpu, more_disk)| {
+                        (cpu + more_cpu, disk + more_disk)
+                    });
+                samples.push_back(Sample {
+                    at: now,
+                    span,
+                    cpu_time,
+                    disk_bytes,
+                });
+            }
+            // Keep the samples that lie within the window, and at least the latest
+            while samples.len() > 1
+                && samples
+                    .front()
+                    .is_some_and(|sample| now - sample.at + sample.span > window)
+            {
+                samples.pop_front();
+            }
+
+            let mut activity = average(samples);
+            let busy = threshold.is_none_or(|threshold| threshold.met_by(&activity));
+            if busy {
+                self.last_active.insert(name.clone(), now);
+            }
+            activity.active = busy || lingering;
+            if activity.active {
+                running.push(name.clone());
+            }
+            self.figures.insert(name, activity);
+        }
+        running
+    }
+
+    /// Usage of the apps running at the last check, keyed by rule.
+    pub fn figures(&self) -> &BTreeMap<String, AppActivity> {
+        &self.figures
+    }
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn descendants(roots: &[u32], children: &HashMap<u32, Vec<u32>>) -> HashSet<u32> {
+    let mut found: HashSet<u32> = roots.iter().copied().collect();
+    let mut queue: Vec<u32> = roots.to_vec();
+    while let Some(pid) = queue.pop() {
+        for child in children.get(&pid).into_iter().flatten() {
+            if found.insert(*child) {
+                queue.push(*child);
+            }
+        }
+    }
+    found
+}
+
+fn average(samples: &VecDeque<Sample>) -> AppActivity {
+    let span: Duration = samples.iter().map(|sample| sample.span).sum();
+    let millis = span.as_millis() as f64;
+    if millis == 0.0 {
+        return AppActivity {
+            cpu_percent: 0.0,
+            io_bytes_per_second: 0,
+            active: false,
+        };
+    }
+    let cpu_time: u64 = samples.iter().map(|sample| sample.cpu_time).sum();
+    let disk_bytes: u64 = samples.iter().map(|sample| sample.disk_bytes).sum();
+    AppActivity {
+        // One decimal is plenty to tune a threshold
+        cpu_percent: (cpu_time as f64 / millis * 1000.0).round() / 10.0,
+        io_bytes_per_second: (disk_bytes as f64 / millis * 1000.0) as u64,
+        active: false,
+    }
+}
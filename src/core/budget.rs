@@ -0,0 +1,233 @@
+use crate::statistic::statistics::{acc_work_time, single_day_work_time};
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, Local, NaiveDate, Timelike};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Percentages of the budget that trigger an alert by default.
+pub const DEFAULT_BUDGET_ALERTS: [u32; 3] = [80, 100, 120];
+
+/// How much work is fine for a day or a week, across all sessions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Seconds of work per day.
+    #[serde(default)]
+    pub daily: Option<u64>,
+    /// Seconds of work per week, starting on Monday.
+    #[serde(default)]
+    pub weekly: Option<u64>,
+    /// Percentages of the budget to alert at.
+    #[serde(default = "default_alerts")]
+    pub alerts: Vec<u32>,
+}
+
+fn default_alerts() -> Vec<u32> {
+    DEFAULT_BUDGET_ALERTS.to_vec()
+}
+
+impl BudgetConfig {
+    /// A config with the default alerts, or `None` when neither budget is set.
+    pub fn new(daily: Option<u64>, weekly: Option<u64>, alerts: Vec<u32>) -> Option<Self> {
+        if daily.is_none() && weekly.is_none() {
+            return None;
+        }
+        let alerts = if alerts.is_empty() {
+            default_alerts()
+        } else {
+            alerts
+        };
+        Some(BudgetConfig {
+            daily,
+            weekly,
+            alerts,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Weekly,
+}
+
+impl BudgetPeriod {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BudgetPeriod::Daily => "daily",
+            BudgetPeriod::Weekly => "weekly",
+        }
+    }
+}
+
+/// A budget threshold that was just crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BudgetAlert {
+    pub period: BudgetPeriod,
+    pub percent: u32,
+    pub worked_seconds: u64,
+    pub budget_seconds: u64,
+}
+
+/// Work counted against the budgets so far, including the open session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct BudgetStatus {
+    pub daily_budget: Option<u64>,
+    pub weekly_budget: Option<u64>,
+    pub worked_today: u64,
+    pub worked_this_week: u64,
+}
+
+/// Adds the logged work of the current day and week to the open session and
+/// reports each alert threshold once per period.
+///
+/// The log is only read again when it grows or the day changes, so checking on
+/// every monitor tick stays cheap.
+pub struct BudgetTracker {
+    config: BudgetConfig,
+    log_path: PathBuf,
+    day: Option<NaiveDate>,
+    log_len: Option<u64>,
+    logged_today: u64,
+    logged_this_week: u64,
+    daily_fired: Vec<u32>,
+    weekly_fired: Vec<u32>,
+    status: BudgetStatus,
+}
+
+impl BudgetTracker {
+    /// `log_path` is the log file itself, not its directory.
+    pub fn new(config: BudgetConfig, log_path: PathBuf) -> Self {
+        let status = BudgetStatus {
+            daily_budget: config.daily,
+            weekly_budget: config.weekly,
+            worked_today: 0,
+            worked_this_week: 0,
+        };
+        BudgetTracker {
+            config,
+            log_path,
+            day: None,
+            log_len: None,
+            logged_today: 0,
+            logged_this_week: 0,
+            daily_fired: Vec::new(),
+            weekly_fired: Vec::new(),
+            status,
+        }
+    }
+
+    pub fn status(&self) -> BudgetStatus {
+        self.status
+    }
+
+    /// Updates the totals at `now` with the session open since `session_start` and
+    /// returns the thresholds crossed since the last check. When several are crossed
+    /// at once, e.g. right after starting, only the highest one is reported.
+    pub fn check(
+        &mut self,
+        now: DateTime<Local>,
+        session_start: Option<DateTime<Local>>,
+    ) -> Vec<BudgetAlert> {
+        let today = now.date_naive();
+        if self.day != Some(today) {
+            if self
+                .day
+                .is_some_and(|day| week_start(day) != week_start(today))
+            {
+                self.weekly_fired.clear();
+            }
+            self.daily_fired.clear();
+            self.day = Some(today);
+            self.log_len = None;
+        }
+        self.refresh_logged(now);
+
+        let open_today = session_start
+            .map(|start| open_seconds(start.max(day_start(now)), now))
+            .unwrap_or_default();
+        let open_this_week = session_start
+            .map(|start| open_seconds(start.max(day_start(now) - days_into_week(now)), now))
+            .unwrap_or_default();
+        self.status.worked_today = self.logged_today + open_today;
+        self.status.worked_this_week = self.logged_this_week + open_this_week;
+
+        let mut alerts = Vec::new();
+        if let Some(budget) = self.config.daily {
+            alerts.extend(crossed(
+                BudgetPeriod::Daily,
+                &self.config.alerts,
+                &mut self.daily_fired,
+                self.status.worked_today,
+                budget,
+            ));
+        }
+        if let Some(budget) = self.config.weekly {
+            alerts.extend(crossed(
+                BudgetPeriod::Weekly,
+                &self.config.alerts,
+                &mut self.weekly_fired,
+                self.status.worked_this_week,
+                budget,
+            ));
+        }
+        alerts
+    }
+
+    // A missing or unreadable log simply counts as no logged work
+    fn refresh_logged(&mut self, now: DateTime<Local>) {
+        let log_len = std::fs::metadata(&self.log_path)
+            .map(|metadata| metadata.len())
+            .ok();
+        if log_len.is_some() && log_len == self.log_len {
+            return;
+        }
+        self.log_len = log_len;
+
+        self.logged_today = single_day_work_time(self.log_path.clone(), now)
+            .unwrap_or_default()
+            .max(0) as u64;
+        let week_start = now - days_into_week(now);
+        self.logged_this_week = acc_work_time(self.log_path.clone(), week_start, now)
+            .unwrap_or_default()
+            .max(0) as u64;
+    }
+}
+
+fn crossed(
+    period: BudgetPeriod,
+    alerts: &[u32],
+    fired: &mut Vec<u32>,
+    worked_seconds: u64,
+    budget_seconds: u64,
+) -> Option<BudgetAlert> {
+    let mut highest = None;
+    for &percent in alerts {
+        if fired.contains(&percent) || worked_seconds * 100 < budget_seconds * percent as u64 {
+            continue;
+        }
+        fired.push(percent);
+        highest = highest.max(Some(percent));
+    }
+    highest.map(|percent| BudgetAlert {
+        period,
+        percent,
+        worked_seconds,
+        budget_seconds,
+    })
+}
+
+pub(crate) fn day_start(now: DateTime<Local>) -> DateTime<Local> {
+    now - ChronoDuration::seconds(now.num_seconds_from_midnight() as i64)
+}
+
+fn days_into_week(now: DateTime<Local>) -> ChronoDuration {
+    ChronoDuration::days(now.weekday().num_days_from_monday() as i64)
+}
+
+fn week_start(day: NaiveDate) -> NaiveDate {
+    day - ChronoDuration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+fn open_seconds(from: DateTime<Local>, to: DateTime<Local>) -> u64 {
+    (to - from).num_seconds().max(0) as u64
+}
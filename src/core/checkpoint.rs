@@ -0,0 +1,196 @@
+use crate::core::probe::ProcessProbe;
+use crate::statistic::log_entry::LogEntry;
+use chrono::{DateTime, Local};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use tokio::time::{Duration, Instant};
+
+/// How often the open session is written to the sidecar file, in seconds.
+pub const CHECKPOINT_SECONDS: u64 = 30;
+
+/// How often the end of the open session is recorded between checkpoints, in seconds.
+pub const HEARTBEAT_SECONDS: u64 = CHECKPOINT_SECONDS / 3;
+
+// A checkpoint this old is orphaned even if its pid was reused by another process
+const STALE_AFTER_SECONDS: i64 = 10 * 60;
+
+/// The open session of a running monitor, as last written to disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// Process that owns the session.
+    pub pid: u32,
+    pub written_at: DateTime<Local>,
+    /// The session as it would be logged, ending at the last heartbeat.
+    pub entry: LogEntry,
+}
+
+/// Sidecar file next to the log, e.g. `focus_log.txt.checkpoint`.
+pub fn checkpoint_path(log_file: &Path) -> PathBuf {
+    let mut file_name = log_file.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".checkpoint");
+    log_file.with_file_name(file_name)
+}
+
+/// File next to the checkpoint that tells when its session was last seen, e.g.
+/// `focus_log.txt.checkpoint.seen`.
+pub fn last_seen_path(checkpoint: &Path) -> PathBuf {
+    let mut file_name = checkpoint.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".seen");
+    checkpoint.with_file_name(file_name)
+}
+
+// The end of the open session at the last heartbeat
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct LastSeen {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+}
+
+pub fn read_checkpoint(path: &Path) -> Result<Option<Checkpoint>, Box<dyn Error>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(serde_json::from_str(&content)?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Takes over the checkpoint at `path` if the monitor that wrote it is gone.
+///
+/// A checkpoint is orphaned when it was written by this process (a monitor that was
+/// aborted), by a process that no longer runs, or has not been refreshed for a
+/// while. The file is removed and the session it held is returned, ready to be
+/// logged, ending at the last heartbeat. Per-app times stay as of the checkpoint.
+/// Checkpoints of live monitors are left alone.
+pub fn recover_orphan(
+    path: &Path,
+    probe: &mut dyn ProcessProbe,
+    now: DateTime<Local>,
+) -> Result<Option<LogEntry>, Box<dyn Error>> {
+    let Some(checkpoint) = read_checkpoint(path)? else {
+        return Ok(None);
+    };
+
+    let own = checkpoint.pid == std::process::id();
+    let alive = !own
+        && probe
+            .processes()
+            .iter()
+            .any(|process| process.pid == checkpoint.pid);
+    let stale = (now - checkpoint.written_at).num_seconds() > STALE_AFTER_SECONDS;
+    if alive && !stale {
+        return Ok(None);
+    }
+
+    let mut entry = checkpoint.entry;
+    let seen_path = last_seen_path(path);
+    // Half written when the monitor died mid-write, the checkpoint's end stands
+    if let Some(seen) = std::fs::read_to_string(&seen_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<LastSeen>(&content).ok())
+        .filter(|seen| seen.start == entry.start && seen.end > entry.end)
+    {
+        entry.end = seen.end;
+        entry.duration_seconds = (seen.end - entry.start).num_seconds();
+    }
+    std::fs::remove_file(path)?;
+    let _ = std::fs::remove_file(&seen_path);
+    Ok(Some(entry))
+}
+
+/// Keeps the sidecar file in step with the open session.
+///
+/// A new session is written right away, a running one every [`CHECKPOINT_SECONDS`],
+/// and the file is removed once the session is closed and logged. In between, a
+/// heartbeat every [`HEARTBEAT_SECONDS`] only records when the session was last seen.
+pub struct Checkpointer {
+    path: PathBuf,
+    seen_path: PathBuf,
+    session_start: Option<DateTime<Local>>,
+    last_written: Option<Instant>,
+    last_seen_written: Option<Instant>,
+}
+
+impl Checkpointer {
+    pub fn new(path: PathBuf) -> Self {
+        Checkpointer {
+            seen_path: last_seen_path(&path),
+            path,
+            session_start: None,
+            last_written: None,
+            last_seen_written: None,
+        }
+    }
+
+    pub fn update(&mut self, entry: Option<LogEntry>, now: DateTime<Local>) {
+        let Some(entry) = entry else {
+            if self.session_start.take().is_some() {
+                self.last_written = None;
+                self.last_seen_written = None;
+                let _ = std::fs::remove_file(&self.path);
+                let _ = std::fs::remove_file(&self.seen_path);
+            }
+            return;
+        };
+
+        let due = self.session_start != Some(entry.start)
+            || self
+                .last_written
+                .is_none_or(|written| written.elapsed() >= Duration::from_secs(CHECKPOINT_SECONDS));
+        if !due {
+            let beat = self
+                .last_seen_written
+                .is_none_or(|written| written.elapsed() >= Duration::from_secs(HEARTBEAT_SECONDS));
+            if !beat {
+                return;
+            }
+            self.last_seen_written = Some(Instant::now());
+            let seen = LastSeen {
+                start: entry.start,
+                end: entry.end,
+            };
+            if let Err(e) = write_last_seen(&self.seen_path, &seen) {
+                println!(
+                    "{} {}",
+                    "Failed to write session heartbeat:".bright_red().bold(),
+                    e.to_string().red()
+                );
+            }
+            return;
+        }
+
+        self.session_start = Some(entry.start);
+        self.last_written = Some(Instant::now());
+        self.last_seen_written = self.last_written;
+        let checkpoint = Checkpoint {
+            pid: std::process::id(),
+            written_at: now,
+            entry,
+        };
+        if let Err(e) = write_checkpoint(&self.path, &checkpoint) {
+            println!(
+                "{} {}",
+                "Failed to write session checkpoint:".bright_red().bold(),
+                e.to_string().red()
+            );
+        }
+    }
+}
+
+// Written to a temporary file first, so a crash mid-write never leaves half a checkpoint
+fn write_checkpoint(path: &Path, checkpoint: &Checkpoint) -> Result<(), Box<dyn Error>> {
+    let mut temp_name = path.as_os_str().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = PathBuf::from(temp_name);
+
+    std::fs::write(&temp_path, serde_json::to_string(checkpoint)?)?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+// A torn write only loses the last heartbeat
+fn write_last_seen(path: &Path, seen: &LastSeen) -> Result<(), Box<dyn Error>> {
+    std::fs::write(path, serde_json::to_string(seen)?)?;
+    Ok(())
+}
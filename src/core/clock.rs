@@ -0,0 +1,56 @@
+use chrono::{DateTime, Local};
+use std::sync::Mutex;
+use tokio::time::Instant;
+
+/// Wall-clock source used for session timestamps.
+///
+/// Elapsed time inside the monitor is measured with `tokio::time::Instant`, which
+/// follows tokio's paused test time. The clock only decides which dates end up in
+/// the log and in plugin contexts.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Local>;
+}
+
+/// The real local time.
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Local> {
+        Local::now()
+    }
+}
+
+/// Wall clock that starts at a fixed date and advances with tokio's time.
+///
+/// Combined with `#[tokio::test(start_paused = true)]`, hours of monitoring run in
+/// milliseconds while the log still shows realistic timestamps.
+pub struct VirtualClock {
+    origin: DateTime<Local>,
+    started_at: Instant,
+    // How far the clock was moved past tokio's time
+    skipped: Mutex<chrono::Duration>,
+}
+
+impl VirtualClock {
+    pub fn starting_at(origin: DateTime<Local>) -> Self {
+        VirtualClock {
+            origin,
+            started_at: Instant::now(),
+            skipped: Mutex::new(chrono::Duration::zero()),
+        }
+    }
+
+    /// Moves the wall clock ahead while tokio's time stands still, like a machine
+    /// waking up from sleep.
+    pub fn skip(&self, by: chrono::Duration) {
+        *self.skipped.lock().unwrap() += by;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> DateTime<Local> {
+        let elapsed = chrono::Duration::from_std(self.started_at.elapsed())
+            .unwrap_or(chrono::Duration::zero());
+        self.origin + elapsed + *self.skipped.lock().unwrap()
+    }
+}
@@ -1,521 +1,848 @@
-use crate::plugin::plugin::{PluginContext, PluginManager};
+use crate::core::activity::{ActivityTracker, AppActivity};
+use crate::core::budget::{
+    BudgetAlert, BudgetConfig, BudgetPeriod, BudgetStatus, BudgetTracker, day_start,
+};
+use crate::core::checkpoint::{Checkpointer, checkpoint_path, recover_orphan};
+use crate::core::clock::{Clock, SystemClock};
+use crate::core::engine::{
+    MonitorEngine, MonitorEvent, MonitorState, PomodoroConfig, PomodoroPhase, PomodoroStatus,
+    Transition,
+};
+use crate::core::git_task::GitTaskConfig;
+use crate::core::hooks::{MonitorHooks, PluginHooks, ReminderAction};
+use crate::core::idle::{IdleDetector, IdleSource, system_idle_source};
+use crate::core::matcher::{AppRule, rule_names};
+use crate::core::messages::{MessageContext, Vars, catalog, tr, tr_with};
+use crate::core::notify::NotifyConfig;
+use crate::core::poll::PollIntervals;
+use crate::core::probe::{ProcessProbe, ScanDetails, SysinfoProbe};
+use crate::core::schedule::{AfterHours, Schedule};
+use crate::core::signals::{SignalRequest, StopSignal};
+use crate::core::suspend::{Heartbeat, SUSPEND_JUMP};
+use crate::core::task_rules::TaskRules;
+use crate::plugin::plugin::PluginContext;
 use crate::statistic::log_entry::LogEntry;
+use crate::statistic::statistics::{break_summary, single_day_work_time};
 use chrono::{DateTime, Local};
 use colored::*;
-use rand::Rng;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::fs::OpenOptions;
 use std::io::Write;
-use std::path::PathBuf;
-use std::time::Instant;
-use sysinfo::{ProcessesToUpdate, System};
-use tokio::sync::watch;
-use tokio::time::{Duration, interval};
-
-// Windows-specific imports
-#[cfg(windows)]
-use {
-    widestring::U16CString,
-    windows::Win32::Foundation::HWND,
-    windows::Win32::UI::WindowsAndMessaging::{MB_OK, MessageBoxW},
-    windows::core::PCWSTR,
-};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Duration, Instant, interval};
+
+/// Everything the monitor talks to outside of its own state.
+///
+/// [`MonitorDeps::system`] wires up the real process table, clock, idle
+/// counter, plugins and notification sinks. Tests swap in scripted
+/// implementations to drive a whole session in virtual time.
+pub struct MonitorDeps {
+    pub probe: Box<dyn ProcessProbe>,
+    pub clock: Arc<dyn Clock>,
+    pub hooks: Box<dyn MonitorHooks>,
+    pub idle_source: Box<dyn IdleSource>,
+}
 
-#[derive(Debug)]
-enum WorkSessionResult {
-    CtrlCPressed,
-    TimeReached,
-    ProcessEnded,
-    Paused,
+impl MonitorDeps {
+    pub fn system(notify: &NotifyConfig) -> Self {
+        MonitorDeps {
+            probe: Box::new(SysinfoProbe::new()),
+            clock: Arc::new(SystemClock),
+            hooks: Box::new(PluginHooks::new(notify)),
+            idle_source: system_idle_source(),
+        }
+    }
 }
 
-pub async fn run_rest_reminder(
-    mut log_location: PathBuf,
-    time: u64,
-    app: Vec<String>,
-    task: Option<String>,
-    mut pause_rx: Option<watch::Receiver<bool>>,
-) {
-    let mut sys = System::new_all();
-    let mut process_check_interval = interval(Duration::from_secs(1));
-    let mut msg_undiscovered_display_interval = interval(Duration::from_secs(2));
+/// What to monitor and where to log it.
+#[derive(Debug, Clone)]
+pub struct MonitorConfig {
+    pub log_location: PathBuf,
+    pub time: u64,
+    pub apps: Vec<AppRule>,
+    pub task: Option<String>,
+    /// Label sessions after the git branch the apps have open, `task` until one is found.
+    pub git_task: Option<GitTaskConfig>,
+    /// Rules assigning the task label, tried before the git branch and `task`.
+    pub task_rules: Option<TaskRules>,
+    pub idle_threshold: Option<u64>,
+    /// How long a snooze postpones the break reminder when no length is given, in seconds.
+    pub snooze: u64,
+    /// Snoozes allowed before a break is taken, unlimited if `None`.
+    pub max_snoozes: Option<u32>,
+    /// Breaks shorter than this many seconds are logged as violations.
+    pub min_break: Option<u64>,
+    /// Work in pomodoro cycles of `time` seconds instead of a single threshold.
+    pub pomodoro: Option<PomodoroConfig>,
+    /// Repeat an ignored break reminder every this many seconds, with rising urgency.
+    pub remind_every: Option<u64>,
+    /// Daily and weekly work budgets across sessions.
+    pub budget: Option<BudgetConfig>,
+    /// Seconds the processes must be seen before a session starts.
+    pub start_debounce: Option<u64>,
+    /// Absences shorter than this many seconds continue the session.
+    pub end_grace: Option<u64>,
+    /// Working hours, holidays and quiet hours.
+    pub schedule: Option<Schedule>,
+    /// How often the processes are checked.
+    pub poll: PollIntervals,
+    /// Where reminders and alerts are shown.
+    pub notify: NotifyConfig,
+}
 
-    let mut plugin_manager = PluginManager::new().unwrap_or_else(|e| {
-        println!(
-            "{} {}",
-            "Failed to initialize plugin manager:".bright_red().bold(),
-            e.to_string().red()
-        );
-        PluginManager::new().unwrap()
-    });
+/// Channels a caller can use to steer a running monitor and watch its state.
+/// The CLI feeds snoozes from the keyboard and Unix signals, the web server wires up
+/// everything else.
+#[derive(Default)]
+pub struct MonitorControl {
+    pub pause_rx: Option<watch::Receiver<bool>>,
+    pub app_rx: Option<watch::Receiver<Vec<AppRule>>>,
+    pub snooze_rx: Option<mpsc::UnboundedReceiver<Duration>>,
+    pub status_tx: Option<watch::Sender<MonitorStatus>>,
+    pub signal_rx: Option<mpsc::UnboundedReceiver<SignalRequest>>,
+    /// `true` when the machine is about to sleep, `false` once it woke up.
+    pub sleep_rx: Option<mpsc::UnboundedReceiver<bool>>,
+    /// Replaces `--task` for the sessions from now on, `None` leaves them unlabeled.
+    pub task_rx: Option<mpsc::UnboundedReceiver<Option<String>>>,
+}
 
-    if let Err(e) = plugin_manager.load_plugins("plugins") {
-        println!(
-            "{} {}",
-            "Failed to load plugins:".bright_red().bold(),
-            e.to_string().red()
-        );
+/// Snapshot of the monitor published after every transition.
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorStatus {
+    pub state: MonitorState,
+    pub task: Option<String>,
+    /// Name of the task rule that set the label, if one did.
+    pub task_rule: Option<String>,
+    pub session_started_at: Option<DateTime<Local>>,
+    pub reminder_pending: bool,
+    pub snoozes: u32,
+    pub snoozed_until: Option<DateTime<Local>>,
+    pub break_started_at: Option<DateTime<Local>>,
+    pub pomodoro: Option<PomodoroStatus>,
+    pub escalations: u32,
+    pub overdue_seconds: u64,
+    pub budget: Option<BudgetStatus>,
+    /// Outside the working hours of the schedule.
+    pub off_schedule: bool,
+    /// Reminders are held back, during quiet hours or silent after-hours work.
+    pub quiet: bool,
+    /// CPU and disk usage of the running apps, keyed by rule.
+    pub activity: BTreeMap<String, AppActivity>,
+}
+
+impl Default for MonitorStatus {
+    fn default() -> Self {
+        MonitorStatus {
+            state: MonitorState::Resting,
+            task: None,
+            task_rule: None,
+            session_started_at: None,
+            reminder_pending: false,
+            snoozes: 0,
+            snoozed_until: None,
+            break_started_at: None,
+            pomodoro: None,
+            escalations: 0,
+            overdue_seconds: 0,
+            budget: None,
+            off_schedule: false,
+            quiet: false,
+            activity: BTreeMap::new(),
+        }
     }
+}
+
+/// Runs the monitor until it is stopped by a signal, which is returned.
+pub async fn run_rest_reminder(config: MonitorConfig, control: MonitorControl) -> StopSignal {
+    run_rest_reminder_with(MonitorDeps::system(&config.notify), config, control).await
+}
+
+pub async fn run_rest_reminder_with(
+    deps: MonitorDeps,
+    config: MonitorConfig,
+    mut control: MonitorControl,
+) -> StopSignal {
+    let MonitorDeps {
+        mut probe,
+        clock,
+        mut hooks,
+        idle_source,
+    } = deps;
+    let mut idle = IdleDetector::new(idle_source, config.idle_threshold);
+    let mut engine = MonitorEngine::new(
+        clock.clone(),
+        config.time,
+        config.apps.clone(),
+        config.task.clone(),
+    )
+    .with_max_snoozes(config.max_snoozes)
+    .with_min_break(config.min_break)
+    .with_pomodoro(config.pomodoro)
+    .with_remind_every(config.remind_every)
+    .with_start_debounce(config.start_debounce)
+    .with_end_grace(config.end_grace)
+    .with_after_hours_time(
+        config
+            .schedule
+            .as_ref()
+            .and_then(|schedule| schedule.after_hours_threshold),
+    );
+    let mut budget = config
+        .budget
+        .clone()
+        .map(|budget| BudgetTracker::new(budget, log_file_path(&config.log_location)));
+    let mut task_rules = config.task_rules.clone();
+    // The label when no rule or checkout gives one
+    let mut default_task = config.task.clone();
+    let mut task_rule = None;
+    let mut plugin_snooze_rx = hooks.snooze_requests();
+    let mut reminder_action_rx = hooks.reminder_actions();
+    probe.set_details(scan_details(&config, engine.apps(), task_rules.as_ref()));
+    let mut checkpointer = recover_session(&config, probe.as_mut(), clock.as_ref());
+    let mut activity = ActivityTracker::new();
+    let mut heartbeat = Heartbeat::new(SUSPEND_JUMP + config.poll.longest());
+    // Between logind's sleep and wakeup signals nothing is checked
+    let mut asleep = false;
+    let mut next_check = Instant::now();
+    let mut msg_undiscovered_display_interval = interval(Duration::from_secs(2));
 
     // Trigger init hook
-    let init_context = PluginContext::new("Rest Reminder initialized", 0);
-    if let Err(e) = plugin_manager.trigger_hook("on_init", &init_context) {
-        println!(
-            "{} {}",
-            "Plugin hook error:".bright_red(),
-            e.to_string().red()
+    let init_context = PluginContext::at("Rest Reminder initialized", 0, clock.now());
+    hooks.trigger("on_init", &init_context);
+
+    if control
+        .pause_rx
+        .as_ref()
+        .is_some_and(|pause_rx| *pause_rx.borrow())
+    {
+        let transition = engine.handle(MonitorEvent::PauseToggled(true));
+        react(
+            &transition,
+            &engine,
+            &config,
+            clock.as_ref(),
+            hooks.as_mut(),
         );
     }
 
-    // Trace current state
-    let mut last_found_state = false;
-
     loop {
-        tokio::select! {
+        let mut stopped_by = None;
+        let event = tokio::select! {
             // Handle Ctrl+C gracefully
             press_ctrl_c_result = tokio::signal::ctrl_c() => {
-                match press_ctrl_c_result {
-                    Ok(_) => println!("{}", "Stopped monitoring".bright_yellow().bold()),
-                    Err(_) => panic!("Failed to start work session")
+                if press_ctrl_c_result.is_err() {
+                    panic!("Failed to start work session");
                 }
-                return;
+                stopped_by = Some(StopSignal::Interrupt);
+                MonitorEvent::Shutdown
             }
 
-            // Regular process checking
-            _ = process_check_interval.tick() => {
-                if is_paused(&pause_rx) {
-                    continue;
+            request = next_request(&mut control.signal_rx) => match request {
+                SignalRequest::Stop(signal) => {
+                    stopped_by = Some(signal);
+                    MonitorEvent::Shutdown
                 }
-
-                sys.refresh_processes(ProcessesToUpdate::All, true);
-                let found = sys.processes()
-                    .values()
-                    .any(|process|
-                        app.iter()
-                            .any(|software|
-                                process.name().to_string_lossy().contains(software)));
-
-                // Reset intervals when state changes
-                if found != last_found_state {
-                    if !found { msg_undiscovered_display_interval = interval(Duration::from_secs(2)); }
-                    last_found_state = found;
+                SignalRequest::TogglePause => {
+                    MonitorEvent::PauseToggled(engine.state() != MonitorState::Paused)
                 }
-
-                if found {
-                    let start = Local::now();
-                    println!("{}", "Process(es) detected, you are about to start working...".bright_green().bold());
-
-                    // Trigger work start hook
-                    let work_start_context = PluginContext::new("Work session started", 0);
-                    trigger_current_plugins(&mut plugin_manager, "on_work_start", &work_start_context);
-
-                    let start_time = Instant::now();
-
-                    // Monitor working session
-                    let work_session_result = monitor_work_session(&mut sys, &app, time, start_time, &mut pause_rx).await;
-                    match work_session_result {
-                        WorkSessionResult::CtrlCPressed => {
-                            log(start, Local::now(), &mut log_location, &app, task.as_deref());
-                            println!("{}", "Stopped monitoring".bright_yellow().bold());
-                            return;
-                        }
-                        WorkSessionResult::TimeReached => {
-                            println!("{}", "Process(es) still running, you need a break!".bright_red().bold());
-
-                            // Trigger break hook
-                            let break_context = PluginContext::new("Time to take a break!", time);
-                            trigger_current_plugins(&mut plugin_manager, "on_break_reminder", &break_context);
-
-                            pop_up(time).await;
-                            log(start, Local::now(), &mut log_location, &app, task.as_deref());
-                        }
-                        WorkSessionResult::ProcessEnded => {
-                            log(start, Local::now(), &mut log_location, &app, task.as_deref());
-                            println!("{}", "Process(es) ended, you finally decide to rest...".bright_blue().bold());
-                            last_found_state = false;
-                        }
-                        WorkSessionResult::Paused => {
-                            log(start, Local::now(), &mut log_location, &app, task.as_deref());
-                            println!("{}", "Monitoring paused".bright_yellow().bold());
-                            wait_until_resumed(&mut pause_rx).await;
-                            last_found_state = false;
-                        }
+                SignalRequest::Reload => {
+                    hooks.reload();
+                    if let Some(Err(e)) = task_rules.as_mut().map(TaskRules::reload) {
+                        println!(
+                            "{} {}",
+                            "Failed to reload task rules:".bright_red().bold(),
+                            e.red()
+                        );
                     }
+                    println!("{}", tr("monitor.reloaded").bright_yellow());
+                    continue;
                 }
-            }
+            },
 
-            _ = msg_undiscovered_display_interval.tick(), if !last_found_state => {
-                println!("{}", "No processes detected, you are resting...".cyan());
+            sleeping = next_request(&mut control.sleep_rx) => {
+                asleep = sleeping;
+                heartbeat.reset();
+                if !sleeping {
+                    continue;
+                }
+                MonitorEvent::Suspended(clock.now())
             }
-        }
-    }
-}
 
-pub async fn run_rest_reminder_dynamic(
-    mut log_location: PathBuf,
-    time: u64,
-    initial_apps: Vec<String>,
-    task: Option<String>,
-    mut pause_rx: Option<watch::Receiver<bool>>,
-    mut app_rx: watch::Receiver<Vec<String>>,
-) {
-    let mut sys = System::new_all();
-    let mut process_check_interval = interval(Duration::from_secs(1));
-    let mut msg_undiscovered_display_interval = interval(Duration::from_secs(2));
+            paused = next_value(&mut control.pause_rx) => MonitorEvent::PauseToggled(paused),
 
-    let mut plugin_manager = PluginManager::new().unwrap_or_else(|e| {
-        println!(
-            "{} {}",
-            "Failed to initialize plugin manager:".bright_red().bold(),
-            e.to_string().red()
-        );
-        PluginManager::new().unwrap()
-    });
+            apps = next_value(&mut control.app_rx) => MonitorEvent::AppsChanged(apps),
 
-    if let Err(e) = plugin_manager.load_plugins("plugins") {
-        println!(
-            "{} {}",
-            "Failed to load plugins:".bright_red().bold(),
-            e.to_string().red()
-        );
-    }
+            task = next_request(&mut control.task_rx) => {
+                default_task = task.clone();
+                MonitorEvent::TaskChanged(task)
+            }
 
-    let init_context = PluginContext::new("Rest Reminder initialized", 0);
-    if let Err(e) = plugin_manager.trigger_hook("on_init", &init_context) {
-        println!(
-            "{} {}",
-            "Plugin hook error:".bright_red(),
-            e.to_string().red()
-        );
-    }
+            snooze_for = next_request(&mut control.snooze_rx) => MonitorEvent::Snooze(snooze_for),
 
-    let mut current_apps = initial_apps;
-    let mut last_found_state = false;
+            snooze_for = next_request(&mut plugin_snooze_rx) => MonitorEvent::Snooze(snooze_for),
 
-    loop {
-        tokio::select! {
-            press_ctrl_c_result = tokio::signal::ctrl_c() => {
-                match press_ctrl_c_result {
-                    Ok(_) => println!("{}", "Stopped monitoring".bright_yellow().bold()),
-                    Err(_) => panic!("Failed to start work session")
-                }
-                return;
-            }
+            action = next_request(&mut reminder_action_rx) => match action {
+                ReminderAction::TakeBreak => MonitorEvent::TakeBreak,
+                ReminderAction::Snooze => MonitorEvent::Snooze(Duration::from_secs(config.snooze)),
+            },
 
-            changed = app_rx.changed() => {
-                if changed.is_err() {
-                    return;
-                }
-                current_apps = app_rx.borrow().clone();
-                if current_apps.is_empty() {
-                    last_found_state = false;
+            // Regular process checking
+            _ = tokio::time::sleep_until(next_check) => {
+                let previous = engine.state();
+                // A jump of the wall clock means the machine slept through the checks
+                if let Some(since) = heartbeat.beat(clock.now()) {
+                    asleep = false;
+                    let transition = engine.handle(MonitorEvent::Suspended(since));
+                    react(&transition, &engine, &config, clock.as_ref(), hooks.as_mut());
                 }
-            }
-
-            _ = process_check_interval.tick() => {
-                if is_paused(&pause_rx) || current_apps.is_empty() {
+                if asleep || engine.state() == MonitorState::Paused {
+                    next_check = Instant::now() + config.poll.next_check(engine.state(), None);
                     continue;
                 }
 
-                sys.refresh_processes(ProcessesToUpdate::All, true);
-                let found = any_monitored_process_running(&sys, &current_apps);
-
-                if found != last_found_state {
-                    if !found { msg_undiscovered_display_interval = interval(Duration::from_secs(2)); }
-                    last_found_state = found;
+                probe.set_details(scan_details(&config, engine.apps(), task_rules.as_ref()));
+                let processes = probe.processes();
+                let current_user = probe.current_user();
+                let running = activity.update(&processes, engine.apps(), current_user.as_deref());
+                let seen = !running.is_empty();
+                engine.observe_apps(running);
+                if let Some(schedule) = &config.schedule {
+                    let now = clock.now();
+                    let transition = engine.handle(MonitorEvent::ScheduleChanged {
+                        off_schedule: !schedule.is_working_time(now),
+                        quiet: schedule.reminders_muted(now),
+                    });
+                    react(&transition, &engine, &config, clock.as_ref(), hooks.as_mut());
                 }
-
-                if found {
-                    let start = Local::now();
-                    println!("{}", "Process(es) detected, you are about to start working...".bright_green().bold());
-
-                    let work_start_context = PluginContext::new("Work session started", 0);
-                    trigger_current_plugins(&mut plugin_manager, "on_work_start", &work_start_context);
-
-                    let start_time = Instant::now();
-                    let work_session_result = monitor_work_session_dynamic(
-                        &mut sys,
-                        &mut current_apps,
-                        time,
-                        start_time,
-                        &mut pause_rx,
-                        &mut app_rx,
-                    ).await;
-
-                    match work_session_result {
-                        WorkSessionResult::CtrlCPressed => {
-                            log(start, Local::now(), &mut log_location, &current_apps, task.as_deref());
-                            println!("{}", "Stopped monitoring".bright_yellow().bold());
-                            return;
-                        }
-                        WorkSessionResult::TimeReached => {
-                            println!("{}", "Process(es) still running, you need a break!".bright_red().bold());
-
-                            let break_context = PluginContext::new("Time to take a break!", time);
-                            trigger_current_plugins(&mut plugin_manager, "on_break_reminder", &break_context);
-
-                            pop_up(time).await;
-                            log(start, Local::now(), &mut log_location, &current_apps, task.as_deref());
-                        }
-                        WorkSessionResult::ProcessEnded => {
-                            log(start, Local::now(), &mut log_location, &current_apps, task.as_deref());
-                            println!("{}", "Process(es) ended, you finally decide to rest...".bright_blue().bold());
-                            last_found_state = false;
-                        }
-                        WorkSessionResult::Paused => {
-                            log(start, Local::now(), &mut log_location, &current_apps, task.as_deref());
-                            println!("{}", "Monitoring paused".bright_yellow().bold());
-                            wait_until_resumed(&mut pause_rx).await;
-                            last_found_state = false;
-                        }
+                // A new task ends the session before anything else happens to it
+                let fired = task_rules.as_ref().and_then(|rules| {
+                    rules.fired(&processes, clock.now(), current_user.as_deref())
+                });
+                let task = match fired {
+                    Some(rule) => Some(rule.task.clone()),
+                    None => config
+                        .git_task
+                        .as_ref()
+                        .and_then(|git_task| {
+                            git_task.resolve(&processes, engine.apps(), current_user.as_deref())
+                        })
+                        // Once no rule fires any more, sessions go back to `--task`
+                        .or_else(|| task_rules.as_ref().map(|_| default_task.clone())),
+                };
+                let fired = fired.map(|rule| rule.name.clone());
+                if fired != task_rule {
+                    if let Some(name) = &fired {
+                        println!(
+                            "{}",
+                            tr_with("monitor.task_rule", &Vars::new().set("rule", name)).cyan()
+                        );
+                    }
+                    task_rule = fired;
+                }
+                if let Some(task) = task {
+                    let transition = engine.handle(MonitorEvent::TaskChanged(task));
+                    react(&transition, &engine, &config, clock.as_ref(), hooks.as_mut());
+                }
+                let observed = match (seen, idle.exceeded()) {
+                    (false, _) => MonitorEvent::ProcessGone,
+                    (true, Some(idle_for)) => MonitorEvent::InputIdle(idle_for),
+                    (true, None) => MonitorEvent::ProcessSeen,
+                };
+
+                let transition = engine.handle(observed);
+                react(&transition, &engine, &config, clock.as_ref(), hooks.as_mut());
+                let transition = engine.handle(MonitorEvent::Tick);
+                react(&transition, &engine, &config, clock.as_ref(), hooks.as_mut());
+                if let Some(budget) = budget.as_mut() {
+                    for alert in budget.check(clock.now(), engine.session_start()) {
+                        budget_reached(&alert, &engine, &config, clock.as_ref(), hooks.as_mut());
                     }
                 }
+                publish(&control.status_tx, &engine, &budget, &task_rule, &activity);
+                checkpointer.update(engine.session_entry(), clock.now());
+
+                // Check again right away when the state changes
+                next_check = Instant::now();
+                if engine.state() != previous {
+                    if engine.state() != MonitorState::Working {
+                        msg_undiscovered_display_interval = interval(Duration::from_secs(2));
+                    }
+                } else {
+                    next_check += config.poll.next_check(engine.state(), engine.until_reminder());
+                }
+                continue;
             }
 
-            _ = msg_undiscovered_display_interval.tick(), if !last_found_state => {
-                println!("{}", "No processes detected, you are resting...".cyan());
+            _ = msg_undiscovered_display_interval.tick(),
+                if matches!(engine.state(), MonitorState::Resting | MonitorState::OnBreak) => {
+                println!("{}", tr("monitor.resting").cyan());
+                continue;
             }
+        };
+
+        let previous = engine.state();
+        let transition = engine.handle(event);
+        react(
+            &transition,
+            &engine,
+            &config,
+            clock.as_ref(),
+            hooks.as_mut(),
+        );
+        publish(&control.status_tx, &engine, &budget, &task_rule, &activity);
+        checkpointer.update(engine.session_entry(), clock.now());
+
+        if let Some(signal) = stopped_by {
+            shut_down(signal, &transition, clock.as_ref(), hooks.as_mut());
+            return signal;
+        }
+        if engine.state() != previous {
+            next_check = Instant::now();
         }
     }
 }
 
-async fn monitor_work_session(
-    sys: &mut System,
-    app: &[String],
-    time: u64,
-    start_time: Instant,
-    pause_rx: &mut Option<watch::Receiver<bool>>,
-) -> WorkSessionResult {
-    let mut heartbeat = interval(Duration::from_secs(2));
-
-    loop {
-        tokio::select! {
-            // Handle Ctrl+C during work session
-            _ = tokio::signal::ctrl_c() => {
-                return WorkSessionResult::CtrlCPressed;
-            }
-
-            // Regular heartbeat check
-            _ = heartbeat.tick() => {
-                if is_paused(pause_rx) {
-                    return WorkSessionResult::Paused;
-                }
+fn shut_down(
+    signal: StopSignal,
+    transition: &Option<Transition>,
+    clock: &dyn Clock,
+    hooks: &mut dyn MonitorHooks,
+) {
+    println!(
+        "{}",
+        tr_with("monitor.stopped", &Vars::new().set("signal", signal.name()))
+            .bright_yellow()
+            .bold()
+    );
 
-                sys.refresh_processes(ProcessesToUpdate::All, true);
-                let still_running = sys.processes()
-                    .values()
-                    .any(|process|
-                        app.iter()
-                            .any(|software|
-                                process.name().to_string_lossy().contains(software)));
+    // Trigger shutdown hook, `work_duration` carries the session that was just logged
+    let worked = transition
+        .as_ref()
+        .and_then(|transition| transition.finished.as_ref())
+        .filter(|entry| entry.is_work())
+        .map(|entry| entry.duration_seconds.max(0) as u64)
+        .unwrap_or_default();
+    let shutdown_context = PluginContext::at(
+        &format!("Rest Reminder stopped by {}", signal.name()),
+        worked,
+        clock.now(),
+    );
+    hooks.trigger("on_shutdown", &shutdown_context);
+}
 
-                if !still_running {
-                    return WorkSessionResult::ProcessEnded;
-                }
+// Logs the session a killed or crashed monitor left behind, then takes over its checkpoint
+fn recover_session(
+    config: &MonitorConfig,
+    probe: &mut dyn ProcessProbe,
+    clock: &dyn Clock,
+) -> Checkpointer {
+    let path = checkpoint_path(&log_file_path(&config.log_location));
+    recover_checkpoint(&path, &config.log_location, probe, clock);
+    Checkpointer::new(path)
+}
 
-                let elapsed = start_time.elapsed();
-                if elapsed.as_secs() >= time {
-                    return WorkSessionResult::TimeReached;
-                }
-            }
-        }
-    }
+/// Logs the session a killed monitor left in the checkpoint of `log_location`.
+///
+/// Monitors do this when they start; the web server does it on startup, so a
+/// session is not left unlogged until the next monitor on the same log.
+pub fn recover_orphaned_session(log_location: &Path) {
+    let path = checkpoint_path(&log_file_path(log_location));
+    recover_checkpoint(&path, log_location, &mut SysinfoProbe::new(), &SystemClock);
 }
 
-fn trigger_current_plugins(
-    plugin_manager: &mut PluginManager,
-    hook_name: &str,
-    context: &PluginContext,
+fn recover_checkpoint(
+    path: &Path,
+    log_location: &Path,
+    probe: &mut dyn ProcessProbe,
+    clock: &dyn Clock,
 ) {
-    if let Err(e) = plugin_manager.load_plugins("plugins") {
-        println!(
+    match recover_orphan(path, probe, clock.now()) {
+        Ok(Some(entry)) => {
+            println!(
+                "{}",
+                tr_with(
+                    "monitor.recovered",
+                    &Vars::new()
+                        .set("start", entry.start.format("%Y-%m-%d %H:%M:%S"))
+                        .set("end", entry.end.format("%Y-%m-%d %H:%M:%S"))
+                )
+                .bright_yellow()
+                .bold()
+            );
+            log(&entry, log_location);
+        }
+        Ok(None) => {}
+        Err(e) => println!(
             "{} {}",
-            "Failed to reload plugins:".bright_red().bold(),
+            "Failed to recover session checkpoint:".bright_red().bold(),
             e.to_string().red()
-        );
+        ),
     }
+}
 
-    if let Err(e) = plugin_manager.trigger_hook(hook_name, context) {
-        println!(
-            "{} {}",
-            "Plugin hook error:".bright_red(),
-            e.to_string().red()
-        );
+// Scans read only what the rules look at
+fn scan_details(
+    config: &MonitorConfig,
+    apps: &[AppRule],
+    task_rules: Option<&TaskRules>,
+) -> ScanDetails {
+    let details = apps.iter().fold(ScanDetails::default(), |details, rule| {
+        details | rule.scan_details()
+    });
+    let details = match &config.git_task {
+        Some(git_task) => details | git_task.scan_details(),
+        None => details,
+    };
+    match task_rules {
+        Some(task_rules) => details | task_rules.scan_details(),
+        None => details,
     }
 }
 
-async fn monitor_work_session_dynamic(
-    sys: &mut System,
-    app: &mut Vec<String>,
-    time: u64,
-    start_time: Instant,
-    pause_rx: &mut Option<watch::Receiver<bool>>,
-    app_rx: &mut watch::Receiver<Vec<String>>,
-) -> WorkSessionResult {
-    let mut heartbeat = interval(Duration::from_secs(2));
+// Logging, plugin hooks and reminders all hang off state transitions
+fn react(
+    transition: &Option<Transition>,
+    engine: &MonitorEngine,
+    config: &MonitorConfig,
+    clock: &dyn Clock,
+    hooks: &mut dyn MonitorHooks,
+) {
+    let Some(transition) = transition else {
+        return;
+    };
 
-    loop {
-        tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                return WorkSessionResult::CtrlCPressed;
-            }
+    if let Some(entry) = &transition.finished {
+        log(entry, &config.log_location);
+        if !entry.is_work() {
+            break_ended(entry, config, clock, hooks);
+        }
+    }
 
-            changed = app_rx.changed() => {
-                if changed.is_err() {
-                    return WorkSessionResult::ProcessEnded;
-                }
-                *app = app_rx.borrow().clone();
-                if app.is_empty() {
-                    return WorkSessionResult::ProcessEnded;
-                }
+    if transition.cause == MonitorEvent::BreakOver {
+        println!("{}", tr("monitor.break_over").bright_green().bold());
+
+        // Trigger break over hook
+        let break_over_context = PluginContext::at("Pomodoro break is over", 0, clock.now());
+        hooks.trigger("on_break_over", &break_over_context);
+    }
+
+    match (transition.to, &transition.cause) {
+        (_, MonitorEvent::TaskChanged(task)) => {
+            let message = match task {
+                Some(task) => tr_with("monitor.task_changed", &Vars::new().set("task", task)),
+                None => tr("monitor.task_cleared"),
+            };
+            println!("{}", message.bright_cyan().bold());
+        }
+        (_, MonitorEvent::ScheduleChanged { off_schedule, .. }) => {
+            if *off_schedule {
+                println!("{}", tr("monitor.off_schedule").bright_cyan().bold());
+                after_hours(engine, config, clock, hooks);
+            } else {
+                println!("{}", tr("monitor.on_schedule").bright_cyan().bold());
+            }
+        }
+        (MonitorState::BreakDue, MonitorEvent::ReminderEscalated) => {
+            let overdue = engine.overdue_seconds();
+            let level = engine.escalations();
+            let message = tr_with(
+                "monitor.escalated",
+                &Vars::new().set("overdue", catalog().duration(overdue)),
+            );
+            if level > 1 {
+                println!("{}", message.bright_red().bold().on_yellow());
+            } else {
+                println!("{}", message.bright_red().bold());
             }
 
-            _ = heartbeat.tick() => {
-                if is_paused(pause_rx) {
-                    return WorkSessionResult::Paused;
-                }
+            // Trigger escalation hook, `work_duration` carries the overdue seconds
+            let escalated_context = PluginContext::at(
+                &format!(
+                    "Break overdue by {} seconds, reminder repeated {} time(s)",
+                    overdue, level
+                ),
+                overdue,
+                clock.now(),
+            );
+            hooks.trigger("on_reminder_escalated", &escalated_context);
+
+            hooks.escalate(overdue, level, &message_context(engine, config, clock));
+        }
+        (MonitorState::Working, _) => {
+            println!("{}", tr("monitor.work_started").bright_green().bold());
 
-                sys.refresh_processes(ProcessesToUpdate::All, true);
-                if !any_monitored_process_running(sys, app) {
-                    return WorkSessionResult::ProcessEnded;
-                }
+            // Trigger work start hook
+            let work_start_context = PluginContext::at("Work session started", 0, clock.now());
+            hooks.trigger("on_work_start", &work_start_context);
 
-                let elapsed = start_time.elapsed();
-                if elapsed.as_secs() >= time {
-                    return WorkSessionResult::TimeReached;
-                }
+            hooks.work_started(&message_context(engine, config, clock));
+            if engine.off_schedule() {
+                after_hours(engine, config, clock, hooks);
             }
         }
+        (MonitorState::BreakDue, _) => {
+            println!("{}", tr("monitor.break_due").bright_red().bold());
+
+            if let Some(pomodoro) = engine.pomodoro_status() {
+                let key = match pomodoro.phase {
+                    PomodoroPhase::LongBreak => "monitor.pomodoro_long_break",
+                    _ => "monitor.pomodoro_short_break",
+                };
+                let vars = Vars::new()
+                    .set("cycle", pomodoro.cycle)
+                    .set("duration", catalog().duration(pomodoro.remaining_seconds));
+                println!("{}", tr_with(key, &vars).bright_red().bold());
+            }
+
+            // Trigger break hook
+            let break_context =
+                PluginContext::at("Time to take a break!", engine.reminder_time(), clock.now());
+            hooks.trigger("on_break_reminder", &break_context);
+
+            if engine.snooze_limit_reached() {
+                println!(
+                    "{}",
+                    tr("monitor.no_snoozes").bright_red().bold().on_yellow()
+                );
+
+                let limit_context = PluginContext::at(
+                    &format!("Snoozed {} time(s), no snoozes left", engine.snoozes()),
+                    engine.reminder_time(),
+                    clock.now(),
+                );
+                hooks.trigger("on_snooze_limit", &limit_context);
+            }
+
+            hooks.remind(
+                engine.reminder_time(),
+                &message_context(engine, config, clock),
+            );
+        }
+        (MonitorState::Snoozed, MonitorEvent::Snooze(snooze_for)) => {
+            let vars = Vars::new().set("duration", catalog().duration(snooze_for.as_secs()));
+            println!(
+                "{}",
+                tr_with("monitor.snoozed", &vars).bright_yellow().bold()
+            );
+
+            let snooze_context = PluginContext::at(
+                &format!(
+                    "Break reminder snoozed for {} seconds",
+                    snooze_for.as_secs()
+                ),
+                engine.reminder_time(),
+                clock.now(),
+            );
+            hooks.trigger("on_break_snoozed", &snooze_context);
+        }
+        (MonitorState::Paused, _) => {
+            println!("{}", tr("monitor.paused").bright_yellow().bold());
+        }
+        (MonitorState::Resting, MonitorEvent::Suspended(since)) => {
+            let vars = Vars::new().set("time", since.format("%Y-%m-%d %H:%M:%S"));
+            println!(
+                "{}",
+                tr_with("monitor.suspended", &vars).bright_blue().bold()
+            );
+        }
+        (MonitorState::Resting, MonitorEvent::InputIdle(_)) => {
+            println!("{}", tr("monitor.away").bright_blue().bold());
+        }
+        (MonitorState::Resting, MonitorEvent::ProcessGone)
+        | (MonitorState::Resting, MonitorEvent::AppsChanged(_)) => {
+            println!("{}", tr("monitor.rest_started").bright_blue().bold());
+        }
+        (MonitorState::OnBreak, cause) => {
+            let message = match cause {
+                MonitorEvent::TakeBreak => tr("monitor.break_started"),
+                _ => tr("monitor.rest_started"),
+            };
+            println!("{}", message.bright_blue().bold());
+
+            // Trigger break start hook
+            let break_start_context = PluginContext::at("Break started", 0, clock.now());
+            hooks.trigger("on_break_start", &break_start_context);
+        }
+        _ => {}
     }
 }
 
-fn any_monitored_process_running(sys: &System, app: &[String]) -> bool {
-    sys.processes().values().any(|process| {
-        app.iter()
-            .any(|software| process.name().to_string_lossy().contains(software))
-    })
-}
+fn break_ended(
+    entry: &LogEntry,
+    config: &MonitorConfig,
+    clock: &dyn Clock,
+    hooks: &mut dyn MonitorHooks,
+) {
+    let seconds = entry.duration_seconds.max(0) as u64;
+    let message = if entry.violation {
+        let catalog = catalog();
+        let vars = Vars::new().set("rested", catalog.duration(seconds)).set(
+            "min_break",
+            catalog.duration(config.min_break.unwrap_or_default()),
+        );
+        println!(
+            "{}",
+            catalog
+                .render("monitor.break_too_short", &vars)
+                .bright_red()
+                .bold()
+        );
+        format!("Break ended too early after {} seconds", seconds)
+    } else {
+        format!("Break ended after {} seconds", seconds)
+    };
 
-fn is_paused(pause_rx: &Option<watch::Receiver<bool>>) -> bool {
-    pause_rx.as_ref().is_some_and(|pause_rx| *pause_rx.borrow())
+    // Trigger break end hook, `work_duration` carries the break length
+    let break_end_context = PluginContext::at(&message, seconds, clock.now());
+    hooks.trigger("on_break_end", &break_end_context);
 }
 
-async fn wait_until_resumed(pause_rx: &mut Option<watch::Receiver<bool>>) {
-    let Some(pause_rx) = pause_rx else {
+// Work going on outside the working hours, nudged about if the schedule asks for it
+fn after_hours(
+    engine: &MonitorEngine,
+    config: &MonitorConfig,
+    clock: &dyn Clock,
+    hooks: &mut dyn MonitorHooks,
+) {
+    if config
+        .schedule
+        .as_ref()
+        .is_none_or(|schedule| schedule.after_hours != AfterHours::Nudge)
+    {
         return;
-    };
-
-    while *pause_rx.borrow() {
-        if pause_rx.changed().await.is_err() {
-            return;
-        }
     }
-}
+    println!("{}", tr("monitor.after_hours").bright_magenta().bold());
 
-async fn pop_up(time: u64) {
-    let slogans = HashMap::from([
-        (
-            0,
-            format!(
-                "{time} seconds NON-STOOOOOOOOOOOOP! YOU MUST BE TIRED! STAND UP AND TAKE A BREAK!!!!!!!"
-            ),
-        ),
-        (
-            1,
-            format!(
-                "{time} seconds OF UNSTOPPABLE GRIND! YOUR LEGS ARE CRYING FOR A BREAK! STAND UP AND SHAKE IT OFF!!!"
-            ),
-        ),
-        (
-            2,
-            format!(
-                "{time} seconds STRAIGHT LIKE A NINJA! YOUR BACK IS REBELLING! POWER UP WITH A QUICK STAND-UP BREAK!!!"
-            ),
-        ),
-        (
-            3,
-            format!(
-                "{time} seconds WITHOUT PAUSE! ALERT: MUSCLES ON STRIKE! RISE AND RELEASE WITH A STRETCH!!!"
-            ),
-        ),
-        (
-            4,
-            format!(
-                "{time} seconds NONSTOP MODE ENGAGED! WARNING: BRAIN FOG IMMINENT! HIT THE PAUSE AND STAND TALL!!!"
-            ),
-        ),
-        (
-            5,
-            format!(
-                "{time} seconds AND COUNTING! MISSION: TAKE A BREAK! DEPLOY YOUR LEGS FOR A STAND-UP MISSION!!!"
-            ),
-        ),
-    ]);
+    // Trigger after hours hook
+    let after_hours_context = PluginContext::at("Working outside working hours", 0, clock.now());
+    hooks.trigger("on_after_hours", &after_hours_context);
+
+    hooks.after_hours(&message_context(engine, config, clock));
+}
 
-    let rng = rand::rng().random_range(0..slogans.len());
-    let message_string = match slogans.get(&rng) {
-        Some(slogan) => slogan.to_string(),
-        None => panic!("Index out of bounds!"),
+fn budget_reached(
+    alert: &BudgetAlert,
+    engine: &MonitorEngine,
+    config: &MonitorConfig,
+    clock: &dyn Clock,
+    hooks: &mut dyn MonitorHooks,
+) {
+    let catalog = catalog();
+    let period = match alert.period {
+        BudgetPeriod::Daily => "budget.daily",
+        BudgetPeriod::Weekly => "budget.weekly",
     };
+    let vars = Vars::new()
+        .set("worked", catalog.duration(alert.worked_seconds))
+        .set("budget", catalog.duration(alert.budget_seconds))
+        .set("period", catalog.text(period))
+        .set("percent", alert.percent);
+    let message = catalog.render("monitor.budget", &vars);
+    if alert.percent >= 100 {
+        println!("{}", message.bright_magenta().bold().on_white());
+    } else {
+        println!("{}", message.bright_magenta().bold());
+    }
 
-    #[cfg(windows)]
-    show_popup_windows(&message_string).await;
+    // Trigger budget hook, `work_duration` carries the work done in the period
+    let budget_context = PluginContext::at(
+        &format!(
+            "You have worked {:.1} of your {:.1} {} hours ({}% of the budget)",
+            alert.worked_seconds as f64 / 3600.0,
+            alert.budget_seconds as f64 / 3600.0,
+            alert.period.name(),
+            alert.percent
+        ),
+        alert.worked_seconds,
+        clock.now(),
+    );
+    hooks.trigger("on_budget_reached", &budget_context);
 
-    #[cfg(target_os = "macos")]
-    show_popup_macos(&message_string).await;
+    hooks.budget_reached(alert, &message_context(engine, config, clock));
+}
 
-    #[cfg(not(any(windows, target_os = "macos")))]
-    {
-        println!(
-            "{}",
-            format!("ALERT: {}", message_string)
-                .bright_red()
-                .bold()
-                .on_yellow()
-        );
-        println!("{}", format!("Rest reminder: You have been working continuously for {} seconds, it's time to take a break!", time).bright_magenta().bold());
+// What the reminder templates may mention besides the event itself. Today's
+// totals come from the log plus the part of the open session since midnight.
+fn message_context(
+    engine: &MonitorEngine,
+    config: &MonitorConfig,
+    clock: &dyn Clock,
+) -> MessageContext {
+    let now = clock.now();
+    let path = log_file_path(&config.log_location);
+    let logged = single_day_work_time(path.clone(), now).unwrap_or_default();
+    let open = engine
+        .session_entry()
+        .map(|entry| (entry.end - entry.start.max(day_start(now))).num_seconds())
+        .unwrap_or_default();
+    let breaks_today = break_summary(path, now, now)
+        .map(|summary| summary.breaks)
+        .unwrap_or_default();
+
+    MessageContext {
+        task: engine.task().map(str::to_string),
+        apps: rule_names(engine.apps()),
+        worked_today: (logged + open).max(0) as u64,
+        breaks_today,
     }
 }
 
-#[cfg(windows)]
-async fn show_popup_windows(message: &str) {
-    let title = U16CString::from_str("REST REMINDEEEEEEEEEEEEEEEEER").unwrap();
-    let message = U16CString::from_str(message).unwrap();
-
-    // Windows API is synchronous, so run it in a background thread
-    tokio::task::spawn_blocking(move || unsafe {
-        MessageBoxW(
-            HWND(0),
-            PCWSTR(message.as_ptr()),
-            PCWSTR(title.as_ptr()),
-            MB_OK,
-        );
-    })
-    .await
-    .expect("Failed to show popup");
+fn publish(
+    status_tx: &Option<watch::Sender<MonitorStatus>>,
+    engine: &MonitorEngine,
+    budget: &Option<BudgetTracker>,
+    task_rule: &Option<String>,
+    activity: &ActivityTracker,
+) {
+    if let Some(status_tx) = status_tx {
+        status_tx.send_replace(MonitorStatus {
+            state: engine.state(),
+            task: engine.task().map(str::to_string),
+            task_rule: task_rule.clone(),
+            session_started_at: engine.session_start(),
+            reminder_pending: engine.reminder_pending(),
+            snoozes: engine.snoozes(),
+            snoozed_until: engine.snoozed_until(),
+            break_started_at: engine.break_start(),
+            pomodoro: engine.pomodoro_status(),
+            escalations: engine.escalations(),
+            overdue_seconds: engine.overdue_seconds(),
+            budget: budget.as_ref().map(BudgetTracker::status),
+            off_schedule: engine.off_schedule(),
+            quiet: engine.quiet(),
+            activity: activity.figures().clone(),
+        });
+    }
 }
 
-#[cfg(target_os = "macos")]
-async fn show_popup_macos(message: &str) {
-    let script = format!(
-        r#"display dialog "{}" with title "REST REMINDEEEEEEEEEEEEEEEEER" buttons {{"OK"}} default button "OK""#,
-        message.replace("\"", "\\\"")
-    );
+// Resolves with the new value whenever the channel changes. Missing or closed
+// channels never resolve, so they simply drop out of the `select!`.
+async fn next_value<T: Clone>(rx: &mut Option<watch::Receiver<T>>) -> T {
+    if let Some(receiver) = rx {
+        if receiver.changed().await.is_ok() {
+            return receiver.borrow_and_update().clone();
+        }
+        *rx = None;
+    }
+    std::future::pending().await
+}
 
-    let _ = tokio::process::Command::new("osascript")
-        .arg("-e")
-        .arg(&script)
-        .status()
-        .await;
+// Like `next_value`, but for one-off requests queued on an mpsc channel
+async fn next_request<T>(rx: &mut Option<mpsc::UnboundedReceiver<T>>) -> T {
+    if let Some(receiver) = rx {
+        if let Some(request) = receiver.recv().await {
+            return request;
+        }
+        *rx = None;
+    }
+    std::future::pending().await
 }
 
-fn log(
-    start: DateTime<Local>,
-    end: DateTime<Local>,
-    log_location: &mut PathBuf,
-    apps: &[String],
-    task: Option<&str>,
-) {
+// A directory (or a path ending in a separator) gets the default file name
+pub(crate) fn log_file_path(log_location: &Path) -> PathBuf {
     let mut path = log_location.to_path_buf();
     if path.is_dir()
         || log_location
@@ -524,15 +851,18 @@ fn log(
     {
         path.push("focus_log.txt");
     }
+    path
+}
+
+fn log(entry: &LogEntry, log_location: &Path) {
+    let path = log_file_path(log_location);
 
-    let duration = end - start;
-    let entry = LogEntry::new(start, end, apps.to_vec(), task.map(str::to_string));
     let log_line = entry.to_json_line().unwrap_or_else(|_| {
         format!(
             "[{} ~ {}] You worked for {:.2} minutes",
-            start.format("%Y-%m-%d %H:%M:%S"),
-            end.format("%Y-%m-%d %H:%M:%S"),
-            duration.num_seconds() as f64 / 60.0
+            entry.start.format("%Y-%m-%d %H:%M:%S"),
+            entry.end.format("%Y-%m-%d %H:%M:%S"),
+            entry.duration_seconds as f64 / 60.0
         )
     });
 
@@ -545,7 +875,7 @@ fn log(
     writeln!(file, "{log_line}").expect("Cannot write log file!");
     println!(
         "{} {}",
-        "Logging to".bright_green().bold(),
+        tr("monitor.logging_to").bright_green().bold(),
         path.to_string_lossy()
     );
 }
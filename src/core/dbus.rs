@@ -0,0 +1,162 @@
+use crate::core::hooks::ReminderAction;
+use crate::core::notify::Urgency;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::time::Duration;
+use zbus::Connection;
+use zbus::zvariant::Value;
+
+/// How long a notification with buttons is watched for a click. Past that the
+/// reminder is stale anyway.
+const ACTION_WAIT: Duration = Duration::from_secs(60 * 60);
+
+// https://specifications.freedesktop.org/notification-spec/latest/
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+
+    #[zbus(signal)]
+    fn action_invoked(&self, id: u32, action_key: String) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn notification_closed(&self, id: u32, reason: u32) -> zbus::Result<()>;
+}
+
+// https://www.freedesktop.org/software/systemd/man/latest/org.freedesktop.login1.html
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Login1Manager {
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+/// A desktop notification as sent to the notification server.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub summary: String,
+    pub body: String,
+    /// Icon name from the freedesktop icon theme, e.g. `dialog-warning`.
+    pub icon: String,
+    pub urgency: Urgency,
+    /// Hidden after this long, or kept until dismissed if `None`.
+    pub timeout: Option<Duration>,
+    /// Buttons shown on the notification.
+    pub actions: Vec<ReminderAction>,
+}
+
+/// Shows notifications through `org.freedesktop.Notifications` on a D-Bus bus.
+pub struct DbusNotifier {
+    connection: Connection,
+}
+
+impl DbusNotifier {
+    /// Connects to the session bus of the logged in user.
+    pub async fn session() -> zbus::Result<Self> {
+        Ok(DbusNotifier {
+            connection: Connection::session().await?,
+        })
+    }
+
+    /// Connects to the bus at `address`, e.g. a private `dbus-daemon`.
+    pub async fn with_address(address: &str) -> zbus::Result<Self> {
+        let connection = zbus::connection::Builder::address(address)?.build().await?;
+        Ok(DbusNotifier { connection })
+    }
+
+    /// Shows `notification` and waits for one of its buttons to be clicked.
+    ///
+    /// Returns `None` right away if it has no buttons, and once it is dismissed or
+    /// expires without a click.
+    pub async fn notify(
+        &self,
+        notification: &Notification,
+    ) -> zbus::Result<Option<ReminderAction>> {
+        let proxy = NotificationsProxy::new(&self.connection).await?;
+        // Subscribe first so a click right after showing is not missed
+        let mut invoked = proxy.receive_action_invoked().await?;
+        let mut closed = proxy.receive_notification_closed().await?;
+
+        let actions: Vec<String> = notification
+            .actions
+            .iter()
+            .flat_map(|action| [action.key().to_string(), action.label()])
+            .collect();
+        let actions: Vec<&str> = actions.iter().map(String::as_str).collect();
+        let hints = HashMap::from([("urgency", Value::U8(notification.urgency as u8))]);
+        // 0 asks the server to never expire the notification
+        let expire_timeout = notification
+            .timeout
+            .map(|timeout| timeout.as_millis().clamp(1, i32::MAX as u128) as i32)
+            .unwrap_or(0);
+        let id = proxy
+            .notify(
+                "Rest Reminder",
+                0,
+                &notification.icon,
+                &notification.summary,
+                &notification.body,
+                &actions,
+                hints,
+                expire_timeout,
+            )
+            .await?;
+        if notification.actions.is_empty() {
+            return Ok(None);
+        }
+
+        let wait_for_click = async {
+            loop {
+                tokio::select! {
+                    Some(signal) = invoked.next() => {
+                        let args = signal.args()?;
+                        if args.id == id {
+                            return Ok(ReminderAction::from_key(&args.action_key));
+                        }
+                    }
+                    Some(signal) = closed.next() => {
+                        if signal.args()?.id == id {
+                            return Ok(None);
+                        }
+                    }
+                    else => return Ok(None),
+                }
+            }
+        };
+        tokio::time::timeout(ACTION_WAIT, wait_for_click)
+            .await
+            .unwrap_or(Ok(None))
+    }
+}
+
+/// Sends logind's `PrepareForSleep` signals from the system bus to `sleep_tx` until
+/// the receiver is dropped.
+pub async fn forward_sleep_signals(sleep_tx: UnboundedSender<bool>) -> zbus::Result<()> {
+    let connection = Connection::system().await?;
+    let proxy = Login1ManagerProxy::new(&connection).await?;
+    let mut signals = proxy.receive_prepare_for_sleep().await?;
+    while let Some(signal) = signals.next().await {
+        if sleep_tx.send(signal.args()?.start).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
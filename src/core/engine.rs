@@ -0,0 +1,984 @@
+use crate::core::clock::Clock;
+use crate::core::matcher::{AppRule, rule_names};
+use crate::statistic::log_entry::LogEntry;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+
+/// Where the monitor currently is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorState {
+    /// No monitored process is running.
+    Resting,
+    /// A work session is open.
+    Working,
+    /// Monitoring is paused, nothing is counted.
+    Paused,
+    /// The work threshold was reached and the reminder has fired.
+    BreakDue,
+    /// The monitored processes went away after a reminder.
+    OnBreak,
+    /// The break reminder was postponed and work goes on until the snooze runs out.
+    Snoozed,
+}
+
+/// Everything that can move the monitor from one state to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonitorEvent {
+    /// A monitored process is running and the user is active.
+    ProcessSeen,
+    /// No monitored process is running.
+    ProcessGone,
+    /// A monitored process is running, but there was no input for this long.
+    InputIdle(Duration),
+    PauseToggled(bool),
+    AppsChanged(Vec<AppRule>),
+    /// The task label changed, e.g. to the branch the monitored apps have open.
+    TaskChanged(Option<String>),
+    /// Whether it is outside the working hours, and whether reminders are held back.
+    ScheduleChanged {
+        off_schedule: bool,
+        quiet: bool,
+    },
+    /// The machine was asleep since this time, the last check before it went to sleep.
+    Suspended(DateTime<Local>),
+    /// The user asked to be reminded again after this long.
+    Snooze(Duration),
+    /// Periodic check of the work threshold.
+    Tick,
+    /// A pomodoro break ran out. Produced by the engine while handling `Tick`.
+    BreakOver,
+    /// Work went on past the reminder for another `remind_every` seconds. Produced by
+    /// the engine while handling `Tick`.
+    ReminderEscalated,
+    /// The user chose to take the break from the reminder itself.
+    TakeBreak,
+    /// The monitor is about to stop.
+    Shutdown,
+}
+
+/// A state change produced by [`MonitorEngine::handle`].
+#[derive(Debug, Clone)]
+pub struct Transition {
+    pub from: MonitorState,
+    pub to: MonitorState,
+    pub cause: MonitorEvent,
+    /// The work session closed by this transition, ready to be logged.
+    pub finished: Option<LogEntry>,
+}
+
+/// Lengths of a pomodoro set. The work length is the monitor's `time`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PomodoroConfig {
+    /// Break after each pomodoro, in seconds.
+    #[serde(default = "default_short_break")]
+    pub short_break: u64,
+    /// Break after every `cycles` pomodoros, in seconds.
+    #[serde(default = "default_long_break")]
+    pub long_break: u64,
+    #[serde(default = "default_cycles")]
+    pub cycles: u32,
+}
+
+pub const DEFAULT_SHORT_BREAK_SECONDS: u64 = 300;
+pub const DEFAULT_LONG_BREAK_SECONDS: u64 = 900;
+pub const DEFAULT_POMODORO_CYCLES: u32 = 4;
+/// Length of a break taken from the reminder when no minimum break is set.
+pub const DEFAULT_TAKEN_BREAK_SECONDS: u64 = 300;
+
+fn default_short_break() -> u64 {
+    DEFAULT_SHORT_BREAK_SECONDS
+}
+
+fn default_long_break() -> u64 {
+    DEFAULT_LONG_BREAK_SECONDS
+}
+
+fn default_cycles() -> u32 {
+    DEFAULT_POMODORO_CYCLES
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        PomodoroConfig {
+            short_break: DEFAULT_SHORT_BREAK_SECONDS,
+            long_break: DEFAULT_LONG_BREAK_SECONDS,
+            cycles: DEFAULT_POMODORO_CYCLES,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Where a pomodoro run currently is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    /// Index of the pomodoro being worked on, or the one just finished during a break.
+    pub cycle: u32,
+    /// Seconds left in the current phase. Only counts down while working or on a break.
+    pub remaining_seconds: u64,
+}
+
+struct Pomodoro {
+    config: PomodoroConfig,
+    phase: PomodoroPhase,
+    completed: u32,
+    phase_deadline: Option<Instant>,
+}
+
+impl Pomodoro {
+    fn break_length(&self) -> u64 {
+        match self.phase {
+            PomodoroPhase::LongBreak => self.config.long_break,
+            _ => self.config.short_break,
+        }
+    }
+}
+
+struct OpenSession {
+    start: DateTime<Local>,
+    started_at: Instant,
+    snoozes: u32,
+    cycle: Option<u32>,
+    gap_seconds: u64,
+    // When the processes went missing, if they still are
+    gone_since: Option<(DateTime<Local>, Instant)>,
+    // Time each app rule was seen running
+    app_time: BTreeMap<String, Duration>,
+    // The part of `app_time` already logged when the task changed
+    logged_app_time: BTreeMap<String, Duration>,
+}
+
+/// State machine behind both the CLI `rest` command and the web `/rest` monitor.
+///
+/// The engine only decides what happens. Logging, plugin hooks and reminders are
+/// driven by the returned [`Transition`]s.
+pub struct MonitorEngine {
+    clock: Arc<dyn Clock>,
+    time: u64,
+    apps: Vec<AppRule>,
+    task: Option<String>,
+    state: MonitorState,
+    session: Option<OpenSession>,
+    max_snoozes: Option<u32>,
+    min_break: Option<u64>,
+    break_start: Option<DateTime<Local>>,
+    // End of the last logged work session, breaks never start before it
+    last_end: Option<DateTime<Local>>,
+    // A reminder fired and no break has been taken since
+    reminder_pending: bool,
+    snoozes: u32,
+    snooze_deadline: Option<Instant>,
+    pomodoro: Option<Pomodoro>,
+    // Repeat the reminder this often while work goes on past it
+    remind_every: Option<u64>,
+    overdue_since: Option<Instant>,
+    escalations: u32,
+    next_escalation: Option<Instant>,
+    // Processes must be seen this long before a session starts
+    start_debounce: Option<u64>,
+    // Absences shorter than this continue the open session
+    end_grace: Option<u64>,
+    // First sighting of the processes while the start is being debounced
+    pending_start: Option<(DateTime<Local>, Instant)>,
+    // A break taken from the reminder lasts at least until then, open processes or not
+    break_hold: Option<Instant>,
+    // Apps running at the last process check, and when it was
+    app_sample: Option<(Instant, Vec<String>)>,
+    // Work seconds after which the pending reminder fired
+    reminded_after: Option<u64>,
+    // Outside the working hours, sessions are logged as off-schedule and use this threshold
+    off_schedule: bool,
+    after_hours_time: Option<u64>,
+    // Reminders wait until this is over
+    quiet: bool,
+}
+
+impl MonitorEngine {
+    pub fn new(clock: Arc<dyn Clock>, time: u64, apps: Vec<AppRule>, task: Option<String>) -> Self {
+        MonitorEngine {
+            clock,
+            time,
+            apps,
+            task,
+            state: MonitorState::Resting,
+            session: None,
+            max_snoozes: None,
+            min_break: None,
+            break_start: None,
+            last_end: None,
+            reminder_pending: false,
+            snoozes: 0,
+            snooze_deadline: None,
+            pomodoro: None,
+            remind_every: None,
+            overdue_since: None,
+            escalations: 0,
+            next_escalation: None,
+            start_debounce: None,
+            end_grace: None,
+            pending_start: None,
+            break_hold: None,
+            app_sample: None,
+            reminded_after: None,
+            off_schedule: false,
+            after_hours_time: None,
+            quiet: false,
+        }
+    }
+
+    /// Runs the monitor in pomodoro cycles, with `time` as the work length.
+    pub fn with_pomodoro(mut self, pomodoro: Option<PomodoroConfig>) -> Self {
+        self.pomodoro = pomodoro.map(|config| Pomodoro {
+            config,
+            phase: PomodoroPhase::Work,
+            completed: 0,
+            phase_deadline: None,
+        });
+        self
+    }
+
+    /// Caps how many times a reminder can be snoozed before a break is taken.
+    pub fn with_max_snoozes(mut self, max_snoozes: Option<u32>) -> Self {
+        self.max_snoozes = max_snoozes;
+        self
+    }
+
+    /// Breaks shorter than this many seconds are logged as violations.
+    pub fn with_min_break(mut self, min_break: Option<u64>) -> Self {
+        self.min_break = min_break;
+        self
+    }
+
+    /// Repeats an ignored reminder every `remind_every` seconds. The session keeps
+    /// running through the overdue period instead of being split at the reminder.
+    pub fn with_remind_every(mut self, remind_every: Option<u64>) -> Self {
+        self.remind_every = remind_every;
+        self
+    }
+
+    /// Only starts a session once the processes were seen for `start_debounce`
+    /// seconds. The session then starts at the first sighting.
+    pub fn with_start_debounce(mut self, start_debounce: Option<u64>) -> Self {
+        self.start_debounce = start_debounce;
+        self
+    }
+
+    /// Keeps the session open while the processes are gone for less than
+    /// `end_grace` seconds, e.g. during an IDE restart. The gap is logged with it.
+    pub fn with_end_grace(mut self, end_grace: Option<u64>) -> Self {
+        self.end_grace = end_grace;
+        self
+    }
+
+    /// Reminds after `after_hours_time` seconds of work outside the working hours.
+    pub fn with_after_hours_time(mut self, after_hours_time: Option<u64>) -> Self {
+        self.after_hours_time = after_hours_time;
+        self
+    }
+
+    pub fn state(&self) -> MonitorState {
+        self.state
+    }
+
+    pub fn apps(&self) -> &[AppRule] {
+        &self.apps
+    }
+
+    /// Label of new sessions, and of the open one.
+    pub fn task(&self) -> Option<&str> {
+        self.task.as_deref()
+    }
+
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// Seconds of work after which the pending reminder fired: `time`, or the
+    /// threshold of the app that reached its own first.
+    pub fn reminder_time(&self) -> u64 {
+        self.reminded_after.unwrap_or_else(|| self.work_time())
+    }
+
+    /// Whether it is outside the working hours of the schedule.
+    pub fn off_schedule(&self) -> bool {
+        self.off_schedule
+    }
+
+    /// Whether reminders are held back, e.g. during quiet hours.
+    pub fn quiet(&self) -> bool {
+        self.quiet
+    }
+
+    // The work threshold in force right now
+    fn work_time(&self) -> u64 {
+        match self.after_hours_time {
+            Some(after_hours_time) if self.off_schedule => after_hours_time,
+            _ => self.time,
+        }
+    }
+
+    /// Time left until the break reminder is due, while working towards it. The
+    /// monitor checks right then instead of waiting for its next scan.
+    pub fn until_reminder(&self) -> Option<Duration> {
+        if self.state != MonitorState::Working || self.quiet {
+            return None;
+        }
+        let session = self.session.as_ref()?;
+        let left =
+            Duration::from_secs(self.work_time()).saturating_sub(session.started_at.elapsed());
+        if self.pomodoro.is_some() {
+            return Some(left);
+        }
+        // Only apps still running get closer to their own threshold
+        let running = self.pending_app_time();
+        Some(self.apps.iter().fold(left, |left, rule| {
+            let Some(threshold) = rule.threshold() else {
+                return left;
+            };
+            let Some((_, pending)) = running.iter().find(|(app, _)| app == rule.as_str()) else {
+                return left;
+            };
+            let used = session
+                .app_time
+                .get(rule.as_str())
+                .copied()
+                .unwrap_or_default()
+                + *pending;
+            left.min(Duration::from_secs(threshold).saturating_sub(used))
+        }))
+    }
+
+    /// Records which app rules matched at a process check. The time since the
+    /// previous check is credited to the apps seen then.
+    pub fn observe_apps(&mut self, running: Vec<String>) {
+        let now = Instant::now();
+        if let Some((sampled_at, apps)) = self.app_sample.take()
+            && let Some(session) = self.session.as_mut()
+            && session.gone_since.is_none()
+        {
+            let elapsed = now.saturating_duration_since(sampled_at);
+            for app in apps {
+                *session.app_time.entry(app).or_default() += elapsed;
+            }
+        }
+        self.app_sample = Some((now, running));
+    }
+
+    pub fn session_start(&self) -> Option<DateTime<Local>> {
+        self.session.as_ref().map(|session| session.start)
+    }
+
+    pub fn break_start(&self) -> Option<DateTime<Local>> {
+        self.break_start
+    }
+
+    /// Whether a break reminder fired and is still waiting for a break.
+    pub fn reminder_pending(&self) -> bool {
+        self.reminder_pending
+    }
+
+    /// Snoozes used since the last break.
+    pub fn snoozes(&self) -> u32 {
+        self.snoozes
+    }
+
+    pub fn snooze_limit_reached(&self) -> bool {
+        self.max_snoozes.is_some_and(|max| self.snoozes >= max)
+    }
+
+    pub fn pomodoro_status(&self) -> Option<PomodoroStatus> {
+        let pomodoro = self.pomodoro.as_ref()?;
+        let (cycle, remaining) = match pomodoro.phase {
+            PomodoroPhase::Work => {
+                let elapsed = self
+                    .session
+                    .as_ref()
+                    .map(|session| session.started_at.elapsed().as_secs())
+                    .unwrap_or_default();
+                (
+                    pomodoro.completed + 1,
+                    self.work_time().saturating_sub(elapsed),
+                )
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => {
+                let remaining = match pomodoro.phase_deadline {
+                    Some(deadline) => deadline.saturating_duration_since(Instant::now()).as_secs(),
+                    None => pomodoro.break_length(),
+                };
+                (pomodoro.completed, remaining)
+            }
+        };
+
+        Some(PomodoroStatus {
+            phase: pomodoro.phase,
+            cycle,
+            remaining_seconds: remaining,
+        })
+    }
+
+    /// How many times the pending reminder has been repeated.
+    pub fn escalations(&self) -> u32 {
+        self.escalations
+    }
+
+    /// Seconds since the pending reminder first fired.
+    pub fn overdue_seconds(&self) -> u64 {
+        self.overdue_since
+            .map(|since| since.elapsed().as_secs())
+            .unwrap_or_default()
+    }
+
+    /// When the current snooze runs out.
+    pub fn snoozed_until(&self) -> Option<DateTime<Local>> {
+        self.snooze_deadline.map(|deadline| {
+            let left = deadline.saturating_duration_since(Instant::now());
+            self.clock.now() + chrono::Duration::from_std(left).unwrap_or(chrono::Duration::zero())
+        })
+    }
+
+    pub fn handle(&mut self, event: MonitorEvent) -> Option<Transition> {
+        use MonitorEvent::*;
+        use MonitorState::*;
+
+        if event == Tick && self.break_phase_over() {
+            return self.end_break_phase();
+        }
+        if event == Tick && self.grace_over() {
+            return self.end_after_gap();
+        }
+        if matches!(event, ProcessGone | InputIdle(_) | Suspended(_)) {
+            self.pending_start = None;
+        }
+
+        match (self.state, &event) {
+            (_, AppsChanged(apps)) => {
+                self.apps = apps.clone();
+                if self.session.is_some() && self.apps.is_empty() {
+                    let end = self.session_end();
+                    return self.transition(Resting, event, Some(end));
+                }
+                None
+            }
+            (_, TaskChanged(task)) => self.change_task(task.clone()),
+            (
+                _,
+                ScheduleChanged {
+                    off_schedule,
+                    quiet,
+                },
+            ) => self.change_schedule(*off_schedule, *quiet),
+            (Paused, PauseToggled(false)) => self.transition(Resting, event, None),
+            (Paused, _) => None,
+            (Working | Snoozed | BreakDue, PauseToggled(true)) => {
+                let end = self.session_end();
+                self.transition(Paused, event, Some(end))
+            }
+            (_, PauseToggled(true)) => self.transition(Paused, event, None),
+            (_, PauseToggled(false)) => None,
+            (Working | Snoozed | BreakDue | OnBreak, Shutdown) => {
+                let end = self.session_end();
+                self.transition(Resting, event, Some(end))
+            }
+            // Nothing was done while asleep, so the session or break ends where the
+            // machine went to sleep and a fresh one starts on the next check
+            (Working | Snoozed | BreakDue | OnBreak, Suspended(since)) => {
+                let end = self.session_end().min(*since);
+                self.transition(Resting, event, Some(end))
+            }
+            // Leaving after a reminder is the break it asked for
+            (Working | Snoozed, ProcessGone) => {
+                if self.end_grace.is_some() {
+                    self.start_gap();
+                    return None;
+                }
+                let end = self.clock.now();
+                self.transition(self.leave_to(), event, Some(end))
+            }
+            (Working | Snoozed, InputIdle(idle_for)) => {
+                let end = self.last_input(*idle_for);
+                self.transition(self.leave_to(), event, Some(end))
+            }
+            // Held back reminders fire on the first check after the quiet hours
+            (Working | Snoozed | BreakDue, Tick) if self.quiet => None,
+            (Working, Tick) => {
+                if let Some(reached) = self.threshold_reached() {
+                    self.reminded_after = Some(reached);
+                    if let Some(pomodoro) = self.pomodoro.as_mut() {
+                        pomodoro.completed += 1;
+                        if let Some(session) = self.session.as_mut() {
+                            session.cycle = Some(pomodoro.completed);
+                        }
+                    }
+                    let end = self.overdue_end();
+                    return self.transition(BreakDue, event, end);
+                }
+                None
+            }
+            (Snoozed, Tick) => {
+                if self
+                    .snooze_deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    let end = self.overdue_end();
+                    return self.transition(BreakDue, event, end);
+                }
+                None
+            }
+            (BreakDue, Tick) => {
+                if self
+                    .next_escalation
+                    .is_some_and(|deadline| Instant::now() >= deadline)
+                {
+                    return self.escalate();
+                }
+                None
+            }
+            (Working | Snoozed | BreakDue, TakeBreak) if self.reminder_pending => {
+                let end = self.session_end();
+                self.hold_break();
+                self.transition(OnBreak, event, Some(end))
+            }
+            (OnBreak, ProcessSeen) if self.break_held() => None,
+            (Working | BreakDue | Snoozed, Snooze(snooze_for)) if self.reminder_pending => {
+                let snooze_for = *snooze_for;
+                self.snooze(event, snooze_for)
+            }
+            (Working | Snoozed, ProcessSeen) => {
+                self.end_gap();
+                None
+            }
+            // Still working past the reminder, the session carries on
+            (BreakDue, ProcessSeen) if self.session.is_some() => None,
+            (_, ProcessSeen) => {
+                if !self.start_confirmed() {
+                    return None;
+                }
+                self.transition(Working, event, None)
+            }
+            (BreakDue, ProcessGone) => {
+                let start = self.clock.now();
+                self.transition(OnBreak, event, Some(start))
+            }
+            (BreakDue, InputIdle(idle_for)) => {
+                let start = self.last_input(*idle_for);
+                self.transition(OnBreak, event, Some(start))
+            }
+            _ => None,
+        }
+    }
+
+    // The session's length counts against `time`. Outside of pomodoros an app with
+    // its own threshold can also use it up sooner.
+    fn threshold_reached(&self) -> Option<u64> {
+        let session = self.session.as_ref()?;
+        let time = self.work_time();
+        if session.started_at.elapsed().as_secs() >= time {
+            return Some(time);
+        }
+        if self.pomodoro.is_some() {
+            return None;
+        }
+        self.apps.iter().find_map(|rule| {
+            let threshold = rule.threshold()?;
+            let running = session.app_time.get(rule.as_str())?;
+            (running.as_secs() >= threshold).then_some(threshold)
+        })
+    }
+
+    // The break lasts for the pomodoro break, or the minimum break outside of pomodoros
+    fn hold_break(&mut self) {
+        let hold_until = match self.pomodoro.as_mut() {
+            Some(pomodoro) => {
+                let length = Duration::from_secs(pomodoro.break_length());
+                *pomodoro
+                    .phase_deadline
+                    .get_or_insert_with(|| Instant::now() + length)
+            }
+            None => {
+                let length = self.min_break.unwrap_or(DEFAULT_TAKEN_BREAK_SECONDS);
+                Instant::now() + Duration::from_secs(length)
+            }
+        };
+        self.break_hold = Some(hold_until);
+    }
+
+    fn break_held(&self) -> bool {
+        self.break_hold
+            .is_some_and(|hold_until| Instant::now() < hold_until)
+    }
+
+    fn break_phase_over(&self) -> bool {
+        self.pomodoro.as_ref().is_some_and(|pomodoro| {
+            pomodoro
+                .phase_deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+        })
+    }
+
+    // The next pomodoro starts right away if the user is already back at work
+    fn end_break_phase(&mut self) -> Option<Transition> {
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.phase = PomodoroPhase::Work;
+            pomodoro.phase_deadline = None;
+        }
+
+        let transition = match self.state {
+            MonitorState::Paused => None,
+            MonitorState::Working | MonitorState::Snoozed => {
+                let end = self.clock.now();
+                self.transition(MonitorState::Working, MonitorEvent::BreakOver, Some(end))
+            }
+            MonitorState::BreakDue if self.session.is_some() => {
+                let end = self.clock.now();
+                self.transition(MonitorState::Working, MonitorEvent::BreakOver, Some(end))
+            }
+            _ => self.transition(MonitorState::Resting, MonitorEvent::BreakOver, None),
+        };
+        self.clear_reminder();
+        transition
+    }
+
+    // With escalation the session stays open while the reminder is ignored
+    fn overdue_end(&self) -> Option<DateTime<Local>> {
+        match self.remind_every {
+            Some(_) => None,
+            None => Some(self.clock.now()),
+        }
+    }
+
+    // The open session is logged under the old label and goes on under the new one.
+    // Changing tasks is no break, so the work towards the reminder carries on.
+    fn change_task(&mut self, task: Option<String>) -> Option<Transition> {
+        if task == self.task {
+            return None;
+        }
+        let transition = self.split_session(MonitorEvent::TaskChanged(task.clone()));
+        self.task = task;
+        transition
+    }
+
+    // Entering or leaving the working hours splits the session the same way, so each
+    // part is logged as on or off schedule
+    fn change_schedule(&mut self, off_schedule: bool, quiet: bool) -> Option<Transition> {
+        self.quiet = quiet;
+        if off_schedule == self.off_schedule {
+            return None;
+        }
+        let transition = self.split_session(MonitorEvent::ScheduleChanged {
+            off_schedule,
+            quiet,
+        });
+        self.off_schedule = off_schedule;
+        transition
+    }
+
+    // Logs the open session so far and carries on with the rest of it
+    fn split_session(&mut self, cause: MonitorEvent) -> Option<Transition> {
+        let split_at = self.session_end();
+        let finished = self
+            .entry_until(split_at)
+            .filter(|entry| entry.end > entry.start)?;
+
+        let pending = self.pending_app_time();
+        if let Some(session) = self.session.as_mut() {
+            session.start = split_at;
+            session.snoozes = 0;
+            // In a gap the split is where the processes left, the rest of it counts
+            // towards the new part
+            session.gap_seconds = 0;
+            session.logged_app_time = session.app_time.clone();
+            for (app, running) in pending {
+                *session.logged_app_time.entry(app).or_default() += running;
+            }
+        }
+        self.last_end = Some(finished.end);
+        Some(Transition {
+            from: self.state,
+            to: self.state,
+            cause,
+            finished: Some(finished),
+        })
+    }
+
+    // Apps seen at the last check are still running, unless the processes are gone
+    fn pending_app_time(&self) -> Vec<(String, Duration)> {
+        match (&self.app_sample, &self.session) {
+            (Some((sampled_at, apps)), Some(session)) if session.gone_since.is_none() => apps
+                .iter()
+                .map(|app| (app.clone(), sampled_at.elapsed()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn escalate(&mut self) -> Option<Transition> {
+        self.escalations += 1;
+        self.next_escalation = self
+            .remind_every
+            .map(|every| Instant::now() + Duration::from_secs(every));
+        Some(Transition {
+            from: MonitorState::BreakDue,
+            to: MonitorState::BreakDue,
+            cause: MonitorEvent::ReminderEscalated,
+            finished: None,
+        })
+    }
+
+    fn clear_reminder(&mut self) {
+        self.reminder_pending = false;
+        self.snoozes = 0;
+        self.snooze_deadline = None;
+        self.overdue_since = None;
+        self.escalations = 0;
+        self.next_escalation = None;
+        self.reminded_after = None;
+    }
+
+    /// The open session as it would be logged if it ended now.
+    pub fn session_entry(&self) -> Option<LogEntry> {
+        self.entry_until(self.session_end())
+    }
+
+    fn entry_until(&self, end: DateTime<Local>) -> Option<LogEntry> {
+        self.session.as_ref().map(|session| {
+            let end = end.max(session.start);
+            let mut app_time = session.app_time.clone();
+            for (app, running) in self.pending_app_time() {
+                *app_time.entry(app).or_default() += running;
+            }
+            // A session ended at the last input is shorter than the apps ran
+            let length = (end - session.start).num_seconds().max(0) as u64;
+            let app_seconds = app_time
+                .into_iter()
+                .map(|(app, running)| {
+                    let logged = session.logged_app_time.get(&app).copied();
+                    let running = running.saturating_sub(logged.unwrap_or_default());
+                    (app, running.as_secs().min(length))
+                })
+                .filter(|(_, seconds)| *seconds > 0)
+                .collect();
+            LogEntry::new(
+                session.start,
+                end,
+                rule_names(&self.apps),
+                self.task.clone(),
+            )
+            .with_app_seconds(app_seconds)
+            .with_snoozes(session.snoozes)
+            .with_gap_seconds(session.gap_seconds)
+            .with_cycle(session.cycle)
+            .with_off_schedule(self.off_schedule)
+        })
+    }
+
+    // A session in a gap ends where the gap began
+    fn session_end(&self) -> DateTime<Local> {
+        self.session
+            .as_ref()
+            .and_then(|session| session.gone_since)
+            .map(|(gone_at, _)| gone_at)
+            .unwrap_or_else(|| self.clock.now())
+    }
+
+    fn start_gap(&mut self) {
+        let now = self.clock.now();
+        if let Some(session) = self.session.as_mut() {
+            session.gone_since.get_or_insert((now, Instant::now()));
+        }
+    }
+
+    fn end_gap(&mut self) {
+        if let Some(session) = self.session.as_mut()
+            && let Some((_, gone_at)) = session.gone_since.take()
+        {
+            session.gap_seconds += gone_at.elapsed().as_secs();
+        }
+    }
+
+    fn grace_over(&self) -> bool {
+        let Some(grace) = self.end_grace else {
+            return false;
+        };
+        self.session
+            .as_ref()
+            .and_then(|session| session.gone_since)
+            .is_some_and(|(_, gone_at)| gone_at.elapsed().as_secs() >= grace)
+    }
+
+    // The processes stayed away past the grace period, so the session ended when they left
+    fn end_after_gap(&mut self) -> Option<Transition> {
+        let end = self.session_end();
+        self.transition(self.leave_to(), MonitorEvent::ProcessGone, Some(end))
+    }
+
+    fn start_confirmed(&mut self) -> bool {
+        let Some(debounce) = self.start_debounce else {
+            return true;
+        };
+        let now = self.clock.now();
+        let (_, first_seen) = *self.pending_start.get_or_insert((now, Instant::now()));
+        first_seen.elapsed().as_secs() >= debounce
+    }
+
+    fn leave_to(&self) -> MonitorState {
+        if self.reminder_pending {
+            MonitorState::OnBreak
+        } else {
+            MonitorState::Resting
+        }
+    }
+
+    // The session ends at the last keyboard/mouse input, but never before it started
+    fn last_input(&self, idle_for: Duration) -> DateTime<Local> {
+        let idle_for = chrono::Duration::from_std(idle_for).unwrap_or(chrono::Duration::zero());
+        let last_input = self.clock.now() - idle_for;
+        match self.session.as_ref() {
+            Some(session) => last_input.max(session.start),
+            None => last_input,
+        }
+    }
+
+    // Postpones the pending reminder, or brings it straight back once the snoozes are used up
+    fn snooze(&mut self, event: MonitorEvent, snooze_for: Duration) -> Option<Transition> {
+        if self.snooze_limit_reached() {
+            let end = match self.state {
+                MonitorState::BreakDue => None,
+                _ => self.overdue_end(),
+            };
+            return self.transition(MonitorState::BreakDue, event, end);
+        }
+
+        self.snoozes += 1;
+        // A postponed pomodoro break starts over once the snooze runs out
+        if let Some(pomodoro) = self.pomodoro.as_mut() {
+            pomodoro.phase_deadline = None;
+        }
+        let transition = self.transition(MonitorState::Snoozed, event, None);
+        self.snooze_deadline = Some(Instant::now() + snooze_for);
+        if let Some(session) = self.session.as_mut() {
+            session.snoozes += 1;
+        }
+        transition
+    }
+
+    // Closes the open session (or break) at `end` and moves to `to`. A break entered
+    // here starts at `end`, or now if there is no end.
+    fn transition(
+        &mut self,
+        to: MonitorState,
+        cause: MonitorEvent,
+        end: Option<DateTime<Local>>,
+    ) -> Option<Transition> {
+        let from = self.state;
+        let mut finished = end.and_then(|end| {
+            let entry = self.entry_until(end)?;
+            self.session = None;
+            self.last_end = Some(entry.end);
+            Some(entry)
+        });
+
+        if from == MonitorState::OnBreak && to != MonitorState::OnBreak {
+            self.break_hold = None;
+            // A debounced comeback ends the break at the first sighting
+            let back_at = match &cause {
+                MonitorEvent::Suspended(since) => *since,
+                _ => self
+                    .pending_start
+                    .map(|(first_seen, _)| first_seen)
+                    .unwrap_or_else(|| self.clock.now()),
+            };
+            finished = self.break_start.take().map(|start| {
+                let end = back_at.max(start);
+                // Only coming back to work early breaks the rule, stopping or pausing does not
+                let violation = to == MonitorState::Working
+                    && self
+                        .min_break
+                        .is_some_and(|min| (end - start).num_seconds() < min as i64);
+                LogEntry::new_break(
+                    start,
+                    end,
+                    rule_names(&self.apps),
+                    self.task.clone(),
+                    violation,
+                )
+            });
+        }
+
+        match to {
+            MonitorState::Working | MonitorState::Snoozed => {
+                if self.session.is_none() {
+                    let (start, started_at) = self
+                        .pending_start
+                        .unwrap_or_else(|| (self.clock.now(), Instant::now()));
+                    // The apps that confirmed a debounced start ran since it
+                    let app_time = self
+                        .app_sample
+                        .iter()
+                        .flat_map(|(_, apps)| apps)
+                        .map(|app| (app.clone(), started_at.elapsed()))
+                        .collect();
+                    self.session = Some(OpenSession {
+                        start,
+                        started_at,
+                        snoozes: 0,
+                        cycle: None,
+                        gap_seconds: 0,
+                        gone_since: None,
+                        app_time,
+                        logged_app_time: BTreeMap::new(),
+                    });
+                }
+            }
+            MonitorState::BreakDue => {
+                self.reminder_pending = true;
+                self.snooze_deadline = None;
+                self.overdue_since.get_or_insert_with(Instant::now);
+                self.next_escalation = self
+                    .remind_every
+                    .map(|every| Instant::now() + Duration::from_secs(every));
+                if let Some(pomodoro) = self.pomodoro.as_mut() {
+                    pomodoro.phase = if pomodoro.completed > 0
+                        && pomodoro.completed % pomodoro.config.cycles.max(1) == 0
+                    {
+                        PomodoroPhase::LongBreak
+                    } else {
+                        PomodoroPhase::ShortBreak
+                    };
+                    pomodoro.phase_deadline =
+                        Some(Instant::now() + Duration::from_secs(pomodoro.break_length()));
+                }
+            }
+            MonitorState::Resting | MonitorState::OnBreak | MonitorState::Paused => {
+                self.clear_reminder();
+            }
+        }
+
+        if to == MonitorState::OnBreak && from != MonitorState::OnBreak {
+            let start = end.unwrap_or_else(|| self.clock.now());
+            self.break_start = Some(match self.last_end {
+                Some(last_end) => start.max(last_end),
+                None => start,
+            });
+        }
+
+        self.pending_start = None;
+        self.state = to;
+        Some(Transition {
+            from,
+            to,
+            cause,
+            finished,
+        })
+    }
+}
@@ -0,0 +1,212 @@
+use crate::core::matcher::{AppRule, ProcessTree};
+use crate::core::probe::{ProcessInfo, ScanDetails};
+use regex::Regex;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const RULE_SEPARATOR: char = '=';
+// Detached checkouts are labelled with the abbreviated commit, as git shows them
+const SHORT_COMMIT: usize = 7;
+
+/// A git checkout found from a monitored process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitRepo {
+    /// The working tree.
+    pub root: PathBuf,
+    /// Name of the working tree directory.
+    pub name: String,
+    /// Checked out branch, or the short commit when detached.
+    pub branch: String,
+}
+
+impl GitRepo {
+    /// `repo:branch`, the label used when no rule matches.
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.name, self.branch)
+    }
+}
+
+/// A single `--git-task-rule`, `<PATTERN>=<LABEL>`.
+///
+/// The pattern is a regular expression matched against `repo:branch`, the label
+/// may refer to its groups as `$1` or `$name`, e.g.
+/// `^[^:]+:feature/([A-Z]+-[0-9]+)=$1` labels `app:feature/PROJ-42-login` as
+/// `PROJ-42`. An empty label leaves the session unlabeled.
+#[derive(Debug, Clone)]
+pub struct GitTaskRule {
+    source: String,
+    pattern: Regex,
+    label: String,
+}
+
+impl GitTaskRule {
+    /// The label for `key` if the pattern matches it, `Some(None)` for an empty label.
+    pub fn apply(&self, key: &str) -> Option<Option<String>> {
+        let captures = self.pattern.captures(key)?;
+        let mut label = String::new();
+        captures.expand(&self.label, &mut label);
+        let label = label.trim();
+        Some((!label.is_empty()).then(|| label.to_string()))
+    }
+}
+
+impl FromStr for GitTaskRule {
+    type Err = String;
+
+    // Labels rarely contain '=', patterns may, so the last one separates them
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let Some((pattern, label)) = value.rsplit_once(RULE_SEPARATOR) else {
+            return Err(format!(
+                "Invalid git task rule '{}', expected <PATTERN>=<LABEL>",
+                value
+            ));
+        };
+        let pattern = Regex::new(pattern.trim())
+            .map_err(|e| format!("Invalid regex '{}': {}", pattern.trim(), e))?;
+
+        Ok(GitTaskRule {
+            source: value.to_string(),
+            pattern,
+            label: label.trim().to_string(),
+        })
+    }
+}
+
+impl fmt::Display for GitTaskRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+impl PartialEq for GitTaskRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for GitTaskRule {}
+
+pub fn parse_git_task_rules(rules: &[String]) -> Result<Vec<GitTaskRule>, String> {
+    rules.iter().map(|rule| rule.parse()).collect()
+}
+
+/// Labels sessions after the git branch the monitored apps have open.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GitTaskConfig {
+    /// Tried in order, the first matching rule gives the label.
+    pub rules: Vec<GitTaskRule>,
+}
+
+impl GitTaskConfig {
+    pub fn new(rules: Vec<GitTaskRule>) -> Self {
+        GitTaskConfig { rules }
+    }
+
+    /// The task label for a checkout, `None` for unlabeled.
+    pub fn label(&self, repo: &GitRepo) -> Option<String> {
+        let key = repo.key();
+        self.rules
+            .iter()
+            .find_map(|rule| rule.apply(&key))
+            .unwrap_or(Some(key))
+    }
+
+    /// Checkouts are found from the command line and the working directory.
+    pub fn scan_details(&self) -> ScanDetails {
+        ScanDetails {
+            cmd: true,
+            cwd: true,
+            ..ScanDetails::default()
+        }
+    }
+
+    /// The label of the first monitored process, by pid, that has a checkout open.
+    /// `None` if none of them has, the current label should then stay.
+    pub fn resolve(
+        &self,
+        processes: &[ProcessInfo],
+        rules: &[AppRule],
+        current_user: Option<&str>,
+    ) -> Option<Option<String>> {
+        let tree = ProcessTree::new(processes);
+        let mut matched: Vec<&ProcessInfo> = processes
+            .iter()
+            .filter(|process| {
+                rules
+                    .iter()
+                    .any(|rule| rule.matches_in(process, &tree, current_user))
+            })
+            .collect();
+        matched.sort_by_key(|process| process.pid);
+        matched
+            .into_iter()
+            .find_map(process_repo)
+            .map(|repo| self.label(&repo))
+    }
+}
+
+/// The checkout a process works on. Paths in its arguments come first, editors are
+/// often started from the home directory with the project as an argument, then its
+/// working directory.
+pub fn process_repo(process: &ProcessInfo) -> Option<GitRepo> {
+    let cwd = process.cwd.as_deref().map(Path::new);
+    let from_args = process
+        .args
+        .iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .filter_map(|arg| {
+            let path = Path::new(arg);
+            match cwd {
+                Some(cwd) if path.is_relative() => Some(cwd.join(path)),
+                _ if path.is_absolute() => Some(path.to_path_buf()),
+                _ => None,
+            }
+        })
+        .filter(|path| path.exists());
+
+    from_args
+        .chain(cwd.map(Path::to_path_buf))
+        .find_map(|path| find_repo(&path))
+}
+
+/// The git checkout containing `path`, looking at its parents as well.
+pub fn find_repo(path: &Path) -> Option<GitRepo> {
+    path.ancestors().find_map(|dir| {
+        let head = git_dir(dir)?.join("HEAD");
+        let branch = read_branch(&std::fs::read_to_string(head).ok()?)?;
+        Some(GitRepo {
+            root: dir.to_path_buf(),
+            name: dir.file_name()?.to_string_lossy().to_string(),
+            branch,
+        })
+    })
+}
+
+// `.git` is a directory, or in worktrees and submodules a file pointing at one
+fn git_dir(dir: &Path) -> Option<PathBuf> {
+    let git = dir.join(".git");
+    if git.is_dir() {
+        return Some(git);
+    }
+    let pointer = std::fs::read_to_string(&git).ok()?;
+    let target = pointer.trim().strip_prefix("gitdir:")?.trim();
+    Some(dir.join(target))
+}
+
+/// The branch `HEAD` points at, or the short commit of a detached `HEAD`.
+pub fn read_branch(head: &str) -> Option<String> {
+    let head = head.trim();
+    match head.strip_prefix("ref:") {
+        Some(reference) => {
+            let reference = reference.trim();
+            let branch = reference.strip_prefix("refs/heads/").unwrap_or(reference);
+            (!branch.is_empty()).then(|| branch.to_string())
+        }
+        None if head.len() >= SHORT_COMMIT && head.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            Some(head[..SHORT_COMMIT].to_string())
+        }
+        None => None,
+    }
+}
@@ -0,0 +1,191 @@
+use crate::core::budget::BudgetAlert;
+use crate::core::messages::{MessageContext, tr};
+use crate::core::notify::{
+    Notifiers, NotifyConfig, after_hours_message, budget_message, escalation_message,
+    reminder_message, work_start_message,
+};
+use crate::plugin::plugin::{PluginContext, PluginManager};
+use colored::*;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::time::Duration;
+
+/// A button clicked on a break reminder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReminderAction {
+    /// Start the break now, even if the monitored processes stay open.
+    TakeBreak,
+    /// Postpone the reminder by the configured snooze length.
+    Snooze,
+}
+
+impl ReminderAction {
+    /// Identifier sent back by the notification server.
+    pub fn key(&self) -> &'static str {
+        match self {
+            ReminderAction::TakeBreak => "take_break",
+            ReminderAction::Snooze => "snooze",
+        }
+    }
+
+    /// Button text in the current message catalog.
+    pub fn label(&self) -> String {
+        match self {
+            ReminderAction::TakeBreak => tr("action.take_break"),
+            ReminderAction::Snooze => tr("action.snooze"),
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "take_break" => Some(ReminderAction::TakeBreak),
+            "snooze" => Some(ReminderAction::Snooze),
+            _ => None,
+        }
+    }
+}
+
+/// Side effects the monitor fires when a session changes state.
+///
+/// The default implementation runs Python plugins and shows the platform popup,
+/// tests record the calls instead.
+pub trait MonitorHooks: Send {
+    /// Fires a plugin hook such as `on_work_start`.
+    fn trigger(&mut self, hook_name: &str, context: &PluginContext);
+
+    /// Tells the user a work session started.
+    fn work_started(&mut self, _context: &MessageContext) {}
+
+    /// Shows the break reminder after `time` seconds of continuous work.
+    fn remind(&mut self, time: u64, context: &MessageContext);
+
+    /// Repeats an ignored reminder, `overdue` seconds after it first fired. `level`
+    /// counts the repeats, starting at 1.
+    fn escalate(&mut self, overdue: u64, level: u32, context: &MessageContext);
+
+    /// Tells the user a daily or weekly work budget threshold was crossed.
+    fn budget_reached(&mut self, alert: &BudgetAlert, context: &MessageContext);
+
+    /// Tells the user they are working outside the working hours.
+    fn after_hours(&mut self, _context: &MessageContext) {}
+
+    /// Reloads whatever the hooks read from disk, asked for with SIGUSR2.
+    fn reload(&mut self) {}
+
+    /// Snooze requests coming back from the hooks themselves. Taken once when the
+    /// monitor starts.
+    fn snooze_requests(&mut self) -> Option<UnboundedReceiver<Duration>> {
+        None
+    }
+
+    /// Buttons clicked on the reminders shown by the hooks. Taken once when the
+    /// monitor starts.
+    fn reminder_actions(&mut self) -> Option<UnboundedReceiver<ReminderAction>> {
+        None
+    }
+}
+
+/// Hooks backed by the Python plugins under `plugins/` and the notification sinks
+/// chosen in the [`NotifyConfig`].
+pub struct PluginHooks {
+    plugin_manager: PluginManager,
+    notifiers: Notifiers,
+    snooze_rx: Option<UnboundedReceiver<Duration>>,
+    action_tx: UnboundedSender<ReminderAction>,
+    action_rx: Option<UnboundedReceiver<ReminderAction>>,
+}
+
+impl PluginHooks {
+    pub fn new(notify: &NotifyConfig) -> Self {
+        let mut plugin_manager = PluginManager::new().unwrap_or_else(|e| {
+            println!(
+                "{} {}",
+                "Failed to initialize plugin manager:".bright_red().bold(),
+                e.to_string().red()
+            );
+            PluginManager::new().unwrap()
+        });
+
+        let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+        plugin_manager.set_snooze_sender(snooze_tx);
+        let (action_tx, action_rx) = mpsc::unbounded_channel();
+
+        PluginHooks {
+            plugin_manager,
+            notifiers: Notifiers::new(notify),
+            snooze_rx: Some(snooze_rx),
+            action_tx,
+            action_rx: Some(action_rx),
+        }
+    }
+}
+
+impl Default for PluginHooks {
+    fn default() -> Self {
+        Self::new(&NotifyConfig::default())
+    }
+}
+
+impl MonitorHooks for PluginHooks {
+    // Plugins are reloaded before every hook so edits in `plugins/` apply without a restart
+    fn trigger(&mut self, hook_name: &str, context: &PluginContext) {
+        if let Err(e) = self.plugin_manager.load_plugins("plugins") {
+            println!(
+                "{} {}",
+                "Failed to load plugins:".bright_red().bold(),
+                e.to_string().red()
+            );
+        }
+
+        if let Err(e) = self.plugin_manager.trigger_hook(hook_name, context) {
+            println!(
+                "{} {}",
+                "Plugin hook error:".bright_red(),
+                e.to_string().red()
+            );
+        }
+    }
+
+    fn work_started(&mut self, context: &MessageContext) {
+        self.notifiers.send(work_start_message(context), None);
+    }
+
+    fn remind(&mut self, time: u64, context: &MessageContext) {
+        self.notifiers.send(
+            reminder_message(time, context),
+            Some(self.action_tx.clone()),
+        );
+    }
+
+    fn escalate(&mut self, overdue: u64, level: u32, context: &MessageContext) {
+        self.notifiers.send(
+            escalation_message(overdue, level, context),
+            Some(self.action_tx.clone()),
+        );
+    }
+
+    fn budget_reached(&mut self, alert: &BudgetAlert, context: &MessageContext) {
+        self.notifiers.send(budget_message(alert, context), None);
+    }
+
+    fn after_hours(&mut self, context: &MessageContext) {
+        self.notifiers.send(after_hours_message(context), None);
+    }
+
+    fn snooze_requests(&mut self) -> Option<UnboundedReceiver<Duration>> {
+        self.snooze_rx.take()
+    }
+
+    fn reminder_actions(&mut self) -> Option<UnboundedReceiver<ReminderAction>> {
+        self.action_rx.take()
+    }
+
+    fn reload(&mut self) {
+        if let Err(e) = self.plugin_manager.load_plugins("plugins") {
+            println!(
+                "{} {}",
+                "Failed to load plugins:".bright_red().bold(),
+                e.to_string().red()
+            );
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::time::Duration;
+
+// Platform-specific imports
+#[cfg(windows)]
+use windows::Win32::{
+    System::SystemInformation::GetTickCount,
+    UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+};
+
+#[cfg(not(any(windows, target_os = "macos")))]
+use std::process::Command;
+
+/// Source of "time since the last keyboard or mouse input".
+///
+/// The monitor only asks for the current idle duration on each heartbeat, so tests
+/// can swap in a scripted implementation instead of the platform one.
+pub trait IdleSource: Send {
+    /// How long the user has been away from keyboard and mouse, or `None` if it
+    /// cannot be determined on this machine.
+    fn idle_duration(&mut self) -> Option<Duration>;
+}
+
+/// Pairs an [`IdleSource`] with the configured idle threshold.
+pub struct IdleDetector {
+    source: Box<dyn IdleSource>,
+    threshold: Option<Duration>,
+}
+
+impl IdleDetector {
+    pub fn new(source: Box<dyn IdleSource>, threshold_secs: Option<u64>) -> Self {
+        IdleDetector {
+            source,
+            threshold: threshold_secs.map(Duration::from_secs),
+        }
+    }
+
+    /// Returns how long the user has been idle once that reaches the threshold.
+    /// Always `None` when no threshold is configured or the source has no data.
+    pub fn exceeded(&mut self) -> Option<Duration> {
+        let threshold = self.threshold?;
+        self.source
+            .idle_duration()
+            .filter(|idle| *idle >= threshold)
+    }
+}
+
+/// Returns the idle source for the current platform.
+pub fn system_idle_source() -> Box<dyn IdleSource> {
+    Box::new(SystemIdleSource::default())
+}
+
+/// Idle source that never reports any idle time, used when idle detection is disabled.
+pub struct NoIdleSource;
+
+impl IdleSource for NoIdleSource {
+    fn idle_duration(&mut self) -> Option<Duration> {
+        None
+    }
+}
+
+#[cfg(windows)]
+#[derive(Default)]
+pub struct SystemIdleSource;
+
+#[cfg(windows)]
+impl IdleSource for SystemIdleSource {
+    fn idle_duration(&mut self) -> Option<Duration> {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+
+        unsafe {
+            if !GetLastInputInfo(&mut info).as_bool() {
+                return None;
+            }
+            // Both values are milliseconds since boot and wrap around together
+            let idle_millis = GetTickCount().wrapping_sub(info.dwTime);
+            Some(Duration::from_millis(idle_millis as u64))
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+#[derive(Default)]
+pub struct SystemIdleSource;
+
+#[cfg(target_os = "macos")]
+impl IdleSource for SystemIdleSource {
+    fn idle_duration(&mut self) -> Option<Duration> {
+        // IOHIDSystem exposes the HID idle counter in nanoseconds
+        let output = std::process::Command::new("ioreg")
+            .args(["-c", "IOHIDSystem", "-d", "4"])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        stdout
+            .lines()
+            .find(|line| line.contains("\"HIDIdleTime\""))
+            .and_then(|line| line.rsplit('=').next())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_nanos)
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+#[derive(Default)]
+pub struct SystemIdleSource {
+    backend: Option<LinuxIdleBackend>,
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LinuxIdleBackend {
+    // X11 screensaver idle counter, read through `xprintidle`
+    X11,
+    // GNOME/Mutter idle monitor on Wayland, read over D-Bus through `gdbus`
+    Mutter,
+    // Nothing usable was found, idle detection is disabled. Interrupt counters miss
+    // USB and Bluetooth input, so they are no fallback.
+    Unavailable,
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+impl IdleSource for SystemIdleSource {
+    fn idle_duration(&mut self) -> Option<Duration> {
+        let backend = *self.backend.get_or_insert_with(detect_linux_backend);
+        match backend {
+            LinuxIdleBackend::X11 => x11_idle_duration(),
+            LinuxIdleBackend::Mutter => mutter_idle_duration(),
+            LinuxIdleBackend::Unavailable => None,
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn detect_linux_backend() -> LinuxIdleBackend {
+    if std::env::var_os("DISPLAY").is_some() && x11_idle_duration().is_some() {
+        LinuxIdleBackend::X11
+    } else if std::env::var_os("WAYLAND_DISPLAY").is_some() && mutter_idle_duration().is_some() {
+        LinuxIdleBackend::Mutter
+    } else {
+        LinuxIdleBackend::Unavailable
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn x11_idle_duration() -> Option<Duration> {
+    let output = Command::new("xprintidle").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_millis)
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+fn mutter_idle_duration() -> Option<Duration> {
+    let output = Command::new("gdbus")
+        .args([
+            "call",
+            "--session",
+            "--dest",
+            "org.gnome.Mutter.IdleMonitor",
+            "--object-path",
+            "/org/gnome/Mutter/IdleMonitor/Core",
+            "--method",
+            "org.gnome.Mutter.IdleMonitor.GetIdletime",
+        ])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // Reply looks like "(uint64 1234,)"
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .trim_start_matches("(uint64")
+        .trim_end_matches(",)")
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .map(Duration::from_millis)
+}
@@ -1,79 +1,103 @@
-use crate::cli::{Cli, Command};
+use crate::cli::Cli;
 use crate::core::execute_command;
+use crate::core::messages::{Vars, tr, tr_with, use_locale};
 use clap::Parser;
 use colored::*;
 use rustyline::DefaultEditor;
 
 pub async fn run_interactive_mode() {
+    println!("{}", tr("interactive.welcome").bright_cyan().bold());
     println!(
         "{}",
-        "Welcome to Rest Reminder Interactive Mode"
-            .bright_cyan()
-            .bold()
+        "═══════════════════════════════════════════════".bright_cyan()
     );
     println!(
         "{}",
-        "═══════════════════════════════════════════════".bright_cyan()
+        tr("interactive.available_commands").bright_green().bold()
     );
-    println!("{}", "Available commands:".bright_green().bold());
     println!(
         "  {}              - {}",
         "rest".yellow().bold(),
-        "Start monitoring and remind you to rest".white()
+        tr("interactive.rest").white()
     );
     println!(
         "  {}             - {}",
         "count".yellow().bold(),
-        "Count work time between two days".white()
+        tr("interactive.count").white()
     );
     println!(
         "  {}  - {}",
         "count-single-day".yellow().bold(),
-        "Count work time for a specific day".white()
+        tr("interactive.count_single_day").white()
     );
     println!(
         "  {}     - {}",
         "count-precise".yellow().bold(),
-        "Count work time between precise timestamps".white()
+        tr("interactive.count_precise").white()
     );
     println!(
         "  {}      - {}",
         "count-by-task".yellow().bold(),
-        "Summarize work time grouped by task label".white()
+        tr("interactive.count_by_task").white()
+    );
+    println!(
+        "  {}       - {}",
+        "count-by-app".yellow().bold(),
+        tr("interactive.count_by_app").white()
+    );
+    println!(
+        "  {}       - {}",
+        "count-breaks".yellow().bold(),
+        tr("interactive.count_breaks").white()
     );
     println!(
         "  {}              - {}",
         "plot".yellow().bold(),
-        "Generate work time trend plot".white()
+        tr("interactive.plot").white()
     );
     println!(
         "  {}               - {}",
         "gen".yellow().bold(),
-        "(FOR DEV USE ONLY) Generate plugin template".white()
+        tr("interactive.gen").white()
     );
     println!(
         "  {}               - {}",
         "web".yellow().bold(),
-        "Start web mode".white()
+        tr("interactive.web").white()
+    );
+    println!(
+        "  {}            - {}",
+        "daemon".yellow().bold(),
+        tr("interactive.daemon").white()
+    );
+    println!(
+        "  {}               - {}",
+        "ctl".yellow().bold(),
+        tr("interactive.ctl").white()
     );
     println!(
         "  {}              - {}",
         "help".yellow().bold(),
-        "Show this help message".white()
+        tr("interactive.help").white()
     );
     println!(
         "  {}       - {}",
         "exit / quit".yellow().bold(),
-        "Exit the program".white()
+        tr("interactive.exit").white()
     );
     println!(
         "{}",
         "═══════════════════════════════════════════════".bright_cyan()
     );
     println!(
-        "{}: {}",
-        "Type a command and press Enter. Example",
-        "rest -t 3600 -a Cursor -l ~/Desktop/focus_log.txt".green()
+        "{}",
+        tr_with(
+            "interactive.example",
+            &Vars::new().set(
+                "example",
+                "rest -t 3600 -a Cursor -l ~/Desktop/focus_log.txt".green()
+            )
+        )
     );
     println!();
 
@@ -98,10 +122,7 @@ pub async fn run_interactive_mode() {
                 // Handle special commands
                 match line {
                     "exit" | "quit" | "q" | "ex" => {
-                        println!(
-                            "{}",
-                            "Goodbye! Thank you for using Rest Reminder!".blue().bold()
-                        );
+                        println!("{}", tr("interactive.goodbye").blue().bold());
                         break;
                     }
                     "help" | "h" => {
@@ -112,33 +133,31 @@ pub async fn run_interactive_mode() {
                 }
 
                 // Parse and execute the command
-                if let Some(command) = parse_interactive_command(line) {
-                    execute_command(command).await;
+                if let Some(cli) = parse_interactive_command(line) {
+                    // The language picked for one command stays for the rest of the session
+                    if cli.locale.is_some() || cli.messages.is_some() {
+                        use_locale(cli.locale.as_deref(), cli.messages.as_deref());
+                    }
+                    // The prompt reads stdin again afterwards, so `rest` must not listen for
+                    // snooze keys or take over signals
+                    execute_command(cli.cmd, false).await;
                 } else {
-                    println!(
-                        "{}",
-                        "Invalid command. Type 'help' for available commands."
-                            .red()
-                            .bold()
-                    );
+                    println!("{}", tr("interactive.invalid_command").red().bold());
                 }
             }
             Err(rustyline::error::ReadlineError::Interrupted) => {
-                println!(
-                    "{}",
-                    "Goodbye! Thank you for using Rest Reminder!".blue().bold()
-                );
+                println!("{}", tr("interactive.goodbye").blue().bold());
                 break;
             }
             Err(rustyline::error::ReadlineError::Eof) => {
-                println!(
-                    "{}",
-                    "Goodbye! Thank you for using Rest Reminder!".blue().bold()
-                );
+                println!("{}", tr("interactive.goodbye").blue().bold());
                 break;
             }
             Err(err) => {
-                println!("Error reading input: {}", err);
+                println!(
+                    "{}",
+                    tr_with("interactive.read_error", &Vars::new().set("error", err))
+                );
                 break;
             }
         }
@@ -148,16 +167,19 @@ pub async fn run_interactive_mode() {
     let _ = rl.save_history("rest_reminder_history.txt");
 }
 
-fn parse_interactive_command(input: &str) -> Option<Command> {
+fn parse_interactive_command(input: &str) -> Option<Cli> {
     // Prepend "rest-reminder" to make it parseable by clap
     let full_args = format!("rest-reminder {}", input);
     let args: Vec<&str> = full_args.split_whitespace().collect();
 
     // Try to parse the command
     match Cli::try_parse_from(args) {
-        Ok(cli) => Some(cli.cmd),
+        Ok(cli) => Some(cli),
         Err(e) => {
-            println!("Error parsing command: {}", e);
+            println!(
+                "{}",
+                tr_with("interactive.parse_error", &Vars::new().set("error", e))
+            );
             None
         }
     }
@@ -165,64 +187,169 @@ fn parse_interactive_command(input: &str) -> Option<Command> {
 
 fn show_help() {
     println!();
-    println!("{}", "Rest Reminder Commands:".bright_cyan().bold());
+    println!("{}", tr("help.title").bright_cyan().bold());
     println!("{}", "─────────────────────────────".bright_cyan());
     println!();
 
-    println!("{}", "MONITORING:".bright_green().bold());
+    println!("{}", tr("help.monitoring").bright_green().bold());
     println!("  {}", "rest [OPTIONS]".yellow().bold());
     println!(
         "    {}     {}",
         "-l, --log-to <PATH>".blue(),
-        "Log file location".white()
+        tr("help.log_to").white()
     );
     println!(
         "    {}    {}",
         "-t, --time <SECONDS>".blue(),
-        "Work time before reminder (default: 3600)".white()
+        tr("help.time").white()
     );
     println!(
         "    {}      {}",
         "-a, --app <APP>...".blue(),
-        "Applications to monitor".white()
+        tr("help.app").white()
     );
     println!(
         "    {}        {}",
         "--task <TASK>".blue(),
-        "Task label stored with new sessions".white()
+        tr("help.task").white()
+    );
+    println!(
+        "    {}           {}",
+        "--git-task".blue(),
+        tr("help.git_task").white()
+    );
+    println!(
+        "    {}     {}",
+        "--git-task-rule <RULE>".blue(),
+        tr("help.git_task_rule").white()
+    );
+    println!(
+        "    {}  {}",
+        "--task-rules <FILE>".blue(),
+        tr("help.task_rules").white()
+    );
+    println!(
+        "    {}     {}",
+        "--idle <SECONDS>".blue(),
+        tr("help.idle").white()
+    );
+    println!(
+        "    {}   {}",
+        "--snooze <SECONDS>".blue(),
+        tr("help.snooze").white()
+    );
+    println!(
+        "    {}  {}",
+        "--max-snoozes <COUNT>".blue(),
+        tr("help.max_snoozes").white()
+    );
+    println!(
+        "    {}  {}",
+        "--min-break <SECONDS>".blue(),
+        tr("help.min_break").white()
+    );
+    println!(
+        "    {}           {}",
+        "--pomodoro".blue(),
+        tr("help.pomodoro").white()
+    );
+    println!(
+        "    {}  {}",
+        "--short-break <SECONDS>".blue(),
+        tr("help.short_break").white()
+    );
+    println!(
+        "    {}   {}",
+        "--long-break <SECONDS>".blue(),
+        tr("help.long_break").white()
+    );
+    println!(
+        "    {}       {}",
+        "--cycles <COUNT>".blue(),
+        tr("help.cycles").white()
+    );
+    println!(
+        "    {} {}",
+        "--remind-every <SECONDS>".blue(),
+        tr("help.remind_every").white()
+    );
+    println!(
+        "    {}  {}",
+        "--daily-budget <SECONDS>".blue(),
+        tr("help.daily_budget").white()
+    );
+    println!(
+        "    {} {}",
+        "--weekly-budget <SECONDS>".blue(),
+        tr("help.weekly_budget").white()
+    );
+    println!(
+        "    {} {}",
+        "--budget-alerts <PERCENT>".blue(),
+        tr("help.budget_alerts").white()
+    );
+    println!(
+        "    {} {}",
+        "--start-debounce <SECONDS>".blue(),
+        tr("help.start_debounce").white()
+    );
+    println!(
+        "    {}    {}",
+        "--end-grace <SECONDS>".blue(),
+        tr("help.end_grace").white()
+    );
+    println!(
+        "    {}          {}",
+        "--schedule <FILE>".blue(),
+        tr("help.schedule").white()
+    );
+    println!(
+        "    {}     {}",
+        "--notify <EVENT=SINKS>".blue(),
+        tr("help.notify").white()
+    );
+    println!(
+        "    {}   {}",
+        "--poll-resting <SECONDS>".blue(),
+        tr("help.poll_resting").white()
+    );
+    println!(
+        "    {}   {}",
+        "--poll-working <SECONDS>".blue(),
+        tr("help.poll_working").white()
     );
     println!(
         "    {}: {}",
-        "Example".bright_magenta(),
+        tr("help.example").bright_magenta(),
         "rest -t 1800 -a Cursor Code".green()
     );
     println!();
 
-    println!("{}", "STATISTICS:".bright_green().bold());
+    println!("{}", tr("help.statistics").bright_green().bold());
     println!("  {}", "count [OPTIONS]".yellow().bold());
     println!(
         "    {}  {}",
         "-l, --log-location <PATH>".blue(),
-        "Log file path".white()
+        tr("help.log_path").white()
     );
     println!(
         "    {}         {}",
         "-s, --start <DATE>".blue(),
-        "Start date (YYYY-MM-DD)".white()
+        tr("help.start_date").white()
     );
     println!(
         "    {}           {}",
         "-e, --end <DATE>".blue(),
-        "End date (YYYY-MM-DD)".white()
+        tr("help.end_date").white()
     );
     println!(
         "    {}        {}",
         "--task <TASK>".blue(),
-        "Only count sessions with this task label".white()
+        tr("help.task_filter").white()
     );
     println!(
         "    {}: {}",
-        "Example".bright_magenta(),
+        tr("help.example").bright_magenta(),
         "count -s 2024-01-01 -e 2024-01-31".green()
     );
     println!();
@@ -231,21 +358,21 @@ fn show_help() {
     println!(
         "    {}  {}",
         "-l, --log-location <PATH>".blue(),
-        "Log file path".white()
+        tr("help.log_path").white()
     );
     println!(
         "    {}           {}",
         "-d, --day <DATE>".blue(),
-        "Date (YYYY-MM-DD)".white()
+        tr("help.day").white()
     );
     println!(
         "    {}        {}",
         "--task <TASK>".blue(),
-        "Only count sessions with this task label".white()
+        tr("help.task_filter").white()
     );
     println!(
         "    {}: {}",
-        "Example".bright_magenta(),
+        tr("help.example").bright_magenta(),
         "count-single-day -d 2024-01-15".green()
     );
     println!();
@@ -254,26 +381,26 @@ fn show_help() {
     println!(
         "    {}  {}",
         "-l, --log-location <PATH>".blue(),
-        "Log file path".white()
+        tr("help.log_path").white()
     );
     println!(
         "    {}     {}",
         "-s, --start <DATETIME>".blue(),
-        "Start time (YYYY-MM-DD HH:MM:SS)".white()
+        tr("help.start_time").white()
     );
     println!(
         "    {}       {}",
         "-e, --end <DATETIME>".blue(),
-        "End time (YYYY-MM-DD HH:MM:SS)".white()
+        tr("help.end_time").white()
     );
     println!(
         "    {}        {}",
         "--task <TASK>".blue(),
-        "Only count sessions with this task label".white()
+        tr("help.task_filter").white()
     );
     println!(
         "    {}: {}",
-        "Example".bright_magenta(),
+        tr("help.example").bright_magenta(),
         "count-precise -s \"2024-01-15 09:00:00\" -e \"2024-01-15 17:00:00\"".green()
     );
     println!();
@@ -282,77 +409,135 @@ fn show_help() {
     println!(
         "    {}  {}",
         "-l, --log-location <PATH>".blue(),
-        "Log file path".white()
+        tr("help.log_path").white()
     );
     println!(
         "    {}         {}",
         "-s, --start <DATE>".blue(),
-        "Start date (YYYY-MM-DD)".white()
+        tr("help.start_date").white()
     );
     println!(
         "    {}           {}",
         "-e, --end <DATE>".blue(),
-        "End date (YYYY-MM-DD)".white()
+        tr("help.end_date").white()
     );
     println!(
         "    {}: {}",
-        "Example".bright_magenta(),
+        tr("help.example").bright_magenta(),
         "count-by-task -s 2024-01-01 -e 2024-01-31".green()
     );
     println!();
 
-    println!("{}", "VISUALIZATION:".bright_green().bold());
+    println!("  {}", "count-breaks [OPTIONS]".yellow().bold());
+    println!(
+        "    {}  {}",
+        "-l, --log-location <PATH>".blue(),
+        tr("help.log_path").white()
+    );
+    println!(
+        "    {}         {}",
+        "-s, --start <DATE>".blue(),
+        tr("help.start_date").white()
+    );
+    println!(
+        "    {}           {}",
+        "-e, --end <DATE>".blue(),
+        tr("help.end_date").white()
+    );
+    println!(
+        "    {}: {}",
+        tr("help.example").bright_magenta(),
+        "count-breaks -s 2024-01-01 -e 2024-01-31".green()
+    );
+    println!();
+
+    println!("{}", tr("help.visualization").bright_green().bold());
     println!("  {}", "plot [OPTIONS]".yellow().bold());
     println!(
         "    {}   {}",
         "-l, --log-location <PATH>".blue(),
-        "Log file path".white()
+        tr("help.log_path").white()
     );
     println!(
         "    {}  {}",
         "-p, --plot-location <PATH>".blue(),
-        "Output plot file path".white()
+        tr("help.plot_location").white()
     );
     println!(
         "    {}          {}",
         "-s, --start <DATE>".blue(),
-        "Start date (YYYY-MM-DD)".white()
+        tr("help.start_date").white()
     );
     println!(
         "    {}            {}",
         "-e, --end <DATE>".blue(),
-        "End date (YYYY-MM-DD)".white()
+        tr("help.end_date").white()
     );
     println!(
         "    {}: {}",
-        "Example".bright_magenta(),
+        tr("help.example").bright_magenta(),
         "plot -s 2024-01-01 -e 2024-01-31 -p ~/work_trend.png".green()
     );
     println!();
 
-    println!("{}", "TEMPLATE GENERATOR:".bright_green().bold());
+    println!("{}", tr("help.template_generator").bright_green().bold());
     println!("  {}", "gen [OPTIONS]".yellow().bold());
     println!(
         "    {}   {}",
         "-n, --name <FILENAME>".blue(),
-        "Template file name".white()
+        tr("help.gen_name").white()
     );
     println!();
 
-    println!("{}", "WEB MODE STARTER:".bright_green().bold());
+    println!("{}", tr("help.web_mode").bright_green().bold());
     println!("  {}", "web".yellow().bold());
     println!();
 
-    println!("{}", "SYSTEM:".bright_green().bold());
+    println!("{}", tr("help.daemon_mode").bright_green().bold());
+    println!("  {}", "daemon [OPTIONS]".yellow().bold());
+    println!(
+        "    {}      {}",
+        "--socket <PATH>".blue(),
+        tr("help.socket").white()
+    );
+    println!(
+        "  {}",
+        "ctl <start|stop|pause|resume|status|set-task|snooze>"
+            .yellow()
+            .bold()
+    );
+    println!(
+        "    {}      {}",
+        "--socket <PATH>".blue(),
+        tr("help.socket").white()
+    );
+    println!(
+        "    {}: {}",
+        tr("help.example").bright_magenta(),
+        "ctl start -t 1800 -a code --task review".green()
+    );
+    println!();
+
+    println!("{}", tr("help.system").bright_green().bold());
+    println!(
+        "  {}      {}",
+        "--locale <LOCALE>".yellow().bold(),
+        tr("help.locale").white()
+    );
+    println!(
+        "  {}   {}",
+        "--messages <CATALOG>".yellow().bold(),
+        tr("help.messages").white()
+    );
     println!(
         "  {}                {}",
         "help, h".yellow().bold(),
-        "Show this help message".white()
+        tr("interactive.help").white()
     );
     println!(
         "  {}      {}",
         "exit, quit, q, ex".yellow().bold(),
-        "Exit interactive mode".white()
+        tr("help.exit").white()
     );
     println!();
 }
@@ -0,0 +1,134 @@
+use crate::core::core::log_file_path;
+use std::error::Error;
+use std::fmt;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+/// Lock file next to the log, e.g. `focus_log.txt.lock`.
+pub fn lock_path(log_file: &Path) -> PathBuf {
+    let mut file_name = log_file.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".lock");
+    log_file.with_file_name(file_name)
+}
+
+#[derive(Debug)]
+pub enum LockError {
+    /// Another monitor logs to the file, the process as the lock file names it.
+    Held {
+        log: PathBuf,
+        pid: Option<u32>,
+    },
+    Io(std::io::Error),
+}
+
+impl fmt::Display for LockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LockError::Held {
+                log,
+                pid: Some(pid),
+            } => write!(
+                f,
+                "{} is already monitored by process {}",
+                log.display(),
+                pid
+            ),
+            LockError::Held { log, pid: None } => {
+                write!(
+                    f,
+                    "{} is already monitored by another process",
+                    log.display()
+                )
+            }
+            LockError::Io(e) => write!(f, "Cannot lock the log file: {}", e),
+        }
+    }
+}
+
+impl Error for LockError {}
+
+impl From<std::io::Error> for LockError {
+    fn from(e: std::io::Error) -> Self {
+        LockError::Io(e)
+    }
+}
+
+/// Held by the monitor that logs to a file, so one started from a second terminal
+/// or the web UI cannot log the same work again.
+///
+/// The lock is an advisory `flock` (`LockFileEx` on Windows) on the lock file, which
+/// the system drops when the monitor dies. The file also names the monitor's pid:
+/// on file systems without locks, a lock file whose process is gone is stale and
+/// taken over.
+#[derive(Debug)]
+pub struct MonitorLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl MonitorLock {
+    pub fn acquire(log_location: &Path) -> Result<Self, LockError> {
+        let log = log_file_path(log_location);
+        let path = lock_path(&log);
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+        let recorded = read_pid(&mut file);
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Err(LockError::Held { log, pid: recorded }),
+            // Without locking support the pid decides
+            Err(TryLockError::Error(_)) => {
+                if let Some(pid) = recorded.filter(|pid| process_alive(*pid)) {
+                    return Err(LockError::Held {
+                        log,
+                        pid: Some(pid),
+                    });
+                }
+            }
+        }
+
+        file.set_len(0)?;
+        file.rewind()?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(MonitorLock { file, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+// The file itself stays: a monitor that opened it just before would otherwise hold a
+// lock on a deleted file while the next one locks a new one
+impl Drop for MonitorLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
+
+fn process_alive(pid: u32) -> bool {
+    if pid == std::process::id() {
+        return true;
+    }
+    let pid = Pid::from_u32(pid);
+    let mut sys = System::new();
+    sys.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        true,
+        ProcessRefreshKind::nothing(),
+    );
+    sys.process(pid).is_some()
+}
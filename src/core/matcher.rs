@@ -0,0 +1,418 @@
+use crate::core::activity::ActivityThreshold;
+use crate::core::probe::{ProcessInfo, ScanDetails};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const MINE_PREFIX: &str = "mine:";
+const THRESHOLD_SEPARATOR: char = '@';
+const ACTIVITY_SEPARATOR: char = '~';
+const ANCESTOR_SEPARATOR: &str = " > ";
+// Guards against parent links that loop after a pid was reused
+const MAX_ANCESTORS: usize = 64;
+
+/// How a monitored app is recognised in the process table.
+#[derive(Debug, Clone)]
+pub enum AppMatcher {
+    /// Process name contains the text. This is what plain strings have always meant.
+    Contains(String),
+    /// Process name is exactly the text (`exact:`).
+    Exact(String),
+    /// Process name equals the text, ignoring case (`iname:`).
+    IgnoreCase(String),
+    /// Process name matches a regular expression (`regex:`).
+    Regex(Regex),
+    /// Process name matches a shell-style glob with `*`, `?` and `[...]` (`glob:`).
+    Glob(Regex),
+    /// Executable path equals the path, or ends with it component-wise (`exe:`).
+    Exe(String),
+    /// Full command line contains the text (`cmd:`).
+    Cmdline(String),
+    /// Working directory is the path or below it (`cwd:`), `~` is the home directory.
+    Cwd(PathBuf),
+    /// Any process (`*`), e.g. everything running below a terminal.
+    Any,
+}
+
+impl AppMatcher {
+    fn matches(&self, process: &ProcessInfo) -> bool {
+        match self {
+            AppMatcher::Contains(text) => process.name.contains(text.as_str()),
+            AppMatcher::Exact(text) => process.name == *text,
+            AppMatcher::IgnoreCase(text) => process.name.eq_ignore_ascii_case(text),
+            AppMatcher::Regex(regex) | AppMatcher::Glob(regex) => regex.is_match(&process.name),
+            AppMatcher::Exe(path) => process.exe.as_deref().is_some_and(|exe| {
+                exe == path.as_str() || Path::new(exe).ends_with(Path::new(path))
+            }),
+            AppMatcher::Cmdline(text) => process.cmd.contains(text.as_str()),
+            AppMatcher::Cwd(path) => process
+                .cwd
+                .as_deref()
+                .is_some_and(|cwd| Path::new(cwd).starts_with(path)),
+            AppMatcher::Any => true,
+        }
+    }
+
+    fn scan_details(&self) -> ScanDetails {
+        ScanDetails {
+            exe: matches!(self, AppMatcher::Exe(_)),
+            cmd: matches!(self, AppMatcher::Cmdline(_)),
+            cwd: matches!(self, AppMatcher::Cwd(_)),
+            ..ScanDetails::default()
+        }
+    }
+}
+
+// One `[mine:][kind:]value` part of a rule
+#[derive(Debug, Clone)]
+struct Link {
+    matcher: AppMatcher,
+    mine_only: bool,
+}
+
+impl Link {
+    fn matches(&self, process: &ProcessInfo, current_user: Option<&str>) -> bool {
+        if self.mine_only {
+            let owned = match (process.user_id.as_deref(), current_user) {
+                (Some(owner), Some(me)) => owner == me,
+                _ => false,
+            };
+            if !owned {
+                return false;
+            }
+        }
+        self.matcher.matches(process)
+    }
+
+    fn scan_details(&self) -> ScanDetails {
+        self.matcher.scan_details()
+            | ScanDetails {
+                user: self.mine_only,
+                ..ScanDetails::default()
+            }
+    }
+}
+
+impl FromStr for Link {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let source = source.trim();
+        let (mine_only, rest) = match source.strip_prefix(MINE_PREFIX) {
+            Some(rest) => (true, rest),
+            None => (false, source),
+        };
+
+        let matcher = match rest.split_once(':') {
+            Some(("exact", text)) => AppMatcher::Exact(non_empty(text)?),
+            Some(("iname", text)) => AppMatcher::IgnoreCase(non_empty(text)?),
+            Some(("regex", pattern)) => AppMatcher::Regex(
+                Regex::new(&non_empty(pattern)?)
+                    .map_err(|e| format!("Invalid regex '{}': {}", pattern, e))?,
+            ),
+            Some(("glob", pattern)) => AppMatcher::Glob(glob_to_regex(&non_empty(pattern)?)?),
+            Some(("exe", path)) => AppMatcher::Exe(non_empty(path)?),
+            Some(("cmd", text)) => AppMatcher::Cmdline(non_empty(text)?),
+            Some(("cwd", path)) => AppMatcher::Cwd(expand_home(&non_empty(path)?)),
+            _ if rest == "*" => AppMatcher::Any,
+            // Anything else, including names that happen to contain ':', keeps the old meaning
+            _ => AppMatcher::Contains(non_empty(rest)?),
+        };
+        Ok(Link { matcher, mine_only })
+    }
+}
+
+/// Parent links of a process snapshot, for rules that look at the ancestors of a
+/// process.
+pub struct ProcessTree<'a> {
+    by_pid: HashMap<u32, &'a ProcessInfo>,
+}
+
+impl<'a> ProcessTree<'a> {
+    pub fn new(processes: &'a [ProcessInfo]) -> Self {
+        ProcessTree {
+            by_pid: processes
+                .iter()
+                .map(|process| (process.pid, process))
+                .collect(),
+        }
+    }
+
+    /// The parent of `process`, its parent and so on, as far as they are running.
+    pub fn ancestors(&self, process: &ProcessInfo) -> Vec<&'a ProcessInfo> {
+        let mut ancestors = Vec::new();
+        let mut parent = process.parent;
+        while let Some(pid) = parent
+            && ancestors.len() < MAX_ANCESTORS
+        {
+            let Some(process) = self.by_pid.get(&pid).copied() else {
+                break;
+            };
+            // pid 0 is its own parent on some platforms
+            if process.parent == Some(pid) {
+                ancestors.push(process);
+                break;
+            }
+            ancestors.push(process);
+            parent = process.parent;
+        }
+        ancestors
+    }
+}
+
+/// A single `--app` / `app_list` entry.
+///
+/// Rules are written as `[mine:][kind:]value[@seconds][~activity]`, e.g. `code`,
+/// `exact:code`, `regex:^rust(rover|-analyzer)$` or `mine:cmd:my_notebook_server.py`.
+/// The `mine:` prefix restricts the rule to processes owned by the current user, an
+/// `@seconds` suffix such as `steam@1200` gives the app its own work threshold, and a
+/// `~` suffix such as `code~cpu=2` only counts the app while it is busy (see
+/// [`ActivityThreshold`]).
+///
+/// Parts joined with ` > ` match on ancestry: `tmux > nvim` is an `nvim` started
+/// anywhere below `tmux`, `alacritty > cwd:~/work` anything below `alacritty` working
+/// in `~/work`.
+#[derive(Debug, Clone)]
+pub struct AppRule {
+    source: String,
+    link: Link,
+    // Outermost first
+    ancestors: Vec<Link>,
+    threshold: Option<u64>,
+    activity: Option<ActivityThreshold>,
+}
+
+impl AppRule {
+    /// The rule as it was written, without its threshold. Used as the app's name in
+    /// the log.
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Seconds this app may run within a session before the break reminder fires.
+    pub fn threshold(&self) -> Option<u64> {
+        self.threshold
+    }
+
+    /// How busy the app has to be to count as running.
+    pub fn activity(&self) -> Option<&ActivityThreshold> {
+        self.activity.as_ref()
+    }
+
+    pub fn matcher(&self) -> &AppMatcher {
+        &self.link.matcher
+    }
+
+    pub fn mine_only(&self) -> bool {
+        self.link.mine_only
+    }
+
+    /// Whether the rule looks at the ancestors of a process.
+    pub fn has_ancestors(&self) -> bool {
+        !self.ancestors.is_empty()
+    }
+
+    /// The process attributes a scan has to read for this rule.
+    pub fn scan_details(&self) -> ScanDetails {
+        let usage = self
+            .activity
+            .is_some_and(|activity| activity.cpu.is_some() || activity.io.is_some());
+        self.ancestors.iter().fold(
+            self.link.scan_details()
+                | ScanDetails {
+                    usage,
+                    ..ScanDetails::default()
+                },
+            |details, ancestor| details | ancestor.scan_details(),
+        )
+    }
+
+    /// Whether the process itself fits the rule. The ancestors a ` > ` rule asks for
+    /// are only checked by [`AppRule::matches_in`].
+    pub fn matches(&self, process: &ProcessInfo, current_user: Option<&str>) -> bool {
+        self.link.matches(process, current_user)
+    }
+
+    /// Whether the process fits the rule, ancestors included.
+    pub fn matches_in(
+        &self,
+        process: &ProcessInfo,
+        tree: &ProcessTree,
+        current_user: Option<&str>,
+    ) -> bool {
+        if !self.matches(process, current_user) {
+            return false;
+        }
+        // Each part has to match further up than the one after it
+        let mut wanted = self.ancestors.iter().rev().peekable();
+        for ancestor in tree.ancestors(process) {
+            if wanted
+                .peek()
+                .is_some_and(|link| link.matches(ancestor, current_user))
+            {
+                wanted.next();
+            }
+        }
+        wanted.peek().is_none()
+    }
+}
+
+impl FromStr for AppRule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        // Like the threshold, only a known activity makes a suffix
+        let (value, activity) = match value.rsplit_once(ACTIVITY_SEPARATOR) {
+            Some((rule, spec)) if ActivityThreshold::recognised(spec) => {
+                (rule.trim(), Some(spec.parse::<ActivityThreshold>()?))
+            }
+            _ => (value, None),
+        };
+        // Only digits after the last '@' make a threshold, so `@` stays usable in patterns
+        let (source, threshold) = match value.rsplit_once(THRESHOLD_SEPARATOR) {
+            Some((rule, seconds))
+                if !seconds.is_empty() && seconds.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                let seconds = seconds
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|seconds| *seconds > 0)
+                    .ok_or_else(|| format!("Invalid threshold in app rule '{}'", value))?;
+                (rule.trim().to_string(), Some(seconds))
+            }
+            _ => (value.to_string(), None),
+        };
+        let mut ancestors = source
+            .split(ANCESTOR_SEPARATOR)
+            .map(str::parse::<Link>)
+            .collect::<Result<Vec<_>, _>>()?;
+        let link = ancestors.pop().ok_or("App rule must not be empty")?;
+
+        Ok(AppRule {
+            source,
+            link,
+            ancestors,
+            threshold,
+            activity,
+        })
+    }
+}
+
+impl fmt::Display for AppRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)?;
+        if let Some(seconds) = self.threshold {
+            write!(f, "{}{}", THRESHOLD_SEPARATOR, seconds)?;
+        }
+        match &self.activity {
+            Some(activity) => write!(f, "{}{}", ACTIVITY_SEPARATOR, activity),
+            None => Ok(()),
+        }
+    }
+}
+
+// Rules are identified by how they were written
+impl PartialEq for AppRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+            && self.threshold == other.threshold
+            && self.activity == other.activity
+    }
+}
+
+impl Eq for AppRule {}
+
+/// Parses every entry, failing on the first invalid rule.
+pub fn parse_app_rules(apps: &[String]) -> Result<Vec<AppRule>, String> {
+    apps.iter().map(|app| app.parse()).collect()
+}
+
+/// The source strings of the rules, as stored in the log.
+pub fn rule_names(rules: &[AppRule]) -> Vec<String> {
+    rules.iter().map(|rule| rule.as_str().to_string()).collect()
+}
+
+/// Names of the rules matching at least one process in the snapshot.
+pub fn matching_rules(
+    processes: &[ProcessInfo],
+    rules: &[AppRule],
+    current_user: Option<&str>,
+) -> Vec<String> {
+    let tree = ProcessTree::new(processes);
+    rules
+        .iter()
+        .filter(|rule| {
+            processes
+                .iter()
+                .any(|process| rule.matches_in(process, &tree, current_user))
+        })
+        .map(|rule| rule.as_str().to_string())
+        .collect()
+}
+
+/// Whether any process in the snapshot matches one of the rules.
+pub fn any_rule_matches(
+    processes: &[ProcessInfo],
+    rules: &[AppRule],
+    current_user: Option<&str>,
+) -> bool {
+    let tree = ProcessTree::new(processes);
+    processes.iter().any(|process| {
+        rules
+            .iter()
+            .any(|rule| rule.matches_in(process, &tree, current_user))
+    })
+}
+
+fn non_empty(value: &str) -> Result<String, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        Err("App rule must not be empty".to_string())
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            PathBuf::from(home).join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+fn glob_to_regex(glob: &str) -> Result<Regex, String> {
+    let mut pattern = String::from("^");
+    let mut in_class = false;
+    for ch in glob.chars() {
+        match ch {
+            '*' if !in_class => pattern.push_str(".*"),
+            '?' if !in_class => pattern.push('.'),
+            '[' if !in_class => {
+                in_class = true;
+                pattern.push('[');
+            }
+            ']' if in_class => {
+                in_class = false;
+                pattern.push(']');
+            }
+            '!' if in_class && pattern.ends_with('[') => pattern.push('^'),
+            // Ranges such as `[a-z]` keep their meaning inside a class
+            '\\' | '[' if in_class => {
+                pattern.push('\\');
+                pattern.push(ch);
+            }
+            '^' if in_class && pattern.ends_with('[') => pattern.push_str("\\^"),
+            _ if in_class => pattern.push(ch),
+            _ => pattern.push_str(&regex::escape(&ch.to_string())),
+        }
+    }
+    pattern.push('$');
+
+    Regex::new(&pattern).map_err(|e| format!("Invalid glob '{}': {}", glob, e))
+}
@@ -0,0 +1,353 @@
+use colored::*;
+use rand::Rng;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+
+/// Directory with the user's catalogs, `messages/<locale>.json`, looked up next to `plugins/`.
+pub const CATALOG_DIR: &str = "messages";
+
+// Catalogs shipped with the binary. English is complete and backs every other locale.
+const BUILT_IN: [(&str, &str); 2] = [
+    ("en", include_str!("../../locales/en.json")),
+    ("zh_CN", include_str!("../../locales/zh_CN.json")),
+];
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([a-z_]+)\}").unwrap());
+
+static CATALOG: LazyLock<RwLock<Arc<Catalog>>> =
+    LazyLock::new(|| RwLock::new(Arc::new(Catalog::english())));
+
+/// How a message is picked from a pool of variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Selection {
+    /// Random, with each variant as likely as its weight.
+    #[default]
+    Random,
+    /// Every variant in turn, ignoring weights.
+    Rotate,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum VariantFile {
+    Text(String),
+    Weighted { text: String, weight: u32 },
+}
+
+// The JSON layout of a catalog. Every part is optional, whatever is missing is
+// taken from the catalog underneath.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CatalogFile {
+    selection: Option<Selection>,
+    #[serde(default)]
+    pools: HashMap<String, Vec<VariantFile>>,
+    #[serde(default)]
+    strings: HashMap<String, String>,
+}
+
+/// Texts for one locale: single strings looked up by key, and pools of variants
+/// the reminders pick from.
+#[derive(Debug)]
+pub struct Catalog {
+    locale: String,
+    selection: Selection,
+    strings: HashMap<String, String>,
+    pools: HashMap<String, Vec<(String, u32)>>,
+    // Next variant of each pool when rotating
+    rotation: Mutex<HashMap<String, usize>>,
+}
+
+impl Catalog {
+    /// The built-in English catalog.
+    pub fn english() -> Self {
+        let mut catalog = Catalog {
+            locale: "en".to_string(),
+            selection: Selection::default(),
+            strings: HashMap::new(),
+            pools: HashMap::new(),
+            rotation: Mutex::new(HashMap::new()),
+        };
+        catalog
+            .merge_str(BUILT_IN[0].1)
+            .expect("built-in English catalog should parse");
+        catalog
+    }
+
+    /// The built-in catalog for `locale`, e.g. `zh_CN`. A locale without its own
+    /// catalog uses the one of its language, and English for anything missing.
+    pub fn built_in(locale: &str) -> Self {
+        let mut catalog = Catalog::english();
+        let wanted = language(locale);
+        let found = BUILT_IN
+            .iter()
+            .skip(1)
+            .find(|(name, _)| *name == locale)
+            .or_else(|| {
+                BUILT_IN
+                    .iter()
+                    .skip(1)
+                    .find(|(name, _)| language(name) == wanted)
+            });
+        if let Some((name, source)) = found {
+            catalog
+                .merge_str(source)
+                .expect("built-in catalogs should parse");
+            catalog.locale = name.to_string();
+        }
+        catalog
+    }
+
+    /// The built-in catalog for `locale` with the user's own on top:
+    /// `messages/<language>.json`, `messages/<locale>.json` and then `file`.
+    pub fn load(locale: &str, file: Option<&Path>) -> Result<Self, String> {
+        let mut catalog = Catalog::built_in(locale);
+        let dir = Path::new(CATALOG_DIR);
+        for name in [language(locale), locale] {
+            let path = dir.join(format!("{}.json", name));
+            if path.is_file() {
+                catalog = catalog.with_file(&path)?;
+            }
+        }
+        match file {
+            Some(file) => catalog.with_file(file),
+            None => Ok(catalog),
+        }
+    }
+
+    /// Overrides strings, pools and the selection with the ones in the JSON
+    /// catalog at `path`.
+    pub fn with_file(mut self, path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        self.merge_str(&source)
+            .map_err(|e| format!("Invalid message catalog {}: {}", path.display(), e))?;
+        Ok(self)
+    }
+
+    fn merge_str(&mut self, source: &str) -> Result<(), serde_json::Error> {
+        let file: CatalogFile = serde_json::from_str(source)?;
+        if let Some(selection) = file.selection {
+            self.selection = selection;
+        }
+        self.strings.extend(file.strings);
+        for (name, variants) in file.pools {
+            let variants: Vec<(String, u32)> = variants
+                .into_iter()
+                .map(|variant| match variant {
+                    VariantFile::Text(text) => (text, 1),
+                    VariantFile::Weighted { text, weight } => (text, weight),
+                })
+                .filter(|(_, weight)| *weight > 0)
+                .collect();
+            // An empty pool would leave nothing to say, keep the one underneath
+            if !variants.is_empty() {
+                self.pools.insert(name, variants);
+            }
+        }
+        Ok(())
+    }
+
+    /// Locale of the built-in catalog in use, `en` if there is none for the
+    /// requested one.
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// The string under `key`, or the key itself if no catalog has it.
+    pub fn text(&self, key: &str) -> String {
+        self.render(key, &Vars::new())
+    }
+
+    /// The string under `key` with its placeholders filled in from `vars`.
+    pub fn render(&self, key: &str, vars: &Vars) -> String {
+        let template = self.strings.get(key).map(String::as_str).unwrap_or(key);
+        vars.fill(template)
+    }
+
+    /// One variant of the pool `name`, filled in from `vars`.
+    pub fn pick(&self, name: &str, vars: &Vars) -> String {
+        let Some(variants) = self.pools.get(name) else {
+            return name.to_string();
+        };
+        let index = match self.selection {
+            Selection::Random => {
+                let total: u32 = variants.iter().map(|(_, weight)| weight).sum();
+                let mut roll = rand::rng().random_range(0..total);
+                variants
+                    .iter()
+                    .position(|(_, weight)| {
+                        let hit = roll < *weight;
+                        roll = roll.saturating_sub(*weight);
+                        hit
+                    })
+                    .unwrap_or_default()
+            }
+            Selection::Rotate => {
+                let mut rotation = self.rotation.lock().unwrap();
+                let next = rotation.entry(name.to_string()).or_default();
+                let index = *next % variants.len();
+                *next = index + 1;
+                index
+            }
+        };
+        vars.fill(&variants[index].0)
+    }
+
+    /// `seconds` as people say it, e.g. "1 hour 5 minutes". Seconds are only
+    /// spelled out below a minute.
+    pub fn duration(&self, seconds: u64) -> String {
+        let unit = |count: u64, one: &str, many: &str| {
+            let key = if count == 1 { one } else { many };
+            self.render(key, &Vars::new().set("count", count))
+        };
+        let (hours, minutes) = (seconds / 3600, seconds % 3600 / 60);
+        match (hours, minutes) {
+            (0, 0) => unit(seconds, "duration.second", "duration.seconds"),
+            (0, _) => unit(minutes, "duration.minute", "duration.minutes"),
+            (_, 0) => unit(hours, "duration.hour", "duration.hours"),
+            _ => self.render(
+                "duration.hours_minutes",
+                &Vars::new()
+                    .set("hours", unit(hours, "duration.hour", "duration.hours"))
+                    .set(
+                        "minutes",
+                        unit(minutes, "duration.minute", "duration.minutes"),
+                    ),
+            ),
+        }
+    }
+}
+
+/// Values for the `{name}` placeholders of a template. Placeholders without a
+/// value are left as they are.
+#[derive(Debug, Clone, Default)]
+pub struct Vars {
+    values: HashMap<&'static str, String>,
+}
+
+impl Vars {
+    pub fn new() -> Self {
+        Vars::default()
+    }
+
+    pub fn set(mut self, name: &'static str, value: impl Display) -> Self {
+        self.values.insert(name, value.to_string());
+        self
+    }
+
+    fn fill(&self, template: &str) -> String {
+        PLACEHOLDER
+            .replace_all(template, |captures: &Captures| {
+                self.values
+                    .get(&captures[1])
+                    .cloned()
+                    .unwrap_or_else(|| captures[0].to_string())
+            })
+            .into_owned()
+    }
+}
+
+/// What a reminder can tell about the work it interrupts.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageContext {
+    /// Task label of the open session.
+    pub task: Option<String>,
+    /// Monitored applications.
+    pub apps: Vec<String>,
+    /// Work logged today, including the open session.
+    pub worked_today: u64,
+    /// Breaks taken today.
+    pub breaks_today: usize,
+}
+
+impl MessageContext {
+    /// The `{task}`, `{apps}`, `{today}` and `{breaks}` placeholders.
+    pub fn vars(&self, catalog: &Catalog) -> Vars {
+        let apps = if self.apps.is_empty() {
+            catalog.text("template.no_apps")
+        } else {
+            self.apps.join(", ")
+        };
+        Vars::new()
+            .set(
+                "task",
+                self.task
+                    .clone()
+                    .unwrap_or_else(|| catalog.text("template.no_task")),
+            )
+            .set("apps", apps)
+            .set("today", catalog.duration(self.worked_today))
+            .set("breaks", self.breaks_today)
+    }
+}
+
+/// The catalog messages are currently taken from, English until [`use_locale`]
+/// or [`set_catalog`] picks another.
+pub fn catalog() -> Arc<Catalog> {
+    CATALOG.read().unwrap().clone()
+}
+
+pub fn set_catalog(catalog: Catalog) {
+    *CATALOG.write().unwrap() = Arc::new(catalog);
+}
+
+/// Switches to `locale`, or to the one in the environment if `None`, with the
+/// user's catalogs and `file` on top. Keeps the built-in texts if those fail to load.
+pub fn use_locale(locale: Option<&str>, file: Option<&Path>) {
+    let locale = locale.map(normalize_locale).unwrap_or_else(env_locale);
+    let catalog = Catalog::load(&locale, file).unwrap_or_else(|e| {
+        println!(
+            "{} {}",
+            "Failed to load messages:".bright_red().bold(),
+            e.red()
+        );
+        Catalog::built_in(&locale)
+    });
+    set_catalog(catalog);
+}
+
+/// The locale from `LC_ALL`, `LC_MESSAGES` or `LANG`, in that order. `C` and
+/// `POSIX` mean English.
+pub fn env_locale() -> String {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| normalize_locale(&value))
+        .filter(|locale| locale != "C" && locale != "POSIX")
+        .unwrap_or_else(|| "en".to_string())
+}
+
+/// Strips encoding and modifier, e.g. `zh_CN.UTF-8` becomes `zh_CN`. `zh-CN`
+/// is accepted as well.
+pub fn normalize_locale(locale: &str) -> String {
+    locale
+        .split(['.', '@'])
+        .next()
+        .unwrap_or_default()
+        .replace('-', "_")
+}
+
+fn language(locale: &str) -> &str {
+    locale.split('_').next().unwrap_or(locale)
+}
+
+/// The string under `key` in the current catalog.
+pub fn tr(key: &str) -> String {
+    catalog().text(key)
+}
+
+/// The string under `key` in the current catalog, filled in from `vars`.
+pub fn tr_with(key: &str, vars: &Vars) -> String {
+    catalog().render(key, vars)
+}
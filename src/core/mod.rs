@@ -1,18 +1,61 @@
-use crate::cli::Command;
-use crate::core::core::run_rest_reminder;
+use crate::cli::{Command, CtlCommand};
+use crate::core::budget::BudgetConfig;
+use crate::core::core::{
+    MonitorConfig, MonitorControl, MonitorStatus, recover_orphaned_session, run_rest_reminder,
+};
+use crate::core::engine::PomodoroConfig;
+use crate::core::git_task::GitTaskConfig;
+use crate::core::lock::{LockError, MonitorLock};
+use crate::core::messages::{Vars, tr, tr_with};
+use crate::core::notify::NotifyConfig;
+use crate::core::poll::PollIntervals;
+use crate::core::signals::send_signals_to;
+use crate::core::snooze::{keyboard_snoozes, restore_terminal};
+use crate::core::suspend::listen_for_sleep;
+use crate::daemon::default_socket_path;
 use crate::plugin::template::generate_plugin_template;
 use crate::statistic::plotter::plot;
 use crate::statistic::statistics::{
-    acc_work_time_for_task, acc_work_time_precise_for_task, single_day_work_time_for_task,
-    task_work_time_summary,
+    acc_work_time_for_task, acc_work_time_precise_for_task, app_work_time_summary, break_summary,
+    overtime_work_time_for_task, overtime_work_time_precise_for_task,
+    single_day_work_time_for_task, task_work_time_summary,
 };
 use crate::web::web::spawn_web_server;
 use colored::Colorize;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::ExitCode;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Duration;
 
+pub mod activity;
+pub mod budget;
+pub mod checkpoint;
+pub mod clock;
 pub mod core;
+#[cfg(not(any(windows, target_os = "macos")))]
+pub mod dbus;
+pub mod engine;
+pub mod git_task;
+pub mod hooks;
+pub mod idle;
 pub mod interactive;
+pub mod lock;
+pub mod matcher;
+pub mod messages;
+pub mod notify;
+pub mod poll;
+pub mod probe;
+pub mod schedule;
+pub mod signals;
+pub mod snooze;
+pub mod suspend;
+pub mod task_rules;
 
-pub async fn execute_command(cmd: Command) {
+/// Runs a parsed command. `standalone` is set when the command owns the process:
+/// `rest` then reads snooze requests from stdin, handles Unix signals and reports
+/// the signal that stopped it through the exit status.
+pub async fn execute_command(cmd: Command, standalone: bool) -> ExitCode {
     match cmd {
         Command::CountPrecise {
             log_location,
@@ -20,11 +63,14 @@ pub async fn execute_command(cmd: Command) {
             end,
             task,
         } => {
-            let sec = acc_work_time_precise_for_task(log_location, start, end, task.as_deref())
-                .expect("Failed to calculate your work time.") as f64;
-            println!("You worked {:?} seconds during this period of time", sec);
-            println!("Or {:?} minutes", sec / 60.0);
-            println!("Or {:?} hours", sec / 3600.0);
+            let sec =
+                acc_work_time_precise_for_task(log_location.clone(), start, end, task.as_deref())
+                    .expect("Failed to calculate your work time.") as f64;
+            print_worked("cli.worked_period", sec);
+            let overtime =
+                overtime_work_time_precise_for_task(log_location, start, end, task.as_deref())
+                    .expect("Failed to calculate your overtime.") as f64;
+            print_overtime(overtime);
         }
         Command::Count {
             log_location,
@@ -32,22 +78,26 @@ pub async fn execute_command(cmd: Command) {
             end_day,
             task,
         } => {
-            let sec = acc_work_time_for_task(log_location, start_day, end_day, task.as_deref())
-                .expect("Failed to calculate your work time.") as f64;
-            println!("You worked {:?} seconds during these days", sec);
-            println!("Or {:?} minutes", sec / 60.0);
-            println!("Or {:?} hours", sec / 3600.0);
+            let sec =
+                acc_work_time_for_task(log_location.clone(), start_day, end_day, task.as_deref())
+                    .expect("Failed to calculate your work time.") as f64;
+            print_worked("cli.worked_days", sec);
+            let overtime =
+                overtime_work_time_for_task(log_location, start_day, end_day, task.as_deref())
+                    .expect("Failed to calculate your overtime.") as f64;
+            print_overtime(overtime);
         }
         Command::CountSingleDay {
             log_location,
             day,
             task,
         } => {
-            let sec = single_day_work_time_for_task(log_location, day, task.as_deref())
+            let sec = single_day_work_time_for_task(log_location.clone(), day, task.as_deref())
                 .expect("Failed to calculate your work time.") as f64;
-            println!("You worked {:?} seconds during this day", sec);
-            println!("Or {:?} minutes", sec / 60.0);
-            println!("Or {:?} hours", sec / 3600.0);
+            print_worked("cli.worked_day", sec);
+            let overtime = overtime_work_time_for_task(log_location, day, day, task.as_deref())
+                .expect("Failed to calculate your overtime.") as f64;
+            print_overtime(overtime);
         }
         Command::CountByTask {
             log_location,
@@ -57,27 +107,161 @@ pub async fn execute_command(cmd: Command) {
             let summaries = task_work_time_summary(log_location, start_day, end_day)
                 .expect("Failed to calculate your task summary.");
             if summaries.is_empty() {
-                println!("No work sessions found during these days");
+                println!("{}", tr("cli.no_sessions"));
             } else {
                 for summary in summaries {
-                    println!(
-                        "{}: {} seconds ({:.2} minutes, {:.2} hours)",
-                        summary.task,
-                        summary.seconds,
-                        summary.seconds as f64 / 60.0,
-                        summary.seconds as f64 / 3600.0
-                    );
+                    let vars = Vars::new()
+                        .set("task", summary.task)
+                        .set("seconds", summary.seconds)
+                        .set("minutes", format!("{:.2}", summary.seconds as f64 / 60.0))
+                        .set("hours", format!("{:.2}", summary.seconds as f64 / 3600.0));
+                    println!("{}", tr_with("cli.task_summary", &vars));
+                }
+            }
+        }
+        Command::CountByApp {
+            log_location,
+            start_day,
+            end_day,
+        } => {
+            let summaries = app_work_time_summary(log_location, start_day, end_day)
+                .expect("Failed to calculate your app summary.");
+            if summaries.is_empty() {
+                println!("{}", tr("cli.no_sessions"));
+            } else {
+                for summary in summaries {
+                    let vars = Vars::new()
+                        .set("app", summary.app)
+                        .set("seconds", summary.seconds)
+                        .set("minutes", format!("{:.2}", summary.seconds as f64 / 60.0))
+                        .set("hours", format!("{:.2}", summary.seconds as f64 / 3600.0));
+                    println!("{}", tr_with("cli.app_summary", &vars));
                 }
             }
         }
+        Command::CountBreaks {
+            log_location,
+            start_day,
+            end_day,
+        } => {
+            let summary = break_summary(log_location, start_day, end_day)
+                .expect("Failed to calculate your breaks.");
+            if summary.breaks == 0 {
+                println!("{}", tr("cli.no_breaks"));
+            } else {
+                let vars = Vars::new()
+                    .set("breaks", summary.breaks)
+                    .set("seconds", summary.total_seconds)
+                    .set(
+                        "minutes",
+                        format!("{:.2}", summary.total_seconds as f64 / 60.0),
+                    )
+                    .set("average", summary.average_seconds)
+                    .set("longest", summary.longest_seconds)
+                    .set("violations", summary.violations);
+                println!("{}", tr_with("cli.breaks_total", &vars));
+                println!("{}", tr_with("cli.breaks_average", &vars));
+                println!("{}", tr_with("cli.breaks_violations", &vars));
+            }
+        }
         Command::Rest {
             log_to,
             time,
             app,
             task,
+            git_task,
+            git_task_rule,
+            task_rules,
+            idle,
+            snooze,
+            max_snoozes,
+            min_break,
+            pomodoro,
+            short_break,
+            long_break,
+            cycles,
+            remind_every,
+            daily_budget,
+            weekly_budget,
+            budget_alerts,
+            start_debounce,
+            end_grace,
+            schedule,
+            notify,
+            poll_resting,
+            poll_working,
         } => {
-            println!("{}", "Starting Rest Reminder...".bright_yellow().bold());
-            run_rest_reminder(log_to, time, app, task, None).await;
+            // Held until the monitor returns, so nobody else logs to the same file meanwhile
+            let _lock = match MonitorLock::acquire(&log_to) {
+                Ok(lock) => lock,
+                Err(LockError::Held { log, pid }) => {
+                    let pid = pid.map_or("?".to_string(), |pid| pid.to_string());
+                    let vars = Vars::new().set("log", log.display()).set("pid", pid);
+                    println!("{}", tr_with("cli.log_locked", &vars).bright_red().bold());
+                    return ExitCode::FAILURE;
+                }
+                Err(e) => {
+                    println!(
+                        "{} {}",
+                        "Failed to start monitoring:".bright_red().bold(),
+                        e.to_string().red()
+                    );
+                    return ExitCode::FAILURE;
+                }
+            };
+            println!("{}", tr("cli.starting").bright_yellow().bold());
+            let config = MonitorConfig {
+                log_location: log_to,
+                time,
+                apps: app,
+                task,
+                git_task: (git_task || !git_task_rule.is_empty())
+                    .then(|| GitTaskConfig::new(git_task_rule)),
+                task_rules,
+                idle_threshold: idle,
+                snooze,
+                max_snoozes,
+                min_break,
+                pomodoro: pomodoro.then_some(PomodoroConfig {
+                    short_break,
+                    long_break,
+                    cycles,
+                }),
+                remind_every,
+                budget: BudgetConfig::new(daily_budget, weekly_budget, budget_alerts),
+                start_debounce,
+                end_grace,
+                schedule,
+                notify: NotifyConfig::default().with_routes(&notify),
+                poll: PollIntervals {
+                    resting: poll_resting,
+                    working: poll_working,
+                },
+            };
+            // Signals and Ctrl+C pressed while snooze keys are read share one channel
+            let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+            if standalone && let Err(e) = send_signals_to(signal_tx.clone()) {
+                println!(
+                    "{} {}",
+                    "Failed to listen for signals:".bright_red().bold(),
+                    e.to_string().red()
+                );
+            }
+            let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+            let snooze_keys = standalone && std::io::stdin().is_terminal();
+            let control = MonitorControl {
+                snooze_rx: snooze_keys
+                    .then(|| keyboard_snoozes(Duration::from_secs(snooze), status_rx, signal_tx)),
+                status_tx: Some(status_tx),
+                signal_rx: Some(signal_rx),
+                sleep_rx: Some(listen_for_sleep()),
+                ..MonitorControl::default()
+            };
+            let signal = run_rest_reminder(config, control).await;
+            if snooze_keys {
+                restore_terminal();
+            }
+            return ExitCode::from(signal.exit_code());
         }
         Command::Plot {
             log_location,
@@ -85,23 +269,24 @@ pub async fn execute_command(cmd: Command) {
             start_day,
             end_day,
         } => {
-            println!("{}", "Generating plot...".bright_yellow().bold());
+            println!("{}", tr("cli.plot_generating").bright_yellow().bold());
             plot(log_location, plot_location, start_day, end_day)
                 .expect("Failed to plot your working trend.");
-            println!("{}", "Plot generated successfully!".bright_green().bold());
+            println!("{}", tr("cli.plot_generated").bright_green().bold());
         }
         Command::Gen { name } => {
-            println!("{}", "Generating plugin template...".bright_yellow().bold());
+            println!("{}", tr("cli.gen_generating").bright_yellow().bold());
             generate_plugin_template(name.as_str())
                 .await
                 .expect("Failed to generate plugin template.");
         }
-        Command::Web {} => {
-            println!("{}", "Starting web server...".bright_yellow().bold());
+        Command::Web { log_to } => {
+            recover_orphaned_session(&log_to);
+            println!("{}", tr("cli.web_starting").bright_yellow().bold());
             let handle = spawn_web_server().await;
             println!(
                 "{} {}",
-                "Web server started:".bright_green().bold(),
+                tr("cli.web_started").bright_green().bold(),
                 "http://localhost:60606".white().bold()
             );
             handle
@@ -109,5 +294,147 @@ pub async fn execute_command(cmd: Command) {
                 .expect("Failed to join web server thread")
                 .expect("Web server failed");
         }
+        Command::Daemon { socket } => {
+            return run_daemon(&socket.unwrap_or_else(default_socket_path)).await;
+        }
+        Command::Ctl { socket, request } => {
+            return run_ctl(&socket.unwrap_or_else(default_socket_path), request).await;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(unix)]
+async fn run_daemon(socket: &Path) -> ExitCode {
+    use crate::daemon::server::DaemonServer;
+
+    let server = match DaemonServer::bind(socket).await {
+        Ok(server) => server,
+        Err(e) => {
+            println!(
+                "{} {}",
+                "Failed to start the daemon:".bright_red().bold(),
+                e.to_string().red()
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    let vars = Vars::new().set("socket", socket.display());
+    println!(
+        "{}",
+        tr_with("daemon.listening", &vars).bright_green().bold()
+    );
+    match server.run().await {
+        Ok(signal) => ExitCode::from(signal.exit_code()),
+        Err(e) => {
+            println!(
+                "{} {}",
+                "Daemon failed:".bright_red().bold(),
+                e.to_string().red()
+            );
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn run_daemon(_socket: &Path) -> ExitCode {
+    println!("{}", tr("daemon.unsupported").bright_red().bold());
+    ExitCode::FAILURE
+}
+
+#[cfg(unix)]
+async fn run_ctl(socket: &Path, command: CtlCommand) -> ExitCode {
+    use crate::daemon::client::send;
+    use crate::daemon::protocol::{Request, StartRequest};
+    use std::io::ErrorKind;
+
+    let (request, done) = match command {
+        CtlCommand::Start {
+            log_to,
+            time,
+            app,
+            task,
+            idle,
+            snooze,
+        } => (
+            Request::Start(StartRequest {
+                // The daemon may run in another directory
+                log_path: std::path::absolute(&log_to).unwrap_or(log_to),
+                time,
+                apps: app.iter().map(|rule| rule.to_string()).collect(),
+                task,
+                idle_threshold: idle,
+                snooze_seconds: Some(snooze),
+            }),
+            Some("ctl.started"),
+        ),
+        CtlCommand::Stop => (Request::Stop, Some("ctl.stopped")),
+        CtlCommand::Pause => (Request::Pause, Some("ctl.paused")),
+        CtlCommand::Resume => (Request::Resume, Some("ctl.resumed")),
+        CtlCommand::Status => (Request::Status, None),
+        CtlCommand::SetTask { task } => (Request::SetTask { task }, Some("ctl.task_set")),
+        CtlCommand::Snooze { seconds } => (Request::Snooze { seconds }, Some("ctl.snoozed")),
+    };
+
+    let response = match send(socket, &request).await {
+        Ok(response) => response,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+            let vars = Vars::new().set("socket", socket.display());
+            println!("{}", tr_with("ctl.no_daemon", &vars).bright_red().bold());
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            println!(
+                "{} {}",
+                "Failed to reach the daemon:".bright_red().bold(),
+                e.to_string().red()
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    if !response.ok {
+        println!(
+            "{} {}",
+            "Request failed:".bright_red().bold(),
+            response.error.unwrap_or_default().red()
+        );
+        return ExitCode::FAILURE;
+    }
+    match done {
+        Some(key) => println!("{}", tr(key).bright_green().bold()),
+        None => {
+            let status = response.status.unwrap_or_default();
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&status).unwrap_or_default()
+            );
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(not(unix))]
+async fn run_ctl(_socket: &Path, _command: CtlCommand) -> ExitCode {
+    println!("{}", tr("daemon.unsupported").bright_red().bold());
+    ExitCode::FAILURE
+}
+
+// Prints a work time total in seconds, minutes and hours
+fn print_worked(key: &str, seconds: f64) {
+    let vars = Vars::new()
+        .set("seconds", format!("{:?}", seconds))
+        .set("minutes", format!("{:?}", seconds / 60.0))
+        .set("hours", format!("{:?}", seconds / 3600.0));
+    println!("{}", tr_with(key, &vars));
+    println!("{}", tr_with("cli.or_minutes", &vars));
+    println!("{}", tr_with("cli.or_hours", &vars));
+}
+
+// Only shown when some of the work was logged outside the working hours
+fn print_overtime(seconds: f64) {
+    if seconds > 0.0 {
+        let vars = Vars::new().set("hours", format!("{:?}", seconds / 3600.0));
+        println!("{}", tr_with("cli.overtime", &vars));
     }
 }
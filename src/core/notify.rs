@@ -0,0 +1,687 @@
+use crate::core::budget::{BudgetAlert, BudgetPeriod};
+use crate::core::hooks::ReminderAction;
+use crate::core::messages::{MessageContext, catalog};
+use chrono::Local;
+use colored::*;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio::sync::mpsc::UnboundedSender;
+
+// Windows-specific imports
+#[cfg(windows)]
+use {
+    widestring::U16CString,
+    windows::Win32::Foundation::HWND,
+    windows::Win32::UI::WindowsAndMessaging::{MB_ICONWARNING, MB_OK, MB_SYSTEMMODAL, MessageBoxW},
+    windows::core::PCWSTR,
+};
+
+/// What a message is about. Each event is routed to its own sinks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NotifyEvent {
+    WorkStart,
+    /// The break reminder.
+    Reminder,
+    /// A repeat of an ignored break reminder.
+    Escalation,
+    /// A daily or weekly work budget threshold was crossed.
+    Budget,
+    /// Work goes on outside the working hours of the schedule.
+    AfterHours,
+}
+
+impl NotifyEvent {
+    pub const ALL: [NotifyEvent; 5] = [
+        NotifyEvent::WorkStart,
+        NotifyEvent::Reminder,
+        NotifyEvent::Escalation,
+        NotifyEvent::Budget,
+        NotifyEvent::AfterHours,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NotifyEvent::WorkStart => "work_start",
+            NotifyEvent::Reminder => "reminder",
+            NotifyEvent::Escalation => "escalation",
+            NotifyEvent::Budget => "budget",
+            NotifyEvent::AfterHours => "after_hours",
+        }
+    }
+}
+
+impl FromStr for NotifyEvent {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        NotifyEvent::ALL
+            .into_iter()
+            .find(|event| event.name() == value.trim())
+            .ok_or_else(|| {
+                format!(
+                    "Unknown notification event '{}', expected work_start, reminder, escalation, budget, after_hours or all",
+                    value
+                )
+            })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Urgency {
+    Low = 0,
+    Normal = 1,
+    /// Stays on screen until dismissed on most desktops.
+    Critical = 2,
+}
+
+impl Urgency {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Urgency::Low => "low",
+            Urgency::Normal => "normal",
+            Urgency::Critical => "critical",
+        }
+    }
+}
+
+/// A message for the user, shown by every sink routed to its event.
+#[derive(Debug, Clone)]
+pub struct Message {
+    pub event: NotifyEvent,
+    pub title: String,
+    pub body: String,
+    pub urgency: Urgency,
+    /// Buttons offered by sinks that can show them.
+    pub actions: Vec<ReminderAction>,
+}
+
+/// Resolves with the button clicked on the message, if the sink has buttons.
+pub type NotifyFuture =
+    Pin<Box<dyn Future<Output = Result<Option<ReminderAction>, String>> + Send>>;
+
+/// Somewhere a message can be shown.
+pub trait Notifier: Send + Sync {
+    fn notify(&self, message: Message) -> NotifyFuture;
+}
+
+/// Where messages can go, as written in `--notify`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sink {
+    /// Colored banner on standard output (`terminal`).
+    Terminal,
+    /// MessageBox on Windows, AppleScript dialog on macOS (`dialog`).
+    Dialog,
+    /// Freedesktop notification over D-Bus, with buttons (`desktop`).
+    Desktop,
+    /// Terminal bell (`bell`).
+    Bell,
+    /// Shell command run with the message in `REST_REMINDER_*` variables (`command:...`).
+    Command(String),
+    /// JSON line appended to a file or written to a FIFO (`file:...`).
+    File(PathBuf),
+}
+
+impl Sink {
+    pub fn notifier(&self) -> Arc<dyn Notifier> {
+        match self {
+            Sink::Terminal => Arc::new(TerminalNotifier),
+            Sink::Dialog => Arc::new(DialogNotifier),
+            Sink::Desktop => Arc::new(DesktopNotifier),
+            Sink::Bell => Arc::new(BellNotifier),
+            Sink::Command(command) => Arc::new(CommandNotifier::new(command.clone())),
+            Sink::File(path) => Arc::new(FileNotifier::new(path.clone())),
+        }
+    }
+}
+
+impl FromStr for Sink {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        match value.split_once(':') {
+            Some(("command", command)) if !command.trim().is_empty() => {
+                Ok(Sink::Command(command.trim().to_string()))
+            }
+            Some(("file", path)) if !path.trim().is_empty() => {
+                Ok(Sink::File(PathBuf::from(path.trim())))
+            }
+            _ => match value {
+                "terminal" => Ok(Sink::Terminal),
+                "dialog" => Ok(Sink::Dialog),
+                "desktop" => Ok(Sink::Desktop),
+                "bell" => Ok(Sink::Bell),
+                _ => Err(format!(
+                    "Unknown notification sink '{}', expected terminal, dialog, desktop, bell, command:<COMMAND> or file:<PATH>",
+                    value
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Sink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sink::Terminal => write!(f, "terminal"),
+            Sink::Dialog => write!(f, "dialog"),
+            Sink::Desktop => write!(f, "desktop"),
+            Sink::Bell => write!(f, "bell"),
+            Sink::Command(command) => write!(f, "command:{}", command),
+            Sink::File(path) => write!(f, "file:{}", path.display()),
+        }
+    }
+}
+
+/// A single `--notify` entry, `<EVENT>=<SINK>[,<SINK>...]`.
+///
+/// `all` as the event sets the sinks of every event, `none` as the only sink
+/// silences it, e.g. `reminder=terminal,command:notify-send Break` or
+/// `work_start=none`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifyRoute {
+    pub events: Vec<NotifyEvent>,
+    pub sinks: Vec<Sink>,
+}
+
+impl FromStr for NotifyRoute {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let Some((event, sinks)) = value.split_once('=') else {
+            return Err(format!(
+                "Invalid notification route '{}', expected <EVENT>=<SINK>[,<SINK>...]",
+                value
+            ));
+        };
+
+        let events = match event.trim() {
+            "all" => NotifyEvent::ALL.to_vec(),
+            event => vec![event.parse()?],
+        };
+        let sinks = match sinks.trim() {
+            "none" => Vec::new(),
+            sinks => sinks
+                .split(',')
+                .map(str::parse)
+                .collect::<Result<Vec<Sink>, String>>()?,
+        };
+        Ok(NotifyRoute { events, sinks })
+    }
+}
+
+pub fn parse_notify_routes(routes: &[String]) -> Result<Vec<NotifyRoute>, String> {
+    routes.iter().map(|route| route.parse()).collect()
+}
+
+/// The sinks chosen for each event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotifyConfig {
+    routes: BTreeMap<NotifyEvent, Vec<Sink>>,
+}
+
+impl Default for NotifyConfig {
+    // The native dialog where there is one, the terminal elsewhere, plus desktop
+    // notifications when there is a session bus to send them over. Work start is
+    // only shown in the monitor's own output.
+    fn default() -> Self {
+        #[cfg(any(windows, target_os = "macos"))]
+        let (reminder, escalation) = (vec![Sink::Dialog], vec![Sink::Dialog]);
+        #[cfg(not(any(windows, target_os = "macos")))]
+        let (reminder, escalation) = {
+            let mut reminder = vec![Sink::Terminal];
+            let mut escalation = vec![Sink::Terminal, Sink::Bell];
+            if std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some() {
+                reminder.push(Sink::Desktop);
+                escalation.push(Sink::Desktop);
+            }
+            (reminder, escalation)
+        };
+
+        NotifyConfig {
+            routes: BTreeMap::from([
+                (NotifyEvent::WorkStart, Vec::new()),
+                (NotifyEvent::Budget, reminder.clone()),
+                (NotifyEvent::AfterHours, reminder.clone()),
+                (NotifyEvent::Reminder, reminder),
+                (NotifyEvent::Escalation, escalation),
+            ]),
+        }
+    }
+}
+
+impl NotifyConfig {
+    /// The defaults with the events named in `routes` sent to their sinks instead.
+    /// Later routes win.
+    pub fn with_routes(mut self, routes: &[NotifyRoute]) -> Self {
+        for route in routes {
+            for event in &route.events {
+                self.routes.insert(*event, route.sinks.clone());
+            }
+        }
+        self
+    }
+
+    pub fn sinks(&self, event: NotifyEvent) -> &[Sink] {
+        self.routes.get(&event).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+// The notifiers of one event, next to the sink they were made from for error messages
+type Route = Vec<(Sink, Arc<dyn Notifier>)>;
+
+/// The notifiers of every routed sink, ready to show messages.
+pub struct Notifiers {
+    routes: BTreeMap<NotifyEvent, Route>,
+}
+
+impl Notifiers {
+    pub fn new(config: &NotifyConfig) -> Self {
+        let routes = NotifyEvent::ALL
+            .into_iter()
+            .map(|event| {
+                let notifiers = config
+                    .sinks(event)
+                    .iter()
+                    .map(|sink| (sink.clone(), sink.notifier()))
+                    .collect();
+                (event, notifiers)
+            })
+            .collect();
+        Notifiers { routes }
+    }
+
+    /// Shows `message` on all sinks of its event at once. Buttons clicked on any of
+    /// them are sent to `actions`.
+    ///
+    /// Sinks run in the background, since dialogs block until dismissed and
+    /// notifications wait for a click.
+    pub fn send(&self, message: Message, actions: Option<UnboundedSender<ReminderAction>>) {
+        let Some(notifiers) = self.routes.get(&message.event) else {
+            return;
+        };
+
+        for (sink, notifier) in notifiers {
+            let shown = notifier.notify(message.clone());
+            let sink = sink.to_string();
+            let actions = actions.clone();
+            tokio::spawn(async move {
+                match shown.await {
+                    Ok(Some(action)) => {
+                        if let Some(actions) = actions {
+                            let _ = actions.send(action);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => println!(
+                        "{} {}",
+                        format!("Failed to notify via {}:", sink).bright_red(),
+                        e.red()
+                    ),
+                }
+            });
+        }
+    }
+}
+
+pub fn work_start_message(context: &MessageContext) -> Message {
+    let catalog = catalog();
+    Message {
+        event: NotifyEvent::WorkStart,
+        title: catalog.text("title.work_start"),
+        body: catalog.pick("work_start", &context.vars(&catalog)),
+        urgency: Urgency::Low,
+        actions: Vec::new(),
+    }
+}
+
+pub fn reminder_message(time: u64, context: &MessageContext) -> Message {
+    let catalog = catalog();
+    let vars = context
+        .vars(&catalog)
+        .set("duration", catalog.duration(time))
+        .set("seconds", time);
+    Message {
+        event: NotifyEvent::Reminder,
+        title: catalog.render("title.reminder", &vars),
+        body: catalog.pick("reminder", &vars),
+        urgency: Urgency::Normal,
+        actions: vec![ReminderAction::TakeBreak, ReminderAction::Snooze],
+    }
+}
+
+// Repeated reminders get a harsher message set the longer they are ignored, and
+// become critical from the second repeat on
+pub fn escalation_message(overdue: u64, level: u32, context: &MessageContext) -> Message {
+    let catalog = catalog();
+    let vars = context
+        .vars(&catalog)
+        .set("overdue", catalog.duration(overdue))
+        .set("level", level);
+    let pool = if level > 1 {
+        "escalation_urgent"
+    } else {
+        "escalation"
+    };
+    Message {
+        event: NotifyEvent::Escalation,
+        title: catalog.render("title.escalation", &vars),
+        body: catalog.pick(pool, &vars),
+        urgency: if level > 1 {
+            Urgency::Critical
+        } else {
+            Urgency::Normal
+        },
+        actions: vec![ReminderAction::TakeBreak, ReminderAction::Snooze],
+    }
+}
+
+pub fn budget_message(alert: &BudgetAlert, context: &MessageContext) -> Message {
+    let catalog = catalog();
+    let (period, span) = match alert.period {
+        BudgetPeriod::Daily => ("budget.daily", "budget.today"),
+        BudgetPeriod::Weekly => ("budget.weekly", "budget.this_week"),
+    };
+    let vars = context
+        .vars(&catalog)
+        .set("period", catalog.text(period))
+        .set("span", catalog.text(span))
+        .set("percent", alert.percent)
+        .set("worked", catalog.duration(alert.worked_seconds))
+        .set("budget", catalog.duration(alert.budget_seconds));
+    Message {
+        event: NotifyEvent::Budget,
+        title: catalog.render("title.budget", &vars),
+        body: catalog.pick("budget", &vars),
+        urgency: if alert.percent >= 100 {
+            Urgency::Critical
+        } else {
+            Urgency::Normal
+        },
+        actions: Vec::new(),
+    }
+}
+
+pub fn after_hours_message(context: &MessageContext) -> Message {
+    let catalog = catalog();
+    let vars = context.vars(&catalog);
+    Message {
+        event: NotifyEvent::AfterHours,
+        title: catalog.render("title.after_hours", &vars),
+        body: catalog.pick("after_hours", &vars),
+        urgency: Urgency::Normal,
+        actions: Vec::new(),
+    }
+}
+
+pub struct TerminalNotifier;
+
+impl Notifier for TerminalNotifier {
+    fn notify(&self, message: Message) -> NotifyFuture {
+        let line = format!("{}: {}", message.title.to_uppercase(), message.body);
+        let line = match message.urgency {
+            Urgency::Critical => line.bright_red().bold().on_yellow(),
+            Urgency::Normal => line.bright_magenta().bold(),
+            Urgency::Low => line.bright_green(),
+        };
+        println!("{}", line);
+        Box::pin(async { Ok(None) })
+    }
+}
+
+pub struct BellNotifier;
+
+impl Notifier for BellNotifier {
+    fn notify(&self, _message: Message) -> NotifyFuture {
+        use std::io::Write;
+
+        print!("\x07");
+        let flushed = std::io::stdout().flush().map_err(|e| e.to_string());
+        Box::pin(async move { flushed.map(|_| None) })
+    }
+}
+
+/// The platform's own message box, which blocks until dismissed.
+pub struct DialogNotifier;
+
+#[cfg(any(windows, target_os = "macos"))]
+impl DialogNotifier {
+    fn title(event: NotifyEvent) -> String {
+        match event {
+            NotifyEvent::Escalation => catalog().text("title.dialog_escalation"),
+            _ => catalog().text("title.dialog"),
+        }
+    }
+}
+
+impl Notifier for DialogNotifier {
+    #[cfg(windows)]
+    fn notify(&self, message: Message) -> NotifyFuture {
+        let title = U16CString::from_str(Self::title(message.event)).unwrap();
+        let body = U16CString::from_str(&message.body).unwrap();
+        let mut style = MB_OK;
+        if message.event == NotifyEvent::Escalation {
+            style |= MB_ICONWARNING;
+        }
+        if message.urgency == Urgency::Critical {
+            style |= MB_SYSTEMMODAL;
+        }
+
+        // Windows API is synchronous, so run it in a background thread
+        Box::pin(async move {
+            tokio::task::spawn_blocking(move || unsafe {
+                MessageBoxW(
+                    HWND(0),
+                    PCWSTR(body.as_ptr()),
+                    PCWSTR(title.as_ptr()),
+                    style,
+                );
+            })
+            .await
+            .map_err(|e| e.to_string())?;
+            Ok(None)
+        })
+    }
+
+    #[cfg(target_os = "macos")]
+    fn notify(&self, message: Message) -> NotifyFuture {
+        let title = Self::title(message.event);
+        let body = message.body.replace("\"", "\\\"");
+        // `display alert ... as critical` shows the stop sign icon and cannot be missed
+        let script = if message.urgency == Urgency::Critical {
+            format!(
+                r#"display alert "{}" message "{}" as critical buttons {{"OK"}} default button "OK""#,
+                title, body
+            )
+        } else if message.event == NotifyEvent::Escalation {
+            format!(
+                r#"display dialog "{}" with title "{}" with icon caution buttons {{"OK"}} default button "OK""#,
+                body, title
+            )
+        } else {
+            format!(
+                r#"display dialog "{}" with title "{}" buttons {{"OK"}} default button "OK""#,
+                body, title
+            )
+        };
+
+        Box::pin(async move {
+            tokio::process::Command::new("osascript")
+                .arg("-e")
+                .arg(&script)
+                .status()
+                .await
+                .map_err(|e| e.to_string())?;
+            Ok(None)
+        })
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    fn notify(&self, _message: Message) -> NotifyFuture {
+        Box::pin(async {
+            Err("Native dialogs are only available on Windows and macOS".to_string())
+        })
+    }
+}
+
+/// Freedesktop notifications on the session bus, with the message's buttons.
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    #[cfg(not(any(windows, target_os = "macos")))]
+    fn notify(&self, message: Message) -> NotifyFuture {
+        use crate::core::dbus::{DbusNotifier, Notification};
+        use tokio::time::Duration;
+
+        let icon = match message.event {
+            NotifyEvent::WorkStart | NotifyEvent::Budget | NotifyEvent::AfterHours => {
+                "dialog-information"
+            }
+            NotifyEvent::Reminder => "appointment-soon",
+            NotifyEvent::Escalation => "dialog-warning",
+        };
+        // Messages with buttons stay until answered
+        let timeout = if message.actions.is_empty() {
+            Some(Duration::from_secs(10))
+        } else {
+            None
+        };
+        let notification = Notification {
+            summary: message.title,
+            body: message.body,
+            icon: icon.to_string(),
+            urgency: message.urgency,
+            timeout,
+            actions: message.actions,
+        };
+
+        Box::pin(async move {
+            let notifier = DbusNotifier::session().await.map_err(|e| e.to_string())?;
+            notifier
+                .notify(&notification)
+                .await
+                .map_err(|e| e.to_string())
+        })
+    }
+
+    #[cfg(any(windows, target_os = "macos"))]
+    fn notify(&self, _message: Message) -> NotifyFuture {
+        Box::pin(async { Err("Desktop notifications need a freedesktop session bus".to_string()) })
+    }
+}
+
+/// Runs a shell command for every message, e.g. `notify-send` or a chat webhook.
+///
+/// The message is passed in `REST_REMINDER_EVENT`, `REST_REMINDER_TITLE`,
+/// `REST_REMINDER_BODY` and `REST_REMINDER_URGENCY`.
+pub struct CommandNotifier {
+    command: String,
+}
+
+impl CommandNotifier {
+    pub fn new(command: String) -> Self {
+        CommandNotifier { command }
+    }
+}
+
+impl Notifier for CommandNotifier {
+    fn notify(&self, message: Message) -> NotifyFuture {
+        #[cfg(windows)]
+        let mut command = {
+            let mut command = tokio::process::Command::new("cmd");
+            command.arg("/C").arg(&self.command);
+            command
+        };
+        #[cfg(not(windows))]
+        let mut command = {
+            let mut command = tokio::process::Command::new("sh");
+            command.arg("-c").arg(&self.command);
+            command
+        };
+        command
+            .env("REST_REMINDER_EVENT", message.event.name())
+            .env("REST_REMINDER_TITLE", &message.title)
+            .env("REST_REMINDER_BODY", &message.body)
+            .env("REST_REMINDER_URGENCY", message.urgency.name());
+
+        Box::pin(async move {
+            let status = command.status().await.map_err(|e| e.to_string())?;
+            if !status.success() {
+                return Err(format!("Command exited with {}", status));
+            }
+            Ok(None)
+        })
+    }
+}
+
+/// Writes every message as a JSON line, appended to a file or written to a FIFO
+/// that another program reads from.
+pub struct FileNotifier {
+    path: PathBuf,
+}
+
+impl FileNotifier {
+    pub fn new(path: PathBuf) -> Self {
+        FileNotifier { path }
+    }
+}
+
+impl Notifier for FileNotifier {
+    fn notify(&self, message: Message) -> NotifyFuture {
+        let line = serde_json::json!({
+            "event": message.event.name(),
+            "title": message.title,
+            "body": message.body,
+            "urgency": message.urgency.name(),
+            "timestamp": Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        })
+        .to_string()
+            + "\n";
+        let path = self.path.clone();
+
+        Box::pin(async move {
+            write_line(&path, &line).await.map_err(|e| e.to_string())?;
+            Ok(None)
+        })
+    }
+}
+
+// Nobody reading the FIFO is an error rather than a write that hangs until someone does
+#[cfg(unix)]
+async fn write_line(path: &std::path::Path, line: &str) -> std::io::Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+    use tokio::io::AsyncWriteExt;
+
+    let is_fifo = tokio::fs::metadata(path)
+        .await
+        .is_ok_and(|metadata| metadata.file_type().is_fifo());
+    if is_fifo {
+        let mut sender = tokio::net::unix::pipe::OpenOptions::new().open_sender(path)?;
+        return sender.write_all(line.as_bytes()).await;
+    }
+
+    append_line(path, line).await
+}
+
+#[cfg(not(unix))]
+async fn write_line(path: &std::path::Path, line: &str) -> std::io::Result<()> {
+    append_line(path, line).await
+}
+
+async fn append_line(path: &std::path::Path, line: &str) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await?;
+    file.write_all(line.as_bytes()).await?;
+    // tokio writes in the background, the line is only on disk once flushed
+    file.flush().await
+}
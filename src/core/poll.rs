@@ -0,0 +1,73 @@
+use crate::core::engine::MonitorState;
+use serde::{Deserialize, Serialize};
+use tokio::time::Duration;
+
+pub const DEFAULT_POLL_RESTING_SECONDS: u64 = 5;
+pub const DEFAULT_POLL_WORKING_SECONDS: u64 = 2;
+// Checks are never closer together than this
+const MIN_POLL: Duration = Duration::from_secs(1);
+// Nearing the reminder, a check comes after this share of the time left
+const APPROACH_DIVISOR: u32 = 4;
+
+/// How often the process table is scanned, in seconds.
+///
+/// Nothing running is checked for at the slow resting pace. Working sessions are
+/// checked more often the closer the break reminder is, and right when it falls due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PollIntervals {
+    /// While no session is open, on a break or paused.
+    #[serde(default = "default_resting")]
+    pub resting: u64,
+    /// While working or snoozed.
+    #[serde(default = "default_working")]
+    pub working: u64,
+}
+
+fn default_resting() -> u64 {
+    DEFAULT_POLL_RESTING_SECONDS
+}
+
+fn default_working() -> u64 {
+    DEFAULT_POLL_WORKING_SECONDS
+}
+
+impl Default for PollIntervals {
+    fn default() -> Self {
+        PollIntervals {
+            resting: DEFAULT_POLL_RESTING_SECONDS,
+            working: DEFAULT_POLL_WORKING_SECONDS,
+        }
+    }
+}
+
+impl PollIntervals {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.resting == 0 || self.working == 0 {
+            return Err("Poll intervals must be at least 1 second".to_string());
+        }
+        Ok(())
+    }
+
+    /// Time from one check to the next in `state`, with `until_reminder` left
+    /// before the break reminder is due.
+    ///
+    /// The interval shrinks to a quarter of the time left, down to one second, and
+    /// the last check before the reminder lands right when it falls due.
+    pub fn next_check(&self, state: MonitorState, until_reminder: Option<Duration>) -> Duration {
+        let interval = match state {
+            MonitorState::Working | MonitorState::Snoozed => self.working,
+            _ => self.resting,
+        };
+        let interval = Duration::from_secs(interval);
+        match until_reminder {
+            Some(left) if left <= MIN_POLL * 2 => interval.min(left.max(MIN_POLL)),
+            Some(left) => interval.min((left / APPROACH_DIVISOR).max(MIN_POLL)),
+            None => interval,
+        }
+    }
+
+    /// The longest time between two checks.
+    pub fn longest(&self) -> Duration {
+        Duration::from_secs(self.resting.max(self.working))
+    }
+}
@@ -0,0 +1,190 @@
+use std::ops::BitOr;
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// A snapshot of one running process, as seen by a [`ProcessProbe`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<String>,
+    /// Command line arguments joined with spaces.
+    pub cmd: String,
+    /// Command line arguments, the program first.
+    pub args: Vec<String>,
+    /// Working directory, if it may be read.
+    pub cwd: Option<String>,
+    pub user_id: Option<String>,
+    pub parent: Option<u32>,
+    /// CPU time used since the process started, in milliseconds.
+    pub cpu_time: u64,
+    /// Bytes read from and written to disk since the process started.
+    pub disk_bytes: u64,
+}
+
+impl ProcessInfo {
+    pub fn new(pid: u32, name: impl Into<String>) -> Self {
+        ProcessInfo {
+            pid,
+            name: name.into(),
+            ..ProcessInfo::default()
+        }
+    }
+}
+
+/// Process attributes a scan reads besides the name and the parent, which come
+/// for free. The monitor asks only for what its rules look at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanDetails {
+    pub exe: bool,
+    /// Command line arguments.
+    pub cmd: bool,
+    /// Owner of the process.
+    pub user: bool,
+    /// Working directory.
+    pub cwd: bool,
+    /// CPU time and disk I/O.
+    pub usage: bool,
+}
+
+impl ScanDetails {
+    /// Every attribute of [`ProcessInfo`].
+    pub const ALL: ScanDetails = ScanDetails {
+        exe: true,
+        cmd: true,
+        user: true,
+        cwd: true,
+        usage: true,
+    };
+
+    // Executable, command line and owner never change for a running process,
+    // so they are only read once for processes that appeared since the last scan.
+    // The working directory does, `cd` in a shell or a project opened in an editor.
+    fn refresh_kind(&self) -> ProcessRefreshKind {
+        let mut kind = ProcessRefreshKind::nothing();
+        if self.exe {
+            kind = kind.with_exe(UpdateKind::OnlyIfNotSet);
+        }
+        if self.cmd {
+            kind = kind.with_cmd(UpdateKind::OnlyIfNotSet);
+        }
+        if self.user {
+            kind = kind.with_user(UpdateKind::OnlyIfNotSet);
+        }
+        if self.cwd {
+            kind = kind.with_cwd(UpdateKind::Always);
+        }
+        if self.usage {
+            kind = kind.with_cpu().with_disk_usage();
+        }
+        kind
+    }
+}
+
+impl BitOr for ScanDetails {
+    type Output = ScanDetails;
+
+    fn bitor(self, other: ScanDetails) -> ScanDetails {
+        ScanDetails {
+            exe: self.exe || other.exe,
+            cmd: self.cmd || other.cmd,
+            user: self.user || other.user,
+            cwd: self.cwd || other.cwd,
+            usage: self.usage || other.usage,
+        }
+    }
+}
+
+/// Source of the current process table.
+///
+/// The monitor asks the probe for a fresh snapshot on every check, so tests can
+/// script which processes exist at which point in time.
+pub trait ProcessProbe: Send {
+    fn processes(&mut self) -> Vec<ProcessInfo>;
+
+    /// Owner of the monitor itself, used by `mine:` rules.
+    fn current_user(&self) -> Option<String> {
+        None
+    }
+
+    /// Limits later snapshots to the attributes in `details`. Probes that have
+    /// everything at hand anyway may ignore it.
+    fn set_details(&mut self, _details: ScanDetails) {}
+}
+
+/// Process probe backed by `sysinfo`. It reads every attribute until told otherwise
+/// with [`ProcessProbe::set_details`].
+pub struct SysinfoProbe {
+    sys: System,
+    current_user: Option<String>,
+    details: ScanDetails,
+}
+
+impl SysinfoProbe {
+    pub fn new() -> Self {
+        // Only the monitor's own process is read here, the first scan reads the rest
+        let mut sys = System::new();
+        let current_user = sysinfo::get_current_pid().ok().and_then(|pid| {
+            sys.refresh_processes_specifics(
+                ProcessesToUpdate::Some(&[pid]),
+                false,
+                ProcessRefreshKind::nothing().with_user(UpdateKind::Always),
+            );
+            sys.process(pid)?.user_id().map(|uid| (**uid).to_string())
+        });
+
+        SysinfoProbe {
+            sys,
+            current_user,
+            details: ScanDetails::ALL,
+        }
+    }
+}
+
+impl Default for SysinfoProbe {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcessProbe for SysinfoProbe {
+    fn processes(&mut self) -> Vec<ProcessInfo> {
+        self.sys.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            self.details.refresh_kind(),
+        );
+
+        self.sys
+            .processes()
+            .iter()
+            .map(|(pid, process)| {
+                let disk = process.disk_usage();
+                let args: Vec<String> = process
+                    .cmd()
+                    .iter()
+                    .map(|arg| arg.to_string_lossy().to_string())
+                    .collect();
+                ProcessInfo {
+                    pid: pid.as_u32(),
+                    name: process.name().to_string_lossy().to_string(),
+                    exe: process.exe().map(|exe| exe.to_string_lossy().to_string()),
+                    cmd: args.join(" "),
+                    args,
+                    cwd: process.cwd().map(|cwd| cwd.to_string_lossy().to_string()),
+                    user_id: process.user_id().map(|uid| (**uid).to_string()),
+                    parent: process.parent().map(|parent| parent.as_u32()),
+                    cpu_time: process.accumulated_cpu_time(),
+                    disk_bytes: disk.total_read_bytes + disk.total_written_bytes,
+                }
+            })
+            .collect()
+    }
+
+    fn current_user(&self) -> Option<String> {
+        self.current_user.clone()
+    }
+
+    fn set_details(&mut self, details: ScanDetails) {
+        self.details = details;
+    }
+}
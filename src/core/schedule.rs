@@ -0,0 +1,206 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+const TIME_FORMAT: &str = "%H:%M";
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A time of day window written as `HH:MM-HH:MM`. It runs over midnight when it
+/// ends before it starts, e.g. `22:00-07:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hours {
+    from: NaiveTime,
+    until: NaiveTime,
+}
+
+impl Hours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.wraps() {
+            time >= self.from || time < self.until
+        } else {
+            self.from <= time && time < self.until
+        }
+    }
+
+    /// Whether the window ends on the day after it starts.
+    pub fn wraps(&self) -> bool {
+        self.until < self.from
+    }
+}
+
+impl FromStr for Hours {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid hours '{}', expected HH:MM-HH:MM", value);
+        let (from, until) = value.split_once('-').ok_or_else(invalid)?;
+        let from = NaiveTime::parse_from_str(from.trim(), TIME_FORMAT).map_err(|_| invalid())?;
+        let until = NaiveTime::parse_from_str(until.trim(), TIME_FORMAT).map_err(|_| invalid())?;
+        if from == until {
+            return Err(invalid());
+        }
+        Ok(Hours { from, until })
+    }
+}
+
+/// What the monitor does while you work outside the working hours.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AfterHours {
+    /// Remind as usual, after `after_hours_threshold` if the schedule gives one.
+    #[default]
+    Remind,
+    /// Remind as usual, and tell you once per session that it is after hours.
+    Nudge,
+    /// No reminders until the working hours begin.
+    Silent,
+}
+
+// The JSON layout of a schedule file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScheduleFile {
+    working_hours: Option<BTreeMap<String, Vec<String>>>,
+    #[serde(default)]
+    holidays: Vec<String>,
+    holiday_file: Option<PathBuf>,
+    #[serde(default)]
+    quiet_hours: Vec<String>,
+    #[serde(default)]
+    after_hours: AfterHours,
+    after_hours_threshold: Option<u64>,
+}
+
+/// The working week of a `--schedule` file.
+///
+/// ```json
+/// {
+///     "working_hours": {"mon": ["09:00-12:00", "13:00-17:30"], "tue": ["09:00-17:30"]},
+///     "holidays": ["2025-12-25"],
+///     "holiday_file": "holidays.txt",
+///     "quiet_hours": ["22:00-07:00"],
+///     "after_hours": "nudge",
+///     "after_hours_threshold": 1800
+/// }
+/// ```
+///
+/// Days missing from `working_hours` are days off, without `working_hours` every day
+/// is a working day. Windows running over midnight belong to the day they start on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    working_hours: Option<HashMap<Weekday, Vec<Hours>>>,
+    holidays: BTreeSet<NaiveDate>,
+    quiet_hours: Vec<Hours>,
+    pub after_hours: AfterHours,
+    /// Seconds of work before the break reminder outside the working hours.
+    pub after_hours_threshold: Option<u64>,
+}
+
+impl Schedule {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let file: ScheduleFile = serde_json::from_str(&source)
+            .map_err(|e| format!("Invalid schedule file {}: {}", path.display(), e))?;
+
+        let working_hours = file
+            .working_hours
+            .map(|days| {
+                days.into_iter()
+                    .map(|(day, windows)| {
+                        let weekday = day
+                            .trim()
+                            .parse::<Weekday>()
+                            .map_err(|_| format!("Invalid weekday '{}'", day))?;
+                        Ok((weekday, parse_windows(&windows)?))
+                    })
+                    .collect::<Result<HashMap<_, _>, String>>()
+            })
+            .transpose()?;
+
+        let mut holidays = file
+            .holidays
+            .iter()
+            .map(|day| parse_date(day))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        // The holiday list is read relative to the schedule file
+        if let Some(holiday_file) = file.holiday_file {
+            let holiday_file = match path.parent() {
+                Some(dir) if holiday_file.is_relative() => dir.join(holiday_file),
+                _ => holiday_file,
+            };
+            holidays.extend(read_holidays(&holiday_file)?);
+        }
+
+        if file.after_hours_threshold == Some(0) {
+            return Err("after_hours_threshold must be greater than 0".to_string());
+        }
+
+        Ok(Schedule {
+            working_hours,
+            holidays,
+            quiet_hours: parse_windows(&file.quiet_hours)?,
+            after_hours: file.after_hours,
+            after_hours_threshold: file.after_hours_threshold,
+        })
+    }
+
+    pub fn is_working_time(&self, now: DateTime<Local>) -> bool {
+        let today = now.date_naive();
+        if self.holidays.contains(&today) {
+            return false;
+        }
+        let Some(working_hours) = &self.working_hours else {
+            return true;
+        };
+
+        let time = now.time();
+        let yesterday = (today - Duration::days(1)).weekday();
+        let started_today = working_hours.get(&today.weekday()).is_some_and(|windows| {
+            windows
+                .iter()
+                .any(|hours| hours.contains(time) && (!hours.wraps() || time >= hours.from))
+        });
+        let started_yesterday = working_hours.get(&yesterday).is_some_and(|windows| {
+            windows
+                .iter()
+                .any(|hours| hours.wraps() && time < hours.until)
+        });
+        started_today || started_yesterday
+    }
+
+    pub fn is_quiet(&self, now: DateTime<Local>) -> bool {
+        self.quiet_hours
+            .iter()
+            .any(|hours| hours.contains(now.time()))
+    }
+
+    /// Whether reminders are held back at `now`: during quiet hours, and outside the
+    /// working hours when they are silent.
+    pub fn reminders_muted(&self, now: DateTime<Local>) -> bool {
+        self.is_quiet(now) || (self.after_hours == AfterHours::Silent && !self.is_working_time(now))
+    }
+}
+
+fn parse_windows(windows: &[String]) -> Result<Vec<Hours>, String> {
+    windows.iter().map(|hours| hours.parse()).collect()
+}
+
+fn parse_date(day: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(day.trim(), DATE_FORMAT)
+        .map_err(|_| format!("Invalid holiday '{}', expected YYYY-MM-DD", day))
+}
+
+/// One `YYYY-MM-DD` date per line, blank lines and `#` comments are skipped.
+pub fn read_holidays(path: &Path) -> Result<Vec<NaiveDate>, String> {
+    let source = std::fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    source
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(parse_date)
+        .collect()
+}
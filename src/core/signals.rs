@@ -0,0 +1,92 @@
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// A signal that stops the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopSignal {
+    /// Ctrl+C
+    Interrupt,
+    Terminate,
+    Hangup,
+    Quit,
+}
+
+impl StopSignal {
+    pub fn name(&self) -> &'static str {
+        match self {
+            StopSignal::Interrupt => "SIGINT",
+            StopSignal::Terminate => "SIGTERM",
+            StopSignal::Hangup => "SIGHUP",
+            StopSignal::Quit => "SIGQUIT",
+        }
+    }
+
+    /// Exit status of a process stopped by this signal, 128 plus the signal number
+    /// like a shell reports it.
+    pub fn exit_code(&self) -> u8 {
+        let number = match self {
+            StopSignal::Hangup => 1,
+            StopSignal::Interrupt => 2,
+            StopSignal::Quit => 3,
+            StopSignal::Terminate => 15,
+        };
+        128 + number
+    }
+}
+
+/// What a signal sent to the process asks the monitor to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignalRequest {
+    /// SIGTERM, SIGHUP or SIGQUIT: log the open session and stop.
+    Stop(StopSignal),
+    /// SIGUSR1
+    TogglePause,
+    /// SIGUSR2: reload the plugins and the task rules file. The rest of the
+    /// monitor's settings are fixed until it is restarted.
+    Reload,
+}
+
+/// Turns SIGTERM, SIGHUP, SIGQUIT, SIGUSR1 and SIGUSR2 into monitor requests.
+///
+/// Once subscribed, these signals no longer kill the process, so only use this when
+/// the monitor owns the process and exits after stopping, e.g. not from the
+/// interactive prompt. Ctrl+C is always handled by the monitor itself.
+pub fn listen_for_signals() -> std::io::Result<UnboundedReceiver<SignalRequest>> {
+    let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+    send_signals_to(signal_tx)?;
+    Ok(signal_rx)
+}
+
+/// Like [`listen_for_signals`], on a channel that other sources of requests share.
+#[cfg(unix)]
+pub fn send_signals_to(signal_tx: UnboundedSender<SignalRequest>) -> std::io::Result<()> {
+    use tokio::signal::unix::{SignalKind, signal};
+
+    let mut terminate = signal(SignalKind::terminate())?;
+    let mut hangup = signal(SignalKind::hangup())?;
+    let mut quit = signal(SignalKind::quit())?;
+    let mut user1 = signal(SignalKind::user_defined1())?;
+    let mut user2 = signal(SignalKind::user_defined2())?;
+
+    tokio::spawn(async move {
+        loop {
+            let request = tokio::select! {
+                _ = terminate.recv() => SignalRequest::Stop(StopSignal::Terminate),
+                _ = hangup.recv() => SignalRequest::Stop(StopSignal::Hangup),
+                _ = quit.recv() => SignalRequest::Stop(StopSignal::Quit),
+                _ = user1.recv() => SignalRequest::TogglePause,
+                _ = user2.recv() => SignalRequest::Reload,
+            };
+            if signal_tx.send(request).is_err() {
+                return;
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Other platforms only have Ctrl+C, which the monitor handles itself.
+#[cfg(not(unix))]
+pub fn send_signals_to(_signal_tx: UnboundedSender<SignalRequest>) -> std::io::Result<()> {
+    Ok(())
+}
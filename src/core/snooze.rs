@@ -0,0 +1,149 @@
+use crate::core::core::MonitorStatus;
+use crate::core::engine::MonitorState;
+use crate::core::messages::{Vars, tr, tr_with};
+use crate::core::signals::{SignalRequest, StopSignal};
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::io;
+use std::sync::Mutex;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::watch;
+use tokio::time::Duration;
+
+/// Default snooze length in seconds.
+pub const DEFAULT_SNOOZE_SECONDS: u64 = 600;
+
+// How long the key reader waits for a key, or for a reminder, before looking again
+const KEY_POLL: Duration = Duration::from_millis(200);
+
+// Time between two waits on a key, for the monitor to take stdout
+const PRINT_GAP: Duration = Duration::from_millis(20);
+
+// Cleared once the monitor is done, so the key reader cannot leave the terminal raw
+static RAW_MODE_ALLOWED: Mutex<bool> = Mutex::new(true);
+
+/// Reads a key pressed while a break reminder is up.
+///
+/// `s` postpones the reminder by `default`, `1` to `9` by that many times 5 minutes.
+/// Any other key is not a snooze.
+pub fn parse_snooze_key(key: char, default: Duration) -> Option<Duration> {
+    match key {
+        's' | 'S' => Some(default),
+        '1'..='9' => key
+            .to_digit(10)
+            .map(|count| Duration::from_secs(u64::from(count) * 5 * 60)),
+        _ => None,
+    }
+}
+
+/// Whether keys snooze the reminder in this state of the monitor.
+///
+/// Only while the reminder is up: a snoozed reminder is still pending, but there is
+/// nothing to answer until it is back.
+pub fn reads_snooze_keys(status: &MonitorStatus) -> bool {
+    status.reminder_pending && status.state != MonitorState::Snoozed
+}
+
+/// Turns keys pressed while a break reminder is up into snooze requests.
+///
+/// The terminal is put in raw mode for each wait on a key, so a single key snoozes
+/// without Enter. Stdout stays locked meanwhile, so the monitor never prints to a raw
+/// terminal. Raw mode keeps Ctrl+C from interrupting the process, so it is sent on
+/// `signal_tx` as a stop instead. The reader lives on its own thread because the
+/// terminal has no async, cancellable read. Only use it when nothing else reads stdin
+/// afterwards, e.g. not from the interactive prompt, and call [`restore_terminal`]
+/// once the monitor returns.
+pub fn keyboard_snoozes(
+    default: Duration,
+    status_rx: watch::Receiver<MonitorStatus>,
+    signal_tx: UnboundedSender<SignalRequest>,
+) -> UnboundedReceiver<Duration> {
+    let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+
+    println!(
+        "{}",
+        tr_with(
+            "snooze.hint",
+            &Vars::new().set("seconds", default.as_secs())
+        )
+        .bright_black()
+    );
+
+    std::thread::spawn(move || {
+        while !snooze_tx.is_closed() {
+            if !reads_snooze_keys(&status_rx.borrow()) {
+                std::thread::sleep(KEY_POLL);
+                continue;
+            }
+            let Ok(key) = read_key() else {
+                break;
+            };
+            // Lets the monitor print what it held back
+            std::thread::sleep(PRINT_GAP);
+            let Some((key, modifiers)) = key else {
+                continue;
+            };
+            if key == 'c' && modifiers.contains(KeyModifiers::CONTROL) {
+                let _ = signal_tx.send(SignalRequest::Stop(StopSignal::Interrupt));
+                break;
+            }
+            match parse_snooze_key(key, default) {
+                Some(snooze_for) => {
+                    if snooze_tx.send(snooze_for).is_err() {
+                        break;
+                    }
+                }
+                None => println!("{}", tr("snooze.unknown").yellow()),
+            }
+        }
+    });
+
+    snooze_rx
+}
+
+// Waits up to `KEY_POLL` for a key in raw mode, with stdout locked so nothing is
+// printed without the cursor going back to the start of the line
+fn read_key() -> io::Result<Option<(char, KeyModifiers)>> {
+    let stdout = io::stdout().lock();
+    if !set_raw_mode(true) {
+        drop(stdout);
+        std::thread::sleep(KEY_POLL);
+        return Ok(None);
+    }
+    let key = next_key();
+    set_raw_mode(false);
+    key
+}
+
+fn next_key() -> io::Result<Option<(char, KeyModifiers)>> {
+    if !event::poll(KEY_POLL)? {
+        return Ok(None);
+    }
+    match event::read()? {
+        Event::Key(KeyEvent {
+            code: KeyCode::Char(key),
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) => Ok(Some((key, modifiers))),
+        _ => Ok(None),
+    }
+}
+
+/// Leaves raw mode for good, for when the monitor that read snooze keys returned.
+pub fn restore_terminal() {
+    let mut allowed = RAW_MODE_ALLOWED.lock().unwrap_or_else(|e| e.into_inner());
+    *allowed = false;
+    let _ = terminal::disable_raw_mode();
+}
+
+// Whether the terminal is raw afterwards
+fn set_raw_mode(raw: bool) -> bool {
+    let allowed = RAW_MODE_ALLOWED.lock().unwrap_or_else(|e| e.into_inner());
+    if raw && *allowed {
+        return terminal::enable_raw_mode().is_ok();
+    }
+    let _ = terminal::disable_raw_mode();
+    false
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Local};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::time::Duration;
+
+/// A wall clock jump between two process checks longer than this, on top of the
+/// poll interval, means the machine was asleep.
+pub const SUSPEND_JUMP: Duration = Duration::from_secs(60);
+
+/// Notices suspend and hibernation from the wall clock.
+///
+/// While the machine sleeps, no process check runs, but the apps are still open when
+/// it wakes up. Without this the whole night would end up in the open session.
+pub struct Heartbeat {
+    jump: chrono::Duration,
+    last: Option<DateTime<Local>>,
+}
+
+impl Heartbeat {
+    pub fn new(jump: Duration) -> Self {
+        Heartbeat {
+            jump: chrono::Duration::from_std(jump).unwrap_or(chrono::Duration::MAX),
+            last: None,
+        }
+    }
+
+    /// Records a check at `now`. Returns the previous check when the wall clock
+    /// jumped ahead since then, which is where the machine went to sleep.
+    pub fn beat(&mut self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        let last = self.last.replace(now)?;
+        (now - last > self.jump).then_some(last)
+    }
+
+    /// Forgets the last check, e.g. after a wakeup was already handled.
+    pub fn reset(&mut self) {
+        self.last = None;
+    }
+}
+
+/// Reports logind's `PrepareForSleep` signal: `true` right before the machine goes
+/// to sleep, `false` once it woke up.
+///
+/// The channel closes without a message when there is no logind on the system bus,
+/// the wall clock jump still ends the session then.
+#[cfg(not(any(windows, target_os = "macos")))]
+pub fn listen_for_sleep() -> UnboundedReceiver<bool> {
+    let (sleep_tx, sleep_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let _ = crate::core::dbus::forward_sleep_signals(sleep_tx).await;
+    });
+    sleep_rx
+}
+
+/// Other platforms rely on the wall clock jump alone.
+#[cfg(any(windows, target_os = "macos"))]
+pub fn listen_for_sleep() -> UnboundedReceiver<bool> {
+    let (_sleep_tx, sleep_rx) = mpsc::unbounded_channel();
+    sleep_rx
+}
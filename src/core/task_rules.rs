@@ -0,0 +1,194 @@
+use crate::core::matcher::{AppRule, ProcessTree};
+use crate::core::probe::{ProcessInfo, ScanDetails};
+use crate::core::schedule::Hours;
+use chrono::{DateTime, Datelike, Local, Weekday};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// The JSON layout of a rules file
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskRulesFile {
+    rules: Vec<TaskRuleFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TaskRuleFile {
+    name: Option<String>,
+    task: String,
+    app: Option<String>,
+    cmd: Option<String>,
+    #[serde(default)]
+    weekdays: Vec<String>,
+    hours: Option<String>,
+}
+
+/// One rule of a `--task-rules` file. Every condition given must hold for the
+/// rule to fire, a rule without conditions always fires.
+#[derive(Debug, Clone)]
+pub struct TaskRule {
+    /// Shown in the status, `rule <N>` if the file gives none.
+    pub name: String,
+    /// The label the rule assigns, `None` to leave sessions unlabeled.
+    pub task: Option<String>,
+    // A process matching this app rule is running
+    app: Option<AppRule>,
+    // A process whose command line matches is running. With `app`, it has to be the same process.
+    cmd: Option<Regex>,
+    weekdays: Vec<Weekday>,
+    hours: Option<Hours>,
+}
+
+impl TaskRule {
+    pub fn fires(
+        &self,
+        processes: &[ProcessInfo],
+        now: DateTime<Local>,
+        current_user: Option<&str>,
+    ) -> bool {
+        if !self.weekdays.is_empty() && !self.weekdays.contains(&now.weekday()) {
+            return false;
+        }
+        if self.hours.is_some_and(|hours| !hours.contains(now.time())) {
+            return false;
+        }
+        if self.app.is_none() && self.cmd.is_none() {
+            return true;
+        }
+
+        let tree = ProcessTree::new(processes);
+        processes.iter().any(|process| {
+            self.app
+                .as_ref()
+                .is_none_or(|app| app.matches_in(process, &tree, current_user))
+                && self
+                    .cmd
+                    .as_ref()
+                    .is_none_or(|cmd| cmd.is_match(&process.cmd))
+        })
+    }
+
+    /// The process attributes a scan has to read for this rule.
+    pub fn scan_details(&self) -> ScanDetails {
+        let app = self
+            .app
+            .as_ref()
+            .map(AppRule::scan_details)
+            .unwrap_or_default();
+        app | ScanDetails {
+            cmd: self.cmd.is_some(),
+            ..ScanDetails::default()
+        }
+    }
+
+    fn from_file(index: usize, rule: TaskRuleFile) -> Result<Self, String> {
+        let name = rule.name.unwrap_or_else(|| format!("rule {}", index + 1));
+        let invalid = |e: String| format!("Invalid task rule '{}': {}", name, e);
+
+        let app = rule
+            .app
+            .map(|app| app.parse::<AppRule>())
+            .transpose()
+            .map_err(invalid)?;
+        let cmd = rule
+            .cmd
+            .map(|cmd| Regex::new(&cmd).map_err(|e| format!("Invalid regex '{}': {}", cmd, e)))
+            .transpose()
+            .map_err(invalid)?;
+        let weekdays = rule
+            .weekdays
+            .iter()
+            .map(|day| {
+                day.trim()
+                    .parse::<Weekday>()
+                    .map_err(|_| format!("Invalid weekday '{}'", day))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(invalid)?;
+        let hours = rule
+            .hours
+            .map(|hours| hours.parse::<Hours>())
+            .transpose()
+            .map_err(invalid)?;
+
+        let task = rule.task.trim();
+        Ok(TaskRule {
+            task: (!task.is_empty()).then(|| task.to_string()),
+            name,
+            app,
+            cmd,
+            weekdays,
+            hours,
+        })
+    }
+}
+
+/// The rules of a `--task-rules` file, tried in order.
+///
+/// ```json
+/// {"rules": [
+///     {"name": "calls", "task": "meetings", "app": "zoom"},
+///     {"task": "deep-work", "app": "rustrover", "weekdays": ["mon", "tue", "wed", "thu", "fri"], "hours": "09:00-12:00"}
+/// ]}
+/// ```
+#[derive(Debug, Clone)]
+pub struct TaskRules {
+    path: PathBuf,
+    rules: Vec<TaskRule>,
+}
+
+impl TaskRules {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let file: TaskRulesFile = serde_json::from_str(&source)
+            .map_err(|e| format!("Invalid task rules file {}: {}", path.display(), e))?;
+        let rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| TaskRule::from_file(index, rule))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(TaskRules {
+            path: path.to_path_buf(),
+            rules,
+        })
+    }
+
+    /// Reads the file again, keeping the current rules if it no longer loads.
+    pub fn reload(&mut self) -> Result<(), String> {
+        *self = TaskRules::load(&self.path)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn rules(&self) -> &[TaskRule] {
+        &self.rules
+    }
+
+    pub fn scan_details(&self) -> ScanDetails {
+        self.rules
+            .iter()
+            .fold(ScanDetails::default(), |details, rule| {
+                details | rule.scan_details()
+            })
+    }
+
+    /// The first rule that fires for the running processes at `now`.
+    pub fn fired(
+        &self,
+        processes: &[ProcessInfo],
+        now: DateTime<Local>,
+        current_user: Option<&str>,
+    ) -> Option<&TaskRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.fires(processes, now, current_user))
+    }
+}
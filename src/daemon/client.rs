@@ -0,0 +1,23 @@
+use crate::daemon::protocol::{Request, Response};
+use std::io;
+use std::path::Path;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Sends one request to the daemon listening on `socket` and waits for its answer.
+pub async fn send(socket: &Path, request: &Request) -> io::Result<Response> {
+    let stream = UnixStream::connect(socket).await?;
+    let (reader, mut writer) = stream.into_split();
+    let mut line = serde_json::to_string(request)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes()).await?;
+
+    let mut answer = String::new();
+    if BufReader::new(reader).read_line(&mut answer).await? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "The daemon closed the connection without answering",
+        ));
+    }
+    Ok(serde_json::from_str(&answer)?)
+}
@@ -0,0 +1,19 @@
+use std::path::PathBuf;
+
+#[cfg(unix)]
+pub mod client;
+pub mod protocol;
+#[cfg(unix)]
+pub mod server;
+
+/// Where `daemon` listens and `ctl` connects without `--socket`:
+/// `$XDG_RUNTIME_DIR/rest-reminder.sock`, or a per-user file in the temp directory.
+pub fn default_socket_path() -> PathBuf {
+    match std::env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir).join("rest-reminder.sock"),
+        _ => {
+            let user = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+            std::env::temp_dir().join(format!("rest-reminder-{}.sock", user))
+        }
+    }
+}
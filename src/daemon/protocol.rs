@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// One line a client sends to the daemon, e.g. `{"command":"snooze","seconds":300}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "kebab-case")]
+pub enum Request {
+    Start(StartRequest),
+    Stop,
+    Pause,
+    Resume,
+    Status,
+    /// Label the sessions from now on, `None` leaves them unlabeled.
+    SetTask {
+        #[serde(default)]
+        task: Option<String>,
+    },
+    /// Postpone the pending break reminder, by the `snooze_seconds` of `start` if no
+    /// length is given.
+    Snooze {
+        #[serde(default)]
+        seconds: Option<u64>,
+    },
+}
+
+/// What `start` monitors, the same as the options of `rest` with these names.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartRequest {
+    pub log_path: PathBuf,
+    pub time: u64,
+    pub apps: Vec<String>,
+    #[serde(default)]
+    pub task: Option<String>,
+    #[serde(default)]
+    pub idle_threshold: Option<u64>,
+    #[serde(default)]
+    pub snooze_seconds: Option<u64>,
+}
+
+/// The line the daemon answers every request with. Successful requests carry the
+/// daemon's status after they were handled.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<serde_json::Value>,
+}
+
+impl Response {
+    pub fn ok(status: serde_json::Value) -> Self {
+        Response {
+            ok: true,
+            error: None,
+            status: Some(status),
+        }
+    }
+
+    pub fn error(error: impl Into<String>) -> Self {
+        Response {
+            ok: false,
+            error: Some(error.into()),
+            status: None,
+        }
+    }
+}
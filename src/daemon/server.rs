@@ -0,0 +1,335 @@
+use crate::core::core::{MonitorConfig, MonitorControl, MonitorStatus, run_rest_reminder};
+use crate::core::lock::MonitorLock;
+use crate::core::matcher::parse_app_rules;
+use crate::core::notify::NotifyConfig;
+use crate::core::poll::PollIntervals;
+use crate::core::signals::{SignalRequest, StopSignal, listen_for_signals};
+use crate::core::snooze::DEFAULT_SNOOZE_SECONDS;
+use crate::core::suspend::listen_for_sleep;
+use crate::daemon::protocol::{Request, Response, StartRequest};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fs::Permissions;
+use std::io;
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{Mutex, mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+
+// The monitor started by `start`, until `stop`
+struct DaemonSession {
+    handle: JoinHandle<StopSignal>,
+    started_at: DateTime<Local>,
+    request: StartRequest,
+    pause_tx: watch::Sender<bool>,
+    snooze_tx: mpsc::UnboundedSender<Duration>,
+    task_tx: mpsc::UnboundedSender<Option<String>>,
+    signal_tx: mpsc::UnboundedSender<SignalRequest>,
+    status_rx: watch::Receiver<MonitorStatus>,
+    // Released once the monitor is stopped
+    _lock: MonitorLock,
+}
+
+#[derive(Serialize)]
+struct DaemonStatus {
+    running: bool,
+    paused: bool,
+    started_at: Option<DateTime<Local>>,
+    log_path: Option<PathBuf>,
+    time: Option<u64>,
+    apps: Vec<String>,
+    #[serde(flatten)]
+    monitor: Option<MonitorStatus>,
+}
+
+type SharedSession = Arc<Mutex<Option<DaemonSession>>>;
+
+/// Runs monitors in the background and takes requests on a Unix-domain socket, one
+/// JSON object per line in each direction.
+pub struct DaemonServer {
+    listener: UnixListener,
+    socket: PathBuf,
+    session: SharedSession,
+}
+
+impl DaemonServer {
+    /// Listens on `socket`, which only the current user may connect to. A socket
+    /// left behind by a daemon that was killed is replaced.
+    pub async fn bind(socket: &Path) -> io::Result<Self> {
+        if socket.exists() {
+            if UnixStream::connect(socket).await.is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    format!("A daemon is already listening on {}", socket.display()),
+                ));
+            }
+            std::fs::remove_file(socket)?;
+        }
+        let listener = UnixListener::bind(socket)?;
+        std::fs::set_permissions(socket, Permissions::from_mode(0o600))?;
+
+        Ok(DaemonServer {
+            listener,
+            socket: socket.to_path_buf(),
+            session: Arc::new(Mutex::new(None)),
+        })
+    }
+
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+
+    /// Serves requests until Ctrl+C, `SIGTERM`, `SIGHUP` or `SIGQUIT`, then stops the
+    /// monitor, which logs its open session. The signal is returned.
+    ///
+    /// Like for `rest`, `SIGUSR1` toggles pause and `SIGUSR2` reloads the plugins of
+    /// the running monitor.
+    pub async fn run(self) -> io::Result<StopSignal> {
+        let mut signal_rx = listen_for_signals()?;
+        let signal = loop {
+            tokio::select! {
+                result = self.serve() => result?,
+                _ = tokio::signal::ctrl_c() => break StopSignal::Interrupt,
+                Some(request) = signal_rx.recv() => match request {
+                    SignalRequest::Stop(signal) => break signal,
+                    request => forward(request, self.session.lock().await.as_ref()),
+                },
+            }
+        };
+        stop(&mut *self.session.lock().await).await;
+        Ok(signal)
+    }
+
+    /// Accepts connections, each one is served on its own task.
+    pub async fn serve(&self) -> io::Result<()> {
+        loop {
+            let (stream, _) = self.listener.accept().await?;
+            tokio::spawn(serve_connection(stream, self.session.clone()));
+        }
+    }
+}
+
+impl Drop for DaemonServer {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.socket);
+    }
+}
+
+async fn serve_connection(stream: UnixStream, session: SharedSession) {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => handle(request, &mut *session.lock().await).await,
+            Err(e) => Response::error(format!("Invalid request: {}", e)),
+        };
+        let Ok(mut answer) = serde_json::to_string(&response) else {
+            return;
+        };
+        answer.push('\n');
+        if writer.write_all(answer.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn handle(request: Request, session: &mut Option<DaemonSession>) -> Response {
+    // A monitor stopped by Ctrl+C in the daemon's terminal is gone
+    if session
+        .as_ref()
+        .is_some_and(|session| session.handle.is_finished())
+    {
+        *session = None;
+    }
+
+    let handled = match request {
+        Request::Start(request) => start(request, session),
+        Request::Stop => {
+            stop(session).await;
+            Ok(())
+        }
+        Request::Status => Ok(()),
+        Request::Pause => running(session).and_then(|session| {
+            session
+                .pause_tx
+                .send(true)
+                .map_err(|_| "Failed to pause monitoring".to_string())
+        }),
+        Request::Resume => running(session).and_then(|session| {
+            session
+                .pause_tx
+                .send(false)
+                .map_err(|_| "Failed to resume monitoring".to_string())
+        }),
+        Request::SetTask { task } => running(session).and_then(|session| {
+            session
+                .task_tx
+                .send(normalized_task(task))
+                .map_err(|_| "Failed to set the task".to_string())
+        }),
+        Request::Snooze { seconds } => {
+            running(session).and_then(|session| snooze(session, seconds))
+        }
+    };
+    match handled {
+        Ok(()) => match serde_json::to_value(status(session)) {
+            Ok(status) => Response::ok(status),
+            Err(e) => Response::error(format!("Failed to report the status: {}", e)),
+        },
+        Err(e) => Response::error(e),
+    }
+}
+
+fn running(session: &mut Option<DaemonSession>) -> Result<&mut DaemonSession, String> {
+    session
+        .as_mut()
+        .ok_or_else(|| "Monitoring is not running".to_string())
+}
+
+fn start(request: StartRequest, session: &mut Option<DaemonSession>) -> Result<(), String> {
+    if let Some(running) = session {
+        return Err(format!(
+            "Already monitoring {}, stop it first",
+            running.request.log_path.display()
+        ));
+    }
+    if request.time == 0 {
+        return Err("Work time must be greater than 0".to_string());
+    }
+    let apps = parse_app_rules(&request.apps)?;
+    if apps.is_empty() {
+        return Err("No apps to monitor".to_string());
+    }
+    let lock = MonitorLock::acquire(&request.log_path).map_err(|e| e.to_string())?;
+
+    let (pause_tx, pause_rx) = watch::channel(false);
+    let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+    let (task_tx, task_rx) = mpsc::unbounded_channel();
+    let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+    let config = MonitorConfig {
+        log_location: request.log_path.clone(),
+        time: request.time,
+        apps,
+        task: normalized_task(request.task.clone()),
+        git_task: None,
+        task_rules: None,
+        idle_threshold: request.idle_threshold,
+        snooze: request.snooze_seconds.unwrap_or(DEFAULT_SNOOZE_SECONDS),
+        max_snoozes: None,
+        min_break: None,
+        pomodoro: None,
+        remind_every: None,
+        budget: None,
+        start_debounce: None,
+        end_grace: None,
+        schedule: None,
+        notify: NotifyConfig::default(),
+        poll: PollIntervals::default(),
+    };
+    let control = MonitorControl {
+        pause_rx: Some(pause_rx),
+        snooze_rx: Some(snooze_rx),
+        status_tx: Some(status_tx),
+        signal_rx: Some(signal_rx),
+        sleep_rx: Some(listen_for_sleep()),
+        task_rx: Some(task_rx),
+        ..MonitorControl::default()
+    };
+
+    *session = Some(DaemonSession {
+        handle: tokio::spawn(run_rest_reminder(config, control)),
+        started_at: Local::now(),
+        request,
+        pause_tx,
+        snooze_tx,
+        task_tx,
+        signal_tx,
+        status_rx,
+        _lock: lock,
+    });
+    Ok(())
+}
+
+// Hands a signal on to the running monitor. Pausing goes through the pause switch, so
+// `status` and `resume` know about it
+fn forward(request: SignalRequest, session: Option<&DaemonSession>) {
+    let Some(session) = session else {
+        return;
+    };
+    match request {
+        SignalRequest::TogglePause => {
+            session.pause_tx.send_modify(|paused| *paused = !*paused);
+        }
+        request => {
+            let _ = session.signal_tx.send(request);
+        }
+    }
+}
+
+// Waits for the monitor to log its open session, so the log is complete once
+// `stop` is answered
+async fn stop(session: &mut Option<DaemonSession>) {
+    let Some(stopped) = session.take() else {
+        return;
+    };
+    let _ = stopped
+        .signal_tx
+        .send(SignalRequest::Stop(StopSignal::Terminate));
+    let _ = stopped.handle.await;
+}
+
+fn snooze(session: &DaemonSession, seconds: Option<u64>) -> Result<(), String> {
+    if !session.status_rx.borrow().reminder_pending {
+        return Err("There is no break reminder to snooze".to_string());
+    }
+    let seconds = seconds
+        .or(session.request.snooze_seconds)
+        .unwrap_or(DEFAULT_SNOOZE_SECONDS);
+    if seconds == 0 {
+        return Err("Snooze length must be greater than 0".to_string());
+    }
+    session
+        .snooze_tx
+        .send(Duration::from_secs(seconds))
+        .map_err(|_| "Failed to snooze the reminder".to_string())
+}
+
+fn status(session: &Option<DaemonSession>) -> DaemonStatus {
+    match session {
+        Some(session) => {
+            let monitor = session.status_rx.borrow().clone();
+            DaemonStatus {
+                running: true,
+                // The monitor may not have seen a pause or resume sent just now
+                paused: *session.pause_tx.borrow(),
+                started_at: Some(session.started_at),
+                log_path: Some(session.request.log_path.clone()),
+                time: Some(session.request.time),
+                apps: session.request.apps.clone(),
+                monitor: Some(monitor),
+            }
+        }
+        None => DaemonStatus {
+            running: false,
+            paused: false,
+            started_at: None,
+            log_path: None,
+            time: None,
+            apps: Vec::new(),
+            monitor: None,
+        },
+    }
+}
+
+fn normalized_task(task: Option<String>) -> Option<String> {
+    task.map(|task| task.trim().to_string())
+        .filter(|task| !task.is_empty())
+}
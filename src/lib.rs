@@ -1,5 +1,6 @@
 pub mod cli;
 pub mod core;
+pub mod daemon;
 pub mod plugin;
 pub mod statistic;
 pub mod web;
@@ -2,18 +2,23 @@ use clap::Parser;
 use rest_reminder::cli::Cli;
 use rest_reminder::core::execute_command;
 use rest_reminder::core::interactive::run_interactive_mode;
+use rest_reminder::core::messages::use_locale;
 use std::env;
+use std::process::ExitCode;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
 
     // If no command line arguments are provided (only program name), enter interactive mode
     if args.len() == 1 {
+        use_locale(None, None);
         run_interactive_mode().await;
+        ExitCode::SUCCESS
     } else {
         // Parse and execute the command normally
         let cli = Cli::parse();
-        execute_command(cli.cmd).await;
+        use_locale(cli.locale.as_deref(), cli.messages.as_deref());
+        execute_command(cli.cmd, true).await
     }
 }
@@ -1,4 +1,4 @@
-use chrono::Local;
+use chrono::{DateTime, Local};
 use colored::*;
 use pyo3::exceptions::PyIOError;
 use pyo3::prelude::*;
@@ -9,11 +9,15 @@ use std::fs::OpenOptions;
 use std::io::{ErrorKind, Write};
 use std::path::{Path, PathBuf};
 use std::process::Output;
+use std::time::Duration;
 use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
 use walkdir::WalkDir;
 
 const IGNORE_PATTERN: &str = r"^\s*_SHOULD_IGNORE\s*=\s*1\s*$";
 pub const PLUGIN_ERROR_LOG_PATH: &str = "plugins/plugin_errors.log";
+// Subprocess plugins report a returned `{"snooze": seconds}` on stdout behind this prefix
+const SNOOZE_MARKER: &str = "__rest_reminder_snooze__=";
 
 pub fn append_plugin_error(plugin_name: &str, event: &str, error: &str) {
     if let Some(parent) = Path::new(PLUGIN_ERROR_LOG_PATH).parent() {
@@ -60,6 +64,7 @@ async fn run_python_code(python_code: &str) -> std::io::Result<Output> {
 pub struct PluginManager {
     inactivated_plugins: Vec<PluginScript>,
     activated_plugins: Vec<PluginScript>,
+    snooze_tx: Option<UnboundedSender<Duration>>,
 }
 
 struct PluginScript {
@@ -74,9 +79,15 @@ impl PluginManager {
         Ok(PluginManager {
             inactivated_plugins: Vec::new(),
             activated_plugins: Vec::new(),
+            snooze_tx: None,
         })
     }
 
+    // Hooks that return `{"snooze": seconds}` postpone the break reminder through this channel
+    pub fn set_snooze_sender(&mut self, snooze_tx: UnboundedSender<Duration>) {
+        self.snooze_tx = Some(snooze_tx);
+    }
+
     // Load all Python plugins in specified directory
     pub fn load_plugins(&mut self, plugin_dir: &str) -> PyResult<()> {
         self.inactivated_plugins.clear();
@@ -101,7 +112,7 @@ impl PluginManager {
         for entry in WalkDir::new(plugin_dir)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().extension().map_or(false, |ext| ext == "py"))
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "py"))
         {
             let path = entry.path();
             match self.load_plugin(path) {
@@ -211,6 +222,7 @@ impl PluginManager {
             let plugin_path = plugin.path.clone();
             let hook = hook_name.to_string();
             let ctx = context.clone();
+            let snooze_tx = self.snooze_tx.clone();
 
             // Check `_RUN_IN_SUBPROCESS` flag in the plugin module or the
             // load-time heuristic `force_subprocess` (e.g., detects tkinter).
@@ -235,12 +247,15 @@ impl PluginManager {
                     mod = runpy.run_path(r'{}')\n\
                     if '{}' in mod:\n    \
                         try:\n        \
-                            mod['{}']({{}})\n    \
+                            result = mod['{}']({{}})\n        \
+                            if isinstance(result, dict) and 'snooze' in result:\n            \
+                                print('{}%d' % int(result['snooze']))\n    \
                         except Exception as e:\n        \
                             import sys, traceback; traceback.print_exc(file=sys.stderr)",
                     plugin_path.display(),
                     hook,
-                    hook
+                    hook,
+                    SNOOZE_MARKER
                 );
 
                 println!(
@@ -253,6 +268,15 @@ impl PluginManager {
                 tokio::spawn(async move {
                     match run_python_code(&python_code).await {
                         Ok(output) => {
+                            let stdout = String::from_utf8_lossy(&output.stdout);
+                            let requested = stdout
+                                .lines()
+                                .filter_map(|line| line.trim().strip_prefix(SNOOZE_MARKER))
+                                .find_map(|seconds| seconds.parse::<u64>().ok());
+                            if let (Some(seconds), Some(snooze_tx)) = (requested, &snooze_tx) {
+                                let _ = snooze_tx.send(Duration::from_secs(seconds));
+                            }
+
                             if !output.status.success() || !output.stderr.is_empty() {
                                 let stderr = String::from_utf8_lossy(&output.stderr);
                                 append_plugin_error(
@@ -288,7 +312,13 @@ impl PluginManager {
                     if let Ok(module) = PyModule::import(py, &plugin_name) {
                         if let Ok(hook_func) = module.getattr(hook.as_str()) {
                             match hook_func.call((py_context.clone(),), None) {
-                                Ok(_) => {
+                                Ok(result) => {
+                                    if let (Some(seconds), Some(snooze_tx)) =
+                                        (requested_snooze(&result), &snooze_tx)
+                                    {
+                                        let _ = snooze_tx.send(Duration::from_secs(seconds));
+                                    }
+
                                     println!(
                                         "  {} {} {}",
                                         "✓".bright_green(),
@@ -375,7 +405,13 @@ impl PluginManager {
     }
 }
 
-#[derive(Clone)]
+// Reads the `snooze` seconds out of a hook's return value, if it asked for one
+fn requested_snooze(result: &Bound<'_, PyAny>) -> Option<u64> {
+    let result = result.downcast::<PyDict>().ok()?;
+    result.get_item("snooze").ok()??.extract::<u64>().ok()
+}
+
+#[derive(Debug, Clone)]
 pub struct PluginContext {
     pub message: String,
     pub timestamp: String,
@@ -384,9 +420,13 @@ pub struct PluginContext {
 
 impl PluginContext {
     pub fn new(message: &str, work_duration: u64) -> Self {
+        Self::at(message, work_duration, Local::now())
+    }
+
+    pub fn at(message: &str, work_duration: u64, timestamp: DateTime<Local>) -> Self {
         PluginContext {
             message: message.to_string(),
-            timestamp: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+            timestamp: timestamp.format("%Y-%m-%d %H:%M:%S").to_string(),
             work_duration,
         }
     }
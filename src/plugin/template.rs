@@ -22,6 +22,38 @@ _RUN_IN_SUBPROCESS = 1
 #     pass
 
 # def on_break_reminder(context):
+#     # Return {"snooze": 600} to postpone the reminder by 600 seconds
+#     pass
+
+# def on_break_snoozed(context):
+#     pass
+
+# def on_snooze_limit(context):
+#     pass
+
+# def on_break_start(context):
+#     pass
+
+# def on_break_end(context):
+#     # context["work_duration"] holds the length of the break in seconds
+#     pass
+
+# def on_break_over(context):
+#     pass
+
+# def on_reminder_escalated(context):
+#     # context["work_duration"] holds how many seconds the break is overdue
+#     pass
+
+# def on_budget_reached(context):
+#     # context["work_duration"] holds the work done today or this week in seconds
+#     pass
+
+# def on_after_hours(context):
+#     pass
+
+# def on_shutdown(context):
+#     # context["work_duration"] holds the length of the session logged on the way out
 #     pass
 
 # Plugin info (optional)
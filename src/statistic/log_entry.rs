@@ -1,7 +1,17 @@
 use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::error::Error;
 
+/// What a log line records. Lines without a `kind` are work sessions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EntryKind {
+    #[default]
+    Work,
+    Break,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogEntry {
     pub start: DateTime<Local>,
@@ -9,8 +19,29 @@ pub struct LogEntry {
     pub duration_seconds: i64,
     #[serde(default)]
     pub apps: Vec<String>,
+    /// Seconds each monitored app was running during the session, keyed by its rule.
+    /// Apps running side by side both count.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub app_seconds: BTreeMap<String, u64>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub task: Option<String>,
+    /// How many times the break reminder was snoozed during this session.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub snoozes: u32,
+    /// Seconds the monitored processes were briefly gone without ending the session.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub gap_seconds: u64,
+    /// Index of the pomodoro this session completed, counted from 1 per run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<u32>,
+    #[serde(default, skip_serializing_if = "is_work_kind")]
+    pub kind: EntryKind,
+    /// A break that ended before the minimum break length.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub violation: bool,
+    /// Work outside the working hours of the `--schedule`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub off_schedule: bool,
 }
 
 impl LogEntry {
@@ -26,10 +57,61 @@ impl LogEntry {
             end,
             duration_seconds,
             apps,
+            app_seconds: BTreeMap::new(),
             task: normalize_task(task),
+            snoozes: 0,
+            gap_seconds: 0,
+            cycle: None,
+            kind: EntryKind::Work,
+            violation: false,
+            off_schedule: false,
+        }
+    }
+
+    pub fn with_cycle(mut self, cycle: Option<u32>) -> Self {
+        self.cycle = cycle;
+        self
+    }
+
+    /// A break taken after a reminder, from leaving the monitored apps until coming back.
+    pub fn new_break(
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+        apps: Vec<String>,
+        task: Option<String>,
+        violation: bool,
+    ) -> Self {
+        Self {
+            kind: EntryKind::Break,
+            violation,
+            ..Self::new(start, end, apps, task)
         }
     }
 
+    pub fn is_work(&self) -> bool {
+        self.kind == EntryKind::Work
+    }
+
+    pub fn with_snoozes(mut self, snoozes: u32) -> Self {
+        self.snoozes = snoozes;
+        self
+    }
+
+    pub fn with_app_seconds(mut self, app_seconds: BTreeMap<String, u64>) -> Self {
+        self.app_seconds = app_seconds;
+        self
+    }
+
+    pub fn with_gap_seconds(mut self, gap_seconds: u64) -> Self {
+        self.gap_seconds = gap_seconds;
+        self
+    }
+
+    pub fn with_off_schedule(mut self, off_schedule: bool) -> Self {
+        self.off_schedule = off_schedule;
+        self
+    }
+
     pub fn to_json_line(&self) -> Result<String, serde_json::Error> {
         serde_json::to_string(self)
     }
@@ -91,6 +173,14 @@ fn parse_local_datetime(value: &str) -> Result<DateTime<Local>, Box<dyn Error>>
     }
 }
 
+fn is_work_kind(kind: &EntryKind) -> bool {
+    *kind == EntryKind::Work
+}
+
+fn is_zero<T: Default + PartialEq>(value: &T) -> bool {
+    *value == T::default()
+}
+
 fn normalize_task(task: Option<String>) -> Option<String> {
     task.map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty())
@@ -8,6 +8,8 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 
 pub const UNLABELED_TASK: &str = "Unlabeled";
+/// Stands for the work of sessions logged before apps were timed separately.
+pub const UNATTRIBUTED_APP: &str = "Unattributed";
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct TaskWorkSummary {
@@ -15,6 +17,22 @@ pub struct TaskWorkSummary {
     pub seconds: i64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppWorkSummary {
+    pub app: String,
+    pub seconds: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BreakSummary {
+    pub breaks: usize,
+    pub total_seconds: i64,
+    pub average_seconds: i64,
+    pub longest_seconds: i64,
+    /// Breaks that ended before the minimum break length.
+    pub violations: usize,
+}
+
 pub fn acc_work_time_precise(
     log_location: PathBuf,
     start: DateTime<Local>,
@@ -77,6 +95,47 @@ pub fn acc_work_time_for_task(
     Ok(calculate_overlap(&entries, start_dt, end_dt, task))
 }
 
+/// The part of [`acc_work_time_precise_for_task`] logged outside the working hours.
+pub fn overtime_work_time_precise_for_task(
+    log_location: PathBuf,
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    task: Option<&str>,
+) -> Result<i64, Box<dyn Error>> {
+    if end < start {
+        panic!("End time must be greater than start time!");
+    }
+
+    let entries = parse_log_entries(&log_location)?;
+    Ok(calculate_overtime(&entries, start, end, task))
+}
+
+/// The part of [`acc_work_time_for_task`] logged outside the working hours.
+pub fn overtime_work_time_for_task(
+    log_location: PathBuf,
+    start_day: DateTime<Local>,
+    end_day: DateTime<Local>,
+    task: Option<&str>,
+) -> Result<i64, Box<dyn Error>> {
+    if end_day < start_day {
+        panic!("End day must be greater than start day!");
+    }
+
+    let naive_start = start_day.date_naive().and_hms_opt(0, 0, 0).unwrap();
+    let naive_end = end_day.date_naive().and_hms_opt(23, 59, 59).unwrap();
+    let start_dt = match Local.from_local_datetime(&naive_start) {
+        LocalResult::Single(dt) => dt,
+        _ => panic!("Invalid local start_day"),
+    };
+    let end_dt = match Local.from_local_datetime(&naive_end) {
+        LocalResult::Single(dt) => dt,
+        _ => panic!("Invalid local end_day"),
+    };
+
+    let entries = parse_log_entries(&log_location)?;
+    Ok(calculate_overtime(&entries, start_dt, end_dt, task))
+}
+
 pub fn task_work_time_summary(
     log_location: PathBuf,
     start_day: DateTime<Local>,
@@ -99,8 +158,8 @@ pub fn task_work_time_summary(
 
     let entries = parse_log_entries(&log_location)?;
     let mut summary = BTreeMap::new();
-    for entry in entries {
-        let overlap_seconds = overlap_seconds(&entry, start_dt, end_dt);
+    for entry in entries.iter().filter(|entry| entry.is_work()) {
+        let overlap_seconds = overlap_seconds(entry, start_dt, end_dt);
         if overlap_seconds <= 0 {
             continue;
         }
@@ -118,6 +177,96 @@ pub fn task_work_time_summary(
         .collect())
 }
 
+/// Work time per monitored app. Apps that ran side by side each get the time,
+/// so the total can exceed the time worked.
+pub fn app_work_time_summary(
+    log_location: PathBuf,
+    start_day: DateTime<Local>,
+    end_day: DateTime<Local>,
+) -> Result<Vec<AppWorkSummary>, Box<dyn Error>> {
+    if end_day < start_day {
+        panic!("End day must be greater than start day!");
+    }
+
+    let naive_start = start_day.date_naive().and_hms_opt(0, 0, 0).unwrap();
+    let naive_end = end_day.date_naive().and_hms_opt(23, 59, 59).unwrap();
+    let start_dt = match Local.from_local_datetime(&naive_start) {
+        LocalResult::Single(dt) => dt,
+        _ => panic!("Invalid local start_day"),
+    };
+    let end_dt = match Local.from_local_datetime(&naive_end) {
+        LocalResult::Single(dt) => dt,
+        _ => panic!("Invalid local end_day"),
+    };
+
+    let entries = parse_log_entries(&log_location)?;
+    let mut summary = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| entry.is_work()) {
+        let overlap_seconds = overlap_seconds(entry, start_dt, end_dt);
+        if overlap_seconds <= 0 {
+            continue;
+        }
+
+        if entry.app_seconds.is_empty() {
+            *summary.entry(UNATTRIBUTED_APP.to_string()).or_insert(0) += overlap_seconds;
+            continue;
+        }
+        // A session cut by the range counts the same share of each app's time
+        let duration = (entry.end - entry.start).num_seconds().max(1);
+        for (app, seconds) in &entry.app_seconds {
+            let seconds = (*seconds as i64).min(duration);
+            *summary.entry(app.clone()).or_insert(0) += seconds * overlap_seconds / duration;
+        }
+    }
+
+    Ok(summary
+        .into_iter()
+        .map(|(app, seconds)| AppWorkSummary { app, seconds })
+        .collect())
+}
+
+pub fn break_summary(
+    log_location: PathBuf,
+    start_day: DateTime<Local>,
+    end_day: DateTime<Local>,
+) -> Result<BreakSummary, Box<dyn Error>> {
+    if end_day < start_day {
+        panic!("End day must be greater than start day!");
+    }
+
+    let naive_start = start_day.date_naive().and_hms_opt(0, 0, 0).unwrap();
+    let naive_end = end_day.date_naive().and_hms_opt(23, 59, 59).unwrap();
+    let start_dt = match Local.from_local_datetime(&naive_start) {
+        LocalResult::Single(dt) => dt,
+        _ => panic!("Invalid local start_day"),
+    };
+    let end_dt = match Local.from_local_datetime(&naive_end) {
+        LocalResult::Single(dt) => dt,
+        _ => panic!("Invalid local end_day"),
+    };
+
+    let entries = parse_log_entries(&log_location)?;
+    let mut summary = BreakSummary::default();
+    for entry in entries.iter().filter(|entry| !entry.is_work()) {
+        let seconds = overlap_seconds(entry, start_dt, end_dt);
+        if seconds <= 0 {
+            continue;
+        }
+
+        summary.breaks += 1;
+        summary.total_seconds += seconds;
+        summary.longest_seconds = summary.longest_seconds.max(seconds);
+        if entry.violation {
+            summary.violations += 1;
+        }
+    }
+    if summary.breaks > 0 {
+        summary.average_seconds = summary.total_seconds / summary.breaks as i64;
+    }
+
+    Ok(summary)
+}
+
 pub fn single_day_work_time(
     log_location: PathBuf,
     day: DateTime<Local>,
@@ -171,7 +320,7 @@ fn calculate_overlap(
     entries
         .iter()
         .fold(Duration::zero(), |acc, entry| {
-            if !entry.task_matches(task) {
+            if !entry.is_work() || !entry.task_matches(task) {
                 return acc;
             }
 
@@ -185,6 +334,20 @@ fn calculate_overlap(
         .num_seconds()
 }
 
+fn calculate_overtime(
+    entries: &[LogEntry],
+    range_start: DateTime<Local>,
+    range_end: DateTime<Local>,
+    task: Option<&str>,
+) -> i64 {
+    let off_schedule: Vec<LogEntry> = entries
+        .iter()
+        .filter(|entry| entry.off_schedule)
+        .cloned()
+        .collect();
+    calculate_overlap(&off_schedule, range_start, range_end, task)
+}
+
 fn overlap_seconds(
     entry: &LogEntry,
     range_start: DateTime<Local>,
@@ -1,7 +1,8 @@
 use crate::cli::{parse_datetime_local, parse_datetime_local_day};
 use crate::statistic::statistics::{
-    TaskWorkSummary, acc_work_time_for_task, acc_work_time_precise_for_task,
-    single_day_work_time_for_task, task_work_time_summary,
+    AppWorkSummary, TaskWorkSummary, acc_work_time_for_task, acc_work_time_precise_for_task,
+    app_work_time_summary, break_summary, overtime_work_time_for_task,
+    overtime_work_time_precise_for_task, single_day_work_time_for_task, task_work_time_summary,
 };
 use actix_web::{HttpResponse, Responder, post, web};
 use serde::{Deserialize, Serialize};
@@ -37,9 +38,25 @@ struct CountByTaskRequest {
     end_time: String,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+struct CountByAppRequest {
+    log_path: String,
+    start_time: String,
+    end_time: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct CountBreaksRequest {
+    log_path: String,
+    start_time: String,
+    end_time: String,
+}
+
 #[derive(Serialize)]
 struct CountResponse {
     seconds: i64,
+    /// The part of `seconds` worked outside the working hours.
+    overtime_seconds: i64,
 }
 
 #[derive(Serialize)]
@@ -47,6 +64,11 @@ struct CountByTaskResponse {
     summaries: Vec<TaskWorkSummary>,
 }
 
+#[derive(Serialize)]
+struct CountByAppResponse {
+    summaries: Vec<AppWorkSummary>,
+}
+
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
@@ -79,9 +101,16 @@ async fn count(req_body: web::Json<CountRequest>) -> impl Responder {
         });
     }
 
-    match acc_work_time_for_task(log_path, start_time, end_time, req_body.task.as_deref()) {
-        Ok(seconds) => HttpResponse::Ok().json(CountResponse { seconds }),
-        Err(_) => HttpResponse::BadRequest().json(ErrorResponse {
+    let task = req_body.task.as_deref();
+    match (
+        acc_work_time_for_task(log_path.clone(), start_time, end_time, task),
+        overtime_work_time_for_task(log_path, start_time, end_time, task),
+    ) {
+        (Ok(seconds), Ok(overtime_seconds)) => HttpResponse::Ok().json(CountResponse {
+            seconds,
+            overtime_seconds,
+        }),
+        _ => HttpResponse::BadRequest().json(ErrorResponse {
             error: "Failed to get work time".to_string(),
         }),
     }
@@ -122,6 +151,76 @@ async fn count_by_task(req_body: web::Json<CountByTaskRequest>) -> impl Responde
     }
 }
 
+#[post("/count-by-app")]
+async fn count_by_app(req_body: web::Json<CountByAppRequest>) -> impl Responder {
+    let log_path = PathBuf::from(&req_body.log_path.as_str());
+    let start_time = match parse_datetime_local_day(req_body.start_time.as_str()) {
+        Ok(t) => t,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                error: "Invalid start time".to_string(),
+            });
+        }
+    };
+
+    let end_time = match parse_datetime_local_day(req_body.end_time.as_str()) {
+        Ok(t) => t,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                error: "Invalid end time".to_string(),
+            });
+        }
+    };
+
+    if end_time < start_time {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "End time must be greater than start time".to_string(),
+        });
+    }
+
+    match app_work_time_summary(log_path, start_time, end_time) {
+        Ok(summaries) => HttpResponse::Ok().json(CountByAppResponse { summaries }),
+        Err(_) => HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Failed to get app summary".to_string(),
+        }),
+    }
+}
+
+#[post("/count-breaks")]
+async fn count_breaks(req_body: web::Json<CountBreaksRequest>) -> impl Responder {
+    let log_path = PathBuf::from(&req_body.log_path.as_str());
+    let start_time = match parse_datetime_local_day(req_body.start_time.as_str()) {
+        Ok(t) => t,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                error: "Invalid start time".to_string(),
+            });
+        }
+    };
+
+    let end_time = match parse_datetime_local_day(req_body.end_time.as_str()) {
+        Ok(t) => t,
+        Err(_) => {
+            return HttpResponse::BadRequest().json(ErrorResponse {
+                error: "Invalid end time".to_string(),
+            });
+        }
+    };
+
+    if end_time < start_time {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "End time must be greater than start time".to_string(),
+        });
+    }
+
+    match break_summary(log_path, start_time, end_time) {
+        Ok(summary) => HttpResponse::Ok().json(summary),
+        Err(_) => HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Failed to get break summary".to_string(),
+        }),
+    }
+}
+
 #[post("/count-single-day")]
 async fn count_single_day(req_body: web::Json<CountSingleDayRequest>) -> impl Responder {
     let log_path = PathBuf::from(&req_body.log_path.as_str());
@@ -134,9 +233,16 @@ async fn count_single_day(req_body: web::Json<CountSingleDayRequest>) -> impl Re
         }
     };
 
-    match single_day_work_time_for_task(log_path, date, req_body.task.as_deref()) {
-        Ok(seconds) => HttpResponse::Ok().json(CountResponse { seconds }),
-        Err(_) => HttpResponse::BadRequest().json(ErrorResponse {
+    let task = req_body.task.as_deref();
+    match (
+        single_day_work_time_for_task(log_path.clone(), date, task),
+        overtime_work_time_for_task(log_path, date, date, task),
+    ) {
+        (Ok(seconds), Ok(overtime_seconds)) => HttpResponse::Ok().json(CountResponse {
+            seconds,
+            overtime_seconds,
+        }),
+        _ => HttpResponse::BadRequest().json(ErrorResponse {
             error: "Failed to get work time".to_string(),
         }),
     }
@@ -145,7 +251,7 @@ async fn count_single_day(req_body: web::Json<CountSingleDayRequest>) -> impl Re
 #[post("/count-precise")]
 async fn count_precise(req_body: web::Json<CountPreciseRequest>) -> impl Responder {
     let log_path = PathBuf::from(&req_body.log_path.as_str());
-    let start_time = match parse_datetime_local(&req_body.start_time.as_str()) {
+    let start_time = match parse_datetime_local(req_body.start_time.as_str()) {
         Ok(t) => t,
         Err(_) => {
             return HttpResponse::BadRequest().json(ErrorResponse {
@@ -154,7 +260,7 @@ async fn count_precise(req_body: web::Json<CountPreciseRequest>) -> impl Respond
         }
     };
 
-    let end_time = match parse_datetime_local(&req_body.end_time.as_str()) {
+    let end_time = match parse_datetime_local(req_body.end_time.as_str()) {
         Ok(t) => t,
         Err(_) => {
             return HttpResponse::BadRequest().json(ErrorResponse {
@@ -169,9 +275,16 @@ async fn count_precise(req_body: web::Json<CountPreciseRequest>) -> impl Respond
         });
     }
 
-    match acc_work_time_precise_for_task(log_path, start_time, end_time, req_body.task.as_deref()) {
-        Ok(seconds) => HttpResponse::Ok().json(CountResponse { seconds }),
-        Err(_) => HttpResponse::BadRequest().json(ErrorResponse {
+    let task = req_body.task.as_deref();
+    match (
+        acc_work_time_precise_for_task(log_path.clone(), start_time, end_time, task),
+        overtime_work_time_precise_for_task(log_path, start_time, end_time, task),
+    ) {
+        (Ok(seconds), Ok(overtime_seconds)) => HttpResponse::Ok().json(CountResponse {
+            seconds,
+            overtime_seconds,
+        }),
+        _ => HttpResponse::BadRequest().json(ErrorResponse {
             error: "Failed to get work time".to_string(),
         }),
     }
@@ -184,7 +184,7 @@ fn plugin_info_value(code: &str, key: &str) -> Option<String> {
 }
 
 fn plugin_hooks(code: &str) -> Vec<String> {
-    let regex = Regex::new(r"(?m)^\s*def\s+(on_init|on_work_start|on_break_reminder)\s*\(")
+    let regex = Regex::new(r"(?m)^\s*def\s+(on_init|on_work_start|on_break_reminder|on_break_snoozed|on_snooze_limit|on_break_start|on_break_end|on_break_over|on_reminder_escalated|on_budget_reached|on_after_hours|on_shutdown)\s*\(")
         .expect("hook regex should compile");
     let mut hooks = regex
         .captures_iter(code)
@@ -1,28 +1,77 @@
-use actix_web::{HttpResponse, Responder, get};
-use serde::Serialize;
+use crate::core::matcher::{AppRule, ProcessTree};
+use crate::core::probe::{ProcessProbe, SysinfoProbe};
+use actix_web::{HttpResponse, Responder, get, web};
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeSet;
-use sysinfo::{ProcessesToUpdate, System};
+
+#[derive(Deserialize)]
+struct ProcessQuery {
+    rule: Option<String>,
+}
 
 #[derive(Serialize)]
 struct ProcessListResponse {
     processes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    matches: Option<Vec<ProcessMatch>>,
+}
+
+#[derive(Serialize)]
+struct ProcessMatch {
+    pid: u32,
+    name: String,
+    exe: Option<String>,
+    cmd: String,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
 }
 
 #[get("/processes")]
-async fn list_processes() -> impl Responder {
-    let mut system = System::new_all();
-    system.refresh_processes(ProcessesToUpdate::All, true);
+async fn list_processes(query: web::Query<ProcessQuery>) -> impl Responder {
+    let rule = match query.rule.as_deref().map(str::parse::<AppRule>) {
+        Some(Ok(rule)) => Some(rule),
+        Some(Err(e)) => return HttpResponse::BadRequest().json(ErrorResponse { error: e }),
+        None => None,
+    };
 
-    let processes = system
-        .processes()
-        .values()
+    let mut probe = SysinfoProbe::new();
+    let current_user = probe.current_user();
+    let mut snapshot = probe.processes();
+    if let Some(rule) = &rule {
+        // Lets the UI preview what a rule would pick up before starting a monitor
+        let tree = ProcessTree::new(&snapshot);
+        snapshot = snapshot
+            .iter()
+            .filter(|process| rule.matches_in(process, &tree, current_user.as_deref()))
+            .cloned()
+            .collect();
+        snapshot.sort_by_key(|process| process.pid);
+    }
+
+    let processes = snapshot
+        .iter()
         .filter_map(|process| {
-            let name = process.name().to_string_lossy().trim().to_string();
+            let name = process.name.trim().to_string();
             (!name.is_empty()).then_some(name)
         })
         .collect::<BTreeSet<_>>()
         .into_iter()
         .collect();
 
-    HttpResponse::Ok().json(ProcessListResponse { processes })
+    let matches = rule.map(|_| {
+        snapshot
+            .into_iter()
+            .map(|process| ProcessMatch {
+                pid: process.pid,
+                name: process.name,
+                exe: process.exe,
+                cmd: process.cmd,
+            })
+            .collect()
+    });
+
+    HttpResponse::Ok().json(ProcessListResponse { processes, matches })
 }
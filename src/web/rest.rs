@@ -1,11 +1,27 @@
-use crate::core::core::run_rest_reminder_dynamic;
+use crate::core::activity::AppActivity;
+use crate::core::budget::{BudgetConfig, BudgetStatus};
+use crate::core::core::{
+    MonitorConfig, MonitorControl, MonitorStatus, log_file_path, run_rest_reminder,
+};
+use crate::core::engine::{MonitorState, PomodoroConfig, PomodoroStatus};
+use crate::core::git_task::{GitTaskConfig, parse_git_task_rules};
+use crate::core::lock::{LockError, MonitorLock};
+use crate::core::matcher::{AppRule, parse_app_rules};
+use crate::core::notify::{NotifyConfig, parse_notify_routes};
+use crate::core::poll::PollIntervals;
+use crate::core::schedule::Schedule;
+use crate::core::signals::{SignalRequest, StopSignal};
+use crate::core::snooze::DEFAULT_SNOOZE_SECONDS;
+use crate::core::suspend::listen_for_sleep;
+use crate::core::task_rules::TaskRules;
 use actix_web::{HttpResponse, Responder, get, post, web};
 use chrono::{DateTime, Local};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::{LazyLock, Mutex};
-use tokio::sync::watch;
+use tokio::sync::{mpsc, watch};
+use tokio::time::Duration;
 
 static MONITOR_SESSION: LazyLock<Mutex<Option<MonitorSession>>> =
     LazyLock::new(|| Mutex::new(None));
@@ -16,18 +32,74 @@ struct RestRequest {
     time: u64,
     app_list: Vec<String>,
     task: Option<String>,
+    #[serde(default)]
+    git_task: bool,
+    #[serde(default)]
+    git_task_rules: Vec<String>,
+    #[serde(default)]
+    task_rules: Option<String>,
+    #[serde(default)]
+    idle_threshold: Option<u64>,
+    #[serde(default)]
+    snooze_seconds: Option<u64>,
+    #[serde(default)]
+    max_snoozes: Option<u32>,
+    #[serde(default)]
+    min_break: Option<u64>,
+    #[serde(default)]
+    pomodoro: Option<PomodoroConfig>,
+    #[serde(default)]
+    remind_every: Option<u64>,
+    #[serde(default)]
+    budget: Option<BudgetConfig>,
+    #[serde(default)]
+    start_debounce: Option<u64>,
+    #[serde(default)]
+    end_grace: Option<u64>,
+    #[serde(default)]
+    schedule: Option<String>,
+    #[serde(default)]
+    notify: Vec<String>,
+    #[serde(default)]
+    poll: Option<PollIntervals>,
+}
+
+#[derive(Deserialize)]
+struct SnoozeRequest {
+    seconds: Option<u64>,
 }
 
 struct MonitorSession {
-    handle: actix_web::rt::task::JoinHandle<()>,
+    handle: actix_web::rt::task::JoinHandle<StopSignal>,
     started_at: DateTime<Local>,
     log_path: String,
     time: u64,
     app_list: Vec<String>,
     task: Option<String>,
+    git_task: bool,
+    git_task_rules: Vec<String>,
+    task_rules: Option<String>,
+    idle_threshold: Option<u64>,
+    snooze_seconds: u64,
+    max_snoozes: Option<u32>,
+    min_break: Option<u64>,
+    pomodoro: Option<PomodoroConfig>,
+    remind_every: Option<u64>,
+    budget: Option<BudgetConfig>,
+    start_debounce: Option<u64>,
+    end_grace: Option<u64>,
+    schedule: Option<String>,
+    notify: Vec<String>,
+    poll: PollIntervals,
     pause_tx: watch::Sender<bool>,
-    app_tx: watch::Sender<Vec<String>>,
+    app_tx: watch::Sender<Vec<AppRule>>,
+    snooze_tx: mpsc::UnboundedSender<Duration>,
+    stop_tx: mpsc::UnboundedSender<SignalRequest>,
+    task_tx: mpsc::UnboundedSender<Option<String>>,
+    status_rx: watch::Receiver<MonitorStatus>,
     app_started_at: HashMap<String, DateTime<Local>>,
+    // Released when the session is stopped or replaced
+    _lock: MonitorLock,
 }
 
 #[derive(Serialize)]
@@ -45,7 +117,36 @@ struct MonitorStatusResponse {
     app_list: Vec<String>,
     app_statuses: Vec<MonitorAppStatus>,
     task: Option<String>,
+    git_task: bool,
+    git_task_rules: Vec<String>,
+    task_rules: Option<String>,
+    current_task: Option<String>,
+    task_rule: Option<String>,
+    idle_threshold: Option<u64>,
     paused: bool,
+    state: Option<MonitorState>,
+    session_started_at: Option<String>,
+    reminder_pending: bool,
+    snoozes: u32,
+    max_snoozes: Option<u32>,
+    snoozed_until: Option<String>,
+    min_break: Option<u64>,
+    break_started_at: Option<String>,
+    pomodoro: Option<PomodoroConfig>,
+    pomodoro_status: Option<PomodoroStatus>,
+    remind_every: Option<u64>,
+    escalations: u32,
+    overdue_seconds: u64,
+    budget: Option<BudgetConfig>,
+    budget_status: Option<BudgetStatus>,
+    start_debounce: Option<u64>,
+    end_grace: Option<u64>,
+    schedule: Option<String>,
+    off_schedule: bool,
+    quiet: bool,
+    activity: BTreeMap<String, AppActivity>,
+    notify: Vec<String>,
+    poll: Option<PollIntervals>,
 }
 
 #[derive(Serialize)]
@@ -63,6 +164,7 @@ fn monitor_status_from(session: Option<&MonitorSession>) -> MonitorStatusRespons
     match session {
         Some(session) => {
             let now = Local::now();
+            let status = session.status_rx.borrow().clone();
             MonitorStatusResponse {
                 running: true,
                 started_at: Some(session.started_at.to_rfc3339()),
@@ -83,7 +185,40 @@ fn monitor_status_from(session: Option<&MonitorSession>) -> MonitorStatusRespons
                     })
                     .collect(),
                 task: session.task.clone(),
+                git_task: session.git_task,
+                git_task_rules: session.git_task_rules.clone(),
+                task_rules: session.task_rules.clone(),
+                current_task: status.task,
+                task_rule: status.task_rule,
+                idle_threshold: session.idle_threshold,
                 paused: *session.pause_tx.borrow(),
+                state: Some(status.state),
+                session_started_at: status
+                    .session_started_at
+                    .map(|started_at| started_at.to_rfc3339()),
+                reminder_pending: status.reminder_pending,
+                snoozes: status.snoozes,
+                max_snoozes: session.max_snoozes,
+                snoozed_until: status.snoozed_until.map(|until| until.to_rfc3339()),
+                min_break: session.min_break,
+                break_started_at: status
+                    .break_started_at
+                    .map(|started_at| started_at.to_rfc3339()),
+                pomodoro: session.pomodoro,
+                pomodoro_status: status.pomodoro,
+                remind_every: session.remind_every,
+                escalations: status.escalations,
+                overdue_seconds: status.overdue_seconds,
+                budget: session.budget.clone(),
+                budget_status: status.budget,
+                start_debounce: session.start_debounce,
+                end_grace: session.end_grace,
+                schedule: session.schedule.clone(),
+                off_schedule: status.off_schedule,
+                quiet: status.quiet,
+                activity: status.activity,
+                notify: session.notify.clone(),
+                poll: Some(session.poll),
             }
         }
         None => MonitorStatusResponse {
@@ -95,7 +230,36 @@ fn monitor_status_from(session: Option<&MonitorSession>) -> MonitorStatusRespons
             app_list: Vec::new(),
             app_statuses: Vec::new(),
             task: None,
+            git_task: false,
+            git_task_rules: Vec::new(),
+            task_rules: None,
+            current_task: None,
+            task_rule: None,
+            idle_threshold: None,
             paused: false,
+            state: None,
+            session_started_at: None,
+            reminder_pending: false,
+            snoozes: 0,
+            max_snoozes: None,
+            snoozed_until: None,
+            min_break: None,
+            break_started_at: None,
+            pomodoro: None,
+            pomodoro_status: None,
+            remind_every: None,
+            escalations: 0,
+            overdue_seconds: 0,
+            budget: None,
+            budget_status: None,
+            start_debounce: None,
+            end_grace: None,
+            schedule: None,
+            off_schedule: false,
+            quiet: false,
+            activity: BTreeMap::new(),
+            notify: Vec::new(),
+            poll: None,
         },
     }
 }
@@ -114,14 +278,90 @@ async fn rest(rest_request: web::Json<RestRequest>) -> impl Responder {
     let log_path = PathBuf::from(&rest_request.log_path.as_str());
     let log_path_string = rest_request.log_path.clone();
     let time = rest_request.time;
+    let idle_threshold = rest_request.idle_threshold;
+    let snooze_seconds = rest_request
+        .snooze_seconds
+        .unwrap_or(DEFAULT_SNOOZE_SECONDS);
+    let max_snoozes = rest_request.max_snoozes;
+    let min_break = rest_request.min_break;
+    let pomodoro = rest_request.pomodoro;
+    if pomodoro.is_some_and(|pomodoro| pomodoro.cycles == 0) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Pomodoro cycles must be at least 1".to_string(),
+        });
+    }
+    let remind_every = rest_request.remind_every;
+    if remind_every == Some(0) {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Reminder repeat interval must be at least 1 second".to_string(),
+        });
+    }
+    let budget = rest_request
+        .budget
+        .clone()
+        .and_then(|budget| BudgetConfig::new(budget.daily, budget.weekly, budget.alerts));
+    let start_debounce = rest_request.start_debounce;
+    let end_grace = rest_request.end_grace;
+    let poll = rest_request.poll.unwrap_or_default();
+    if let Err(e) = poll.validate() {
+        return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+    }
+    let notify = rest_request.notify.clone();
+    let notify_routes = match parse_notify_routes(&notify) {
+        Ok(routes) => routes,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+        }
+    };
     let app_list = normalized_apps(&rest_request.app_list);
-    let app_list_for_task = app_list.clone();
+    let app_rules = match parse_app_rules(&app_list) {
+        Ok(rules) => rules,
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+        }
+    };
     let task = rest_request
         .task
         .as_ref()
         .map(|task| task.trim().to_string())
         .filter(|task| !task.is_empty());
     let task_for_task = task.clone();
+    let git_task = rest_request.git_task || !rest_request.git_task_rules.is_empty();
+    let git_task_rules = rest_request.git_task_rules.clone();
+    let git_task_config = match parse_git_task_rules(&git_task_rules) {
+        Ok(rules) => git_task.then(|| GitTaskConfig::new(rules)),
+        Err(e) => {
+            return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+        }
+    };
+    let task_rules = rest_request
+        .task_rules
+        .as_ref()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+    let task_rules_config = match task_rules
+        .as_ref()
+        .map(|path| TaskRules::load(Path::new(path)))
+    {
+        Some(Err(e)) => {
+            return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+        }
+        loaded => loaded.and_then(Result::ok),
+    };
+    let schedule = rest_request
+        .schedule
+        .as_ref()
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty());
+    let schedule_config = match schedule
+        .as_ref()
+        .map(|path| Schedule::load(Path::new(path)))
+    {
+        Some(Err(e)) => {
+            return HttpResponse::BadRequest().json(ErrorResponse { error: e });
+        }
+        loaded => loaded.and_then(Result::ok),
+    };
 
     let mut current_session = match MONITOR_SESSION.lock() {
         Ok(session) => session,
@@ -134,13 +374,30 @@ async fn rest(rest_request: web::Json<RestRequest>) -> impl Responder {
 
     clear_finished_session(&mut current_session);
     if let Some(session) = current_session.as_mut() {
-        session.log_path = log_path_string;
-        session.time = time;
+        // The running monitor and its lock stay on the file it was started with
+        if log_file_path(Path::new(&session.log_path)) != log_file_path(&log_path) {
+            return HttpResponse::Conflict().json(ErrorResponse {
+                error: format!(
+                    "Already monitoring {}, stop it before logging to another file",
+                    session.log_path
+                ),
+            });
+        }
+        // The work time, idle threshold, snooze limit, minimum break, pomodoro lengths,
+        // reminder repeats, budgets, debounce, grace, git task rules, task rules, schedule,
+        // notification sinks and poll intervals belong to the running monitor, only the
+        // apps, the task and the default snooze length can change
+        if session.task_tx.send(task.clone()).is_err() {
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Failed to update the task".to_string(),
+            });
+        }
         session.task = task;
+        session.snooze_seconds = snooze_seconds;
         session.app_list = app_list.clone();
         update_app_started_at(&mut session.app_started_at, &app_list);
 
-        if session.app_tx.send(app_list).is_err() {
+        if session.app_tx.send(app_rules).is_err() {
             return HttpResponse::InternalServerError().json(ErrorResponse {
                 error: "Failed to update monitored apps".to_string(),
             });
@@ -151,24 +408,61 @@ async fn rest(rest_request: web::Json<RestRequest>) -> impl Responder {
         });
     }
 
+    // A monitor started from a terminal may already log to the same file
+    let lock = match MonitorLock::acquire(&log_path) {
+        Ok(lock) => lock,
+        Err(e @ LockError::Held { .. }) => {
+            return HttpResponse::Conflict().json(ErrorResponse {
+                error: e.to_string(),
+            });
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                error: e.to_string(),
+            });
+        }
+    };
     let (pause_tx, pause_rx) = watch::channel(false);
-    let (app_tx, app_rx) = watch::channel(app_list.clone());
+    let (app_tx, app_rx) = watch::channel(app_rules.clone());
+    let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+    let (stop_tx, stop_rx) = mpsc::unbounded_channel();
+    let (task_tx, task_rx) = mpsc::unbounded_channel();
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
     let app_started_at = app_list
         .iter()
         .map(|app| (app.clone(), Local::now()))
         .collect();
 
-    let handle = actix_web::rt::spawn(async move {
-        run_rest_reminder_dynamic(
-            log_path,
-            time,
-            app_list_for_task,
-            task_for_task,
-            Some(pause_rx),
-            app_rx,
-        )
-        .await;
-    });
+    let config = MonitorConfig {
+        log_location: log_path,
+        time,
+        apps: app_rules,
+        task: task_for_task,
+        git_task: git_task_config,
+        task_rules: task_rules_config,
+        idle_threshold,
+        snooze: snooze_seconds,
+        max_snoozes,
+        min_break,
+        pomodoro,
+        remind_every,
+        budget: budget.clone(),
+        start_debounce,
+        end_grace,
+        schedule: schedule_config,
+        notify: NotifyConfig::default().with_routes(&notify_routes),
+        poll,
+    };
+    let control = MonitorControl {
+        pause_rx: Some(pause_rx),
+        app_rx: Some(app_rx),
+        snooze_rx: Some(snooze_rx),
+        status_tx: Some(status_tx),
+        signal_rx: Some(stop_rx),
+        sleep_rx: Some(listen_for_sleep()),
+        task_rx: Some(task_rx),
+    };
+    let handle = actix_web::rt::spawn(run_rest_reminder(config, control));
 
     *current_session = Some(MonitorSession {
         handle,
@@ -177,9 +471,29 @@ async fn rest(rest_request: web::Json<RestRequest>) -> impl Responder {
         time,
         app_list,
         task,
+        git_task,
+        git_task_rules,
+        task_rules,
+        idle_threshold,
+        snooze_seconds,
+        max_snoozes,
+        min_break,
+        pomodoro,
+        remind_every,
+        budget,
+        start_debounce,
+        end_grace,
+        schedule,
+        notify,
+        poll,
         pause_tx,
         app_tx,
+        snooze_tx,
+        stop_tx,
+        task_tx,
+        status_rx,
         app_started_at,
+        _lock: lock,
     });
 
     HttpResponse::Ok().json(RestResponse {
@@ -214,8 +528,8 @@ fn update_app_started_at(
 
 #[post("/rest/stop")]
 async fn stop_rest() -> impl Responder {
-    let mut current_session = match MONITOR_SESSION.lock() {
-        Ok(session) => session,
+    let stopped = match MONITOR_SESSION.lock() {
+        Ok(mut session) => session.take(),
         Err(_) => {
             return HttpResponse::InternalServerError().json(ErrorResponse {
                 error: "Failed to lock monitor session".to_string(),
@@ -223,8 +537,13 @@ async fn stop_rest() -> impl Responder {
         }
     };
 
-    if let Some(session) = current_session.take() {
-        session.handle.abort();
+    // Waits for the monitor to log its open session, the log is complete once this
+    // is answered
+    if let Some(session) = stopped {
+        let _ = session
+            .stop_tx
+            .send(SignalRequest::Stop(StopSignal::Terminate));
+        let _ = session.handle.await;
     }
 
     HttpResponse::Ok().json(RestResponse {
@@ -270,6 +589,61 @@ fn set_pause_state(paused: bool, status: &str) -> HttpResponse {
     })
 }
 
+#[post("/rest/snooze")]
+async fn snooze_rest(snooze_request: Option<web::Json<SnoozeRequest>>) -> impl Responder {
+    let mut current_session = match MONITOR_SESSION.lock() {
+        Ok(session) => session,
+        Err(_) => {
+            return HttpResponse::InternalServerError().json(ErrorResponse {
+                error: "Failed to lock monitor session".to_string(),
+            });
+        }
+    };
+
+    clear_finished_session(&mut current_session);
+    let Some(session) = current_session.as_ref() else {
+        return HttpResponse::Conflict().json(ErrorResponse {
+            error: "Monitoring is not running".to_string(),
+        });
+    };
+
+    let status = session.status_rx.borrow().clone();
+    if !status.reminder_pending {
+        return HttpResponse::Conflict().json(ErrorResponse {
+            error: "There is no break reminder to snooze".to_string(),
+        });
+    }
+
+    let seconds = snooze_request
+        .and_then(|request| request.seconds)
+        .unwrap_or(session.snooze_seconds);
+    if seconds == 0 {
+        return HttpResponse::BadRequest().json(ErrorResponse {
+            error: "Snooze length must be greater than 0".to_string(),
+        });
+    }
+
+    // Past the limit the monitor still gets the request, it answers with an escalated reminder
+    let limit_reached = session.max_snoozes.is_some_and(|max| status.snoozes >= max);
+    if session
+        .snooze_tx
+        .send(Duration::from_secs(seconds))
+        .is_err()
+    {
+        return HttpResponse::InternalServerError().json(ErrorResponse {
+            error: "Failed to snooze the break reminder".to_string(),
+        });
+    }
+
+    HttpResponse::Ok().json(RestResponse {
+        status: if limit_reached {
+            "snooze_limit_reached".to_string()
+        } else {
+            "snoozed".to_string()
+        },
+    })
+}
+
 #[get("/rest/status")]
 async fn rest_status() -> impl Responder {
     let mut current_session = match MONITOR_SESSION.lock() {
@@ -307,6 +681,31 @@ mod tests {
         String::from_utf8(body.to_vec()).expect("response body should be utf-8")
     }
 
+    // A monitor of an app that never runs, each test overrides what it checks
+    fn test_request(log: &Path) -> RestRequest {
+        RestRequest {
+            log_path: log.to_string_lossy().to_string(),
+            time: 3600,
+            app_list: vec!["rest-reminder-test-process-that-should-not-exist".to_string()],
+            task: None,
+            git_task: false,
+            git_task_rules: Vec::new(),
+            task_rules: None,
+            idle_threshold: None,
+            snooze_seconds: None,
+            max_snoozes: None,
+            min_break: None,
+            pomodoro: None,
+            remind_every: None,
+            budget: None,
+            start_debounce: None,
+            end_grace: None,
+            schedule: None,
+            notify: Vec::new(),
+            poll: None,
+        }
+    }
+
     #[actix_web::test]
     async fn status_reports_stopped_when_no_monitor_is_running() {
         let _guard = TEST_LOCK.lock().await;
@@ -352,13 +751,8 @@ mod tests {
         )
         .await;
         let request = RestRequest {
-            log_path: std::env::temp_dir()
-                .join("rest_reminder_route_test")
-                .to_string_lossy()
-                .to_string(),
-            time: 3600,
-            app_list: vec!["rest-reminder-test-process-that-should-not-exist".to_string()],
             task: Some("test".to_string()),
+            ..test_request(&std::env::temp_dir().join("rest_reminder_route_test"))
         };
 
         let start_req = test::TestRequest::post()
@@ -437,4 +831,233 @@ mod tests {
         let resume_response = test::call_service(&app, resume_req).await;
         assert_eq!(resume_response.status(), StatusCode::CONFLICT);
     }
+
+    #[actix_web::test]
+    async fn starting_monitor_rejects_invalid_app_rules() {
+        let _guard = TEST_LOCK.lock().await;
+        reset_monitor_session();
+
+        let app = test::init_service(App::new().service(rest).service(rest_status)).await;
+        let request = RestRequest {
+            app_list: vec!["regex:(unclosed".to_string()],
+            ..test_request(&std::env::temp_dir().join("rest_reminder_route_test"))
+        };
+
+        let start_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&request)
+            .to_request();
+        let start_response = test::call_service(&app, start_req).await;
+        assert_eq!(start_response.status(), StatusCode::BAD_REQUEST);
+        let body = body_to_string(test::read_body(start_response).await);
+        assert!(body.contains("Invalid regex"));
+
+        let status_req = test::TestRequest::get().uri("/rest/status").to_request();
+        let status_response = test::call_service(&app, status_req).await;
+        let status_body = body_to_string(test::read_body(status_response).await);
+        assert!(status_body.contains(r#""running":false"#));
+    }
+
+    #[actix_web::test]
+    async fn start_is_refused_while_another_monitor_logs_to_the_file() {
+        let _guard = TEST_LOCK.lock().await;
+        reset_monitor_session();
+
+        let app = test::init_service(App::new().service(rest).service(stop_rest)).await;
+        let log_path = std::env::temp_dir().join(format!(
+            "rest_reminder_route_lock_test_{}",
+            std::process::id()
+        ));
+        let request = test_request(&log_path);
+
+        // As a monitor started from a terminal would hold it
+        let lock = MonitorLock::acquire(&log_path).unwrap();
+        let start_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&request)
+            .to_request();
+        let start_response = test::call_service(&app, start_req).await;
+        assert_eq!(start_response.status(), StatusCode::CONFLICT);
+        let body = body_to_string(test::read_body(start_response).await);
+        assert!(body.contains(&format!(
+            "already monitored by process {}",
+            std::process::id()
+        )));
+
+        drop(lock);
+        let start_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&request)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, start_req).await.status(),
+            StatusCode::OK
+        );
+        let stop_req = test::TestRequest::post().uri("/rest/stop").to_request();
+        assert_eq!(
+            test::call_service(&app, stop_req).await.status(),
+            StatusCode::OK
+        );
+        assert!(MonitorLock::acquire(&log_path).is_ok());
+        reset_monitor_session();
+    }
+
+    #[actix_web::test]
+    async fn update_cannot_move_a_running_monitor_to_another_log() {
+        let _guard = TEST_LOCK.lock().await;
+        reset_monitor_session();
+
+        let app = test::init_service(
+            App::new()
+                .service(rest)
+                .service(rest_status)
+                .service(stop_rest),
+        )
+        .await;
+        let log_dir = std::env::temp_dir().join(format!(
+            "rest_reminder_route_update_test_{}_{}",
+            std::process::id(),
+            Local::now().timestamp_nanos_opt().unwrap()
+        ));
+        std::fs::create_dir_all(&log_dir).unwrap();
+        let other_log = log_dir.join("other_log.txt");
+        let request = test_request(&log_dir);
+        let start_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&request)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, start_req).await.status(),
+            StatusCode::OK
+        );
+
+        let moved = RestRequest {
+            log_path: other_log.to_string_lossy().to_string(),
+            ..request.clone()
+        };
+        let update_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&moved)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, update_req).await.status(),
+            StatusCode::CONFLICT
+        );
+        let status_req = test::TestRequest::get().uri("/rest/status").to_request();
+        let status_body =
+            body_to_string(test::read_body(test::call_service(&app, status_req).await).await);
+        assert!(!status_body.contains("other_log.txt"));
+        assert!(MonitorLock::acquire(&other_log).is_ok());
+
+        // The file form of the same log is no move
+        let same = RestRequest {
+            log_path: log_dir.join("focus_log.txt").to_string_lossy().to_string(),
+            task: Some("review".to_string()),
+            ..request.clone()
+        };
+        let update_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&same)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, update_req).await.status(),
+            StatusCode::OK
+        );
+
+        let stop_req = test::TestRequest::post().uri("/rest/stop").to_request();
+        test::call_service(&app, stop_req).await;
+        reset_monitor_session();
+    }
+
+    #[actix_web::test]
+    async fn stop_logs_the_open_session() {
+        let _guard = TEST_LOCK.lock().await;
+        reset_monitor_session();
+
+        let app = test::init_service(App::new().service(rest).service(stop_rest)).await;
+        let log_dir = std::env::temp_dir().join(format!(
+            "rest_reminder_route_stop_test_{}_{}",
+            std::process::id(),
+            Local::now().timestamp_nanos_opt().unwrap()
+        ));
+        std::fs::create_dir_all(&log_dir).unwrap();
+        let request = RestRequest {
+            // The test binary itself is the monitored app
+            app_list: vec!["regex:^rest_reminder".to_string()],
+            task: Some("review".to_string()),
+            poll: Some(PollIntervals {
+                resting: 1,
+                working: 1,
+            }),
+            ..test_request(&log_dir)
+        };
+        let start_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&request)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, start_req).await.status(),
+            StatusCode::OK
+        );
+
+        let mut working = false;
+        for _ in 0..50 {
+            working = MONITOR_SESSION
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|session| session.status_rx.borrow().session_started_at.is_some());
+            if working {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(working, "the test process should open a session");
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+
+        let stop_req = test::TestRequest::post().uri("/rest/stop").to_request();
+        assert_eq!(
+            test::call_service(&app, stop_req).await.status(),
+            StatusCode::OK
+        );
+        let log = std::fs::read_to_string(log_dir.join("focus_log.txt")).unwrap();
+        assert!(log.contains(r#""task":"review""#), "{}", log);
+        assert!(log.contains("regex:^rest_reminder"), "{}", log);
+        assert!(MonitorLock::acquire(&log_dir).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn snooze_rejects_when_no_reminder_is_pending() {
+        let _guard = TEST_LOCK.lock().await;
+        reset_monitor_session();
+
+        let app = test::init_service(App::new().service(rest).service(snooze_rest)).await;
+        let snooze_req = test::TestRequest::post().uri("/rest/snooze").to_request();
+        let snooze_response = test::call_service(&app, snooze_req).await;
+        assert_eq!(snooze_response.status(), StatusCode::CONFLICT);
+
+        let request = RestRequest {
+            snooze_seconds: Some(300),
+            max_snoozes: Some(2),
+            ..test_request(&std::env::temp_dir().join("rest_reminder_route_test"))
+        };
+        let start_req = test::TestRequest::post()
+            .uri("/rest")
+            .set_json(&request)
+            .to_request();
+        assert_eq!(
+            test::call_service(&app, start_req).await.status(),
+            StatusCode::OK
+        );
+
+        let snooze_req = test::TestRequest::post()
+            .uri("/rest/snooze")
+            .set_json(serde_json::json!({ "seconds": 60 }))
+            .to_request();
+        let snooze_response = test::call_service(&app, snooze_req).await;
+        assert_eq!(snooze_response.status(), StatusCode::CONFLICT);
+        let body = body_to_string(test::read_body(snooze_response).await);
+        assert!(body.contains("no break reminder"));
+        reset_monitor_session();
+    }
 }
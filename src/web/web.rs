@@ -1,10 +1,12 @@
-use crate::web::count::{count, count_by_task, count_precise, count_single_day};
+use crate::web::count::{
+    count, count_breaks, count_by_app, count_by_task, count_precise, count_single_day,
+};
 use crate::web::dialog::{pick_directory, pick_file, pick_save_file};
 use crate::web::log::log_preview;
 use crate::web::plot::plot_work_trend;
 use crate::web::plugin::{disable_plugin, enable_plugin, generate_plugin, list_plugins};
 use crate::web::process::list_processes;
-use crate::web::rest::{pause_rest, rest, rest_status, resume_rest, stop_rest};
+use crate::web::rest::{pause_rest, rest, rest_status, resume_rest, snooze_rest, stop_rest};
 use actix_files::Files;
 use actix_web::{App, HttpServer, rt};
 use std::thread;
@@ -20,10 +22,13 @@ pub async fn spawn_web_server() -> thread::JoinHandle<std::io::Result<()>> {
                     .service(rest)
                     .service(pause_rest)
                     .service(resume_rest)
+                    .service(snooze_rest)
                     .service(stop_rest)
                     .service(rest_status)
                     .service(count)
                     .service(count_by_task)
+                    .service(count_by_app)
+                    .service(count_breaks)
                     .service(count_single_day)
                     .service(count_precise)
                     .service(plot_work_trend)
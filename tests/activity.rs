@@ -0,0 +1,156 @@
+use rest_reminder::core::activity::ActivityTracker;
+use rest_reminder::core::matcher::{AppRule, parse_app_rules};
+use rest_reminder::core::probe::ProcessInfo;
+use tokio::time::{Duration, advance};
+
+fn process(
+    pid: u32,
+    name: &str,
+    parent: Option<u32>,
+    cpu_time: u64,
+    disk_bytes: u64,
+) -> ProcessInfo {
+    ProcessInfo {
+        parent,
+        cpu_time,
+        disk_bytes,
+        ..ProcessInfo::new(pid, name)
+    }
+}
+
+fn rules(rules: &[&str]) -> Vec<AppRule> {
+    parse_app_rules(
+        &rules
+            .iter()
+            .map(|rule| rule.to_string())
+            .collect::<Vec<_>>(),
+    )
+    .unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn usage_is_averaged_over_the_window() {
+    let rules = rules(&["Code~cpu=20,window=10", "Slack"]);
+    let mut tracker = ActivityTracker::new();
+
+    // The first check has nothing to compare with
+    let running = tracker.update(
+        &[
+            process(1, "Code", None, 5_000, 0),
+            process(2, "Slack", None, 0, 0),
+        ],
+        &rules,
+        None,
+    );
+    assert_eq!(running, ["Slack"]);
+    assert!(!tracker.figures()["Code"].active);
+
+    // Half a core for 4 seconds
+    advance(Duration::from_secs(4)).await;
+    let running = tracker.update(&[process(1, "Code", None, 7_000, 4096)], &rules, None);
+    assert_eq!(running, ["Code"]);
+    let code = tracker.figures()["Code"];
+    assert_eq!(code.cpu_percent, 50.0);
+    assert_eq!(code.io_bytes_per_second, 1024);
+    assert!(code.active);
+    assert!(!tracker.figures().contains_key("Slack"));
+
+    // Idle for 6 more seconds: 2 of 10 seconds of CPU is exactly the threshold
+    advance(Duration::from_secs(6)).await;
+    assert!(
+        tracker
+            .update(&[process(1, "Code", None, 7_000, 4096)], &rules, None)
+            .is_empty()
+    );
+    assert_eq!(tracker.figures()["Code"].cpu_percent, 20.0);
+
+    // The busy seconds drop out of the window
+    advance(Duration::from_secs(4)).await;
+    tracker.update(&[process(1, "Code", None, 7_000, 4096)], &rules, None);
+    assert_eq!(tracker.figures()["Code"].cpu_percent, 0.0);
+    assert_eq!(tracker.figures()["Code"].io_bytes_per_second, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn tree_rules_count_the_child_processes() {
+    let rules = rules(&["exact:code~cpu=10", "exact:node~cpu=10,tree"]);
+    let mut tracker = ActivityTracker::new();
+    let processes = |server_time| {
+        [
+            process(1, "code", None, 0, 0),
+            process(2, "node", None, 0, 0),
+            // Started by both through a shell
+            process(3, "bash", Some(1), 0, 0),
+            process(4, "tsserver", Some(3), server_time, 0),
+            process(5, "bash", Some(2), 0, 0),
+            process(6, "tsserver", Some(5), server_time, 0),
+        ]
+    };
+
+    tracker.update(&processes(0), &rules, None);
+    advance(Duration::from_secs(10)).await;
+    let running = tracker.update(&processes(5_000), &rules, None);
+
+    assert_eq!(running, ["exact:node"]);
+    assert_eq!(tracker.figures()["exact:code"].cpu_percent, 0.0);
+    assert_eq!(tracker.figures()["exact:node"].cpu_percent, 50.0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn short_lived_processes_linger_between_runs() {
+    let rules = rules(&["tmux > cargo~linger=30"]);
+    let mut tracker = ActivityTracker::new();
+    let tmux = process(1, "tmux: server", None, 0, 0);
+    let cargo = process(2, "cargo", Some(1), 0, 0);
+    let stray = process(3, "cargo", None, 0, 0);
+
+    assert!(
+        tracker
+            .update(&[tmux.clone(), stray], &rules, None)
+            .is_empty()
+    );
+    assert_eq!(
+        tracker.update(&[tmux.clone(), cargo], &rules, None),
+        ["tmux > cargo"]
+    );
+    advance(Duration::from_secs(29)).await;
+    assert_eq!(
+        tracker.update(std::slice::from_ref(&tmux), &rules, None),
+        ["tmux > cargo"]
+    );
+    advance(Duration::from_secs(1)).await;
+    assert!(tracker.update(&[tmux], &rules, None).is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+async fn processes_new_since_the_last_check_only_set_a_baseline() {
+    let rules = rules(&["Code~cpu=20,window=10"]);
+    let mut tracker = ActivityTracker::new();
+    tracker.update(&[process(1, "Slack", None, 0, 0)], &rules, None);
+
+    // Idle for hours before it is first seen, its lifetime usage is no burst
+    advance(Duration::from_secs(2)).await;
+    let idle = process(1, "Slack", None, 0, 0);
+    let code = |cpu_time| process(2, "Code", None, cpu_time, 64 * 1024 * 1024);
+    assert!(
+        tracker
+            .update(&[idle.clone(), code(3_600_000)], &rules, None)
+            .is_empty()
+    );
+    assert_eq!(tracker.figures()["Code"].cpu_percent, 0.0);
+    advance(Duration::from_secs(2)).await;
+    assert!(
+        tracker
+            .update(&[idle.clone(), code(3_600_000)], &rules, None)
+            .is_empty()
+    );
+    assert_eq!(tracker.figures()["Code"].io_bytes_per_second, 0);
+
+    // Counted from the baseline on
+    advance(Duration::from_secs(2)).await;
+    assert_eq!(
+        tracker.update(&[idle, code(3_601_000)], &rules, None),
+        ["Code"]
+    );
+    assert_eq!(tracker.figures()["Code"].cpu_percent, 25.0);
+}
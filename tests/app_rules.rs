@@ -0,0 +1,293 @@
+use rest_reminder::core::matcher::{
+    AppRule, ProcessTree, any_rule_matches, matching_rules, parse_app_rules,
+};
+use rest_reminder::core::probe::{ProcessInfo, ScanDetails};
+
+fn process(name: &str, exe: Option<&str>, cmd: &str, user: Option<&str>) -> ProcessInfo {
+    ProcessInfo {
+        pid: 1,
+        name: name.to_string(),
+        exe: exe.map(str::to_string),
+        cmd: cmd.to_string(),
+        user_id: user.map(str::to_string),
+        ..ProcessInfo::default()
+    }
+}
+
+fn rule(source: &str) -> AppRule {
+    source.parse().expect("rule should parse")
+}
+
+#[test]
+fn plain_names_keep_substring_matching() {
+    let code = process("Code Helper", None, "", None);
+    assert!(rule("Code").matches(&code, None));
+    assert!(!rule("code").matches(&code, None));
+}
+
+#[test]
+fn exact_and_ignore_case_compare_the_whole_name() {
+    let code = process("code", None, "", None);
+    let helper = process("code-helper", None, "", None);
+
+    assert!(rule("exact:code").matches(&code, None));
+    assert!(!rule("exact:code").matches(&helper, None));
+    assert!(rule("iname:CODE").matches(&code, None));
+    assert!(!rule("iname:CODE").matches(&helper, None));
+}
+
+#[test]
+fn regex_and_glob_match_the_name() {
+    let analyzer = process("rust-analyzer", None, "", None);
+    let rustc = process("rustc", None, "", None);
+
+    assert!(rule("regex:^rust(rover|-analyzer)$").matches(&analyzer, None));
+    assert!(!rule("regex:^rust(rover|-analyzer)$").matches(&rustc, None));
+    assert!(rule("glob:rust*").matches(&rustc, None));
+    assert!(rule("glob:rust?").matches(&rustc, None));
+    assert!(!rule("glob:rust?").matches(&analyzer, None));
+    assert!(rule("glob:[!x]ustc").matches(&rustc, None));
+}
+
+#[test]
+fn glob_classes_match_ranges() {
+    let cargo = process("cargo", None, "", None);
+    let bargo = process("bargo", None, "", None);
+    let dargo = process("dargo", None, "", None);
+
+    assert!(rule("glob:[a-c]argo").matches(&cargo, None));
+    assert!(rule("glob:[a-c]argo").matches(&bargo, None));
+    assert!(!rule("glob:[a-c]argo").matches(&dargo, None));
+    assert!(!rule("glob:[!a-c]argo").matches(&cargo, None));
+    assert!(rule("glob:[!a-c]argo").matches(&dargo, None));
+    assert!(rule("glob:[^c]argo").matches(&cargo, None));
+    assert!(!rule("glob:[^c]argo").matches(&bargo, None));
+}
+
+#[test]
+fn exe_matches_full_path_or_trailing_components() {
+    let idea = process(
+        "java",
+        Some("/opt/idea/jbr/bin/java"),
+        "java -jar idea.jar",
+        None,
+    );
+
+    assert!(rule("exe:/opt/idea/jbr/bin/java").matches(&idea, None));
+    assert!(rule("exe:jbr/bin/java").matches(&idea, None));
+    assert!(!rule("exe:r/bin/java").matches(&idea, None));
+    assert!(!rule("exe:java").matches(&process("java", None, "", None), None));
+}
+
+#[test]
+fn cmd_matches_the_command_line() {
+    let notebook = process("python3", None, "python3 -m jupyter notebook", None);
+
+    assert!(rule("cmd:jupyter notebook").matches(&notebook, None));
+    assert!(!rule("cmd:jupyter lab").matches(&notebook, None));
+}
+
+#[test]
+fn mine_only_matches_processes_of_the_current_user() {
+    let mine = process("python3", None, "", Some("1000"));
+    let theirs = process("python3", None, "", Some("1001"));
+    let unknown = process("python3", None, "", None);
+    let rules = vec![rule("mine:exact:python3")];
+
+    assert!(any_rule_matches(
+        std::slice::from_ref(&mine),
+        &rules,
+        Some("1000")
+    ));
+    assert!(!any_rule_matches(&[theirs], &rules, Some("1000")));
+    assert!(!any_rule_matches(&[unknown], &rules, Some("1000")));
+    assert!(!any_rule_matches(&[mine], &rules, None));
+}
+
+#[test]
+fn invalid_rules_are_rejected() {
+    assert!("regex:(unclosed".parse::<AppRule>().is_err());
+    assert!("exact:".parse::<AppRule>().is_err());
+    assert!("  ".parse::<AppRule>().is_err());
+
+    let error = parse_app_rules(&["Code".to_string(), "regex:[".to_string()]).unwrap_err();
+    assert!(error.contains("Invalid regex"));
+}
+
+#[test]
+fn rules_display_as_written() {
+    let rules = parse_app_rules(&["mine:glob:*.py".to_string(), "Code".to_string()]).unwrap();
+    assert_eq!(rules[0].to_string(), "mine:glob:*.py");
+    assert_eq!(rules[1].as_str(), "Code");
+}
+
+#[test]
+fn trailing_seconds_set_a_threshold_of_their_own() {
+    let game = rule("mine:glob:steam*@1200");
+    assert_eq!(game.threshold(), Some(1200));
+    assert_eq!(game.as_str(), "mine:glob:steam*");
+    assert_eq!(game.to_string(), "mine:glob:steam*@1200");
+    assert!(game.matches(
+        &process("steamwebhelper", None, "", Some("1000")),
+        Some("1000")
+    ));
+
+    // Only digits after the last '@' make a threshold
+    let versioned = rule("cmd:node@lts");
+    assert_eq!(versioned.threshold(), None);
+    assert!(versioned.matches(&process("node", None, "npx node@lts", None), None));
+    assert_eq!(rule("Code").threshold(), None);
+
+    assert!("Code@0".parse::<AppRule>().is_err());
+    assert!("@600".parse::<AppRule>().is_err());
+    assert_ne!(rule("Code@600"), rule("Code"));
+}
+
+#[test]
+fn a_trailing_activity_only_counts_the_app_while_busy() {
+    let code = rule("Code@1800~cpu=2.5,io=64k,window=120,tree");
+    let activity = code.activity().unwrap();
+    assert_eq!(code.as_str(), "Code");
+    assert_eq!(code.threshold(), Some(1800));
+    assert_eq!(activity.cpu, Some(2.5));
+    assert_eq!(activity.io, Some(64 * 1024));
+    assert_eq!(activity.window, 120);
+    assert!(activity.tree);
+    assert_eq!(
+        code.to_string(),
+        "Code@1800~cpu=2.5,io=65536,window=120,tree"
+    );
+    assert_eq!(rule(&code.to_string()), code);
+
+    let busy_disk = rule("Code~io=1m");
+    assert_eq!(busy_disk.activity().unwrap().cpu, None);
+    assert_eq!(busy_disk.activity().unwrap().window, 60);
+    assert_ne!(busy_disk, rule("Code"));
+
+    // Only a known activity makes a suffix
+    let home = rule("cmd:~/bin/server");
+    assert!(home.activity().is_none());
+    assert_eq!(home.as_str(), "cmd:~/bin/server");
+
+    for invalid in ["Code~cpu=fast", "Code~io=-1", "Code~window=0", "Code~tree"] {
+        assert!(invalid.parse::<AppRule>().is_err(), "{}", invalid);
+    }
+}
+
+fn child(pid: u32, name: &str, parent: u32, cwd: Option<&str>) -> ProcessInfo {
+    ProcessInfo {
+        parent: Some(parent),
+        cwd: cwd.map(str::to_string),
+        ..ProcessInfo::new(pid, name)
+    }
+}
+
+#[test]
+fn ancestors_are_matched_from_the_bottom_up() {
+    let processes = [
+        ProcessInfo::new(1, "alacritty"),
+        child(2, "tmux: server", 1, None),
+        child(3, "bash", 2, Some("/home/me/work/app")),
+        child(4, "nvim", 3, Some("/home/me/work/app")),
+        child(5, "nvim", 1, Some("/tmp")),
+        child(6, "cargo", 3, Some("/home/me/work/app")),
+    ];
+    let tree = ProcessTree::new(&processes);
+    let matches = |source: &str| -> Vec<u32> {
+        let rule = rule(source);
+        processes
+            .iter()
+            .filter(|process| rule.matches_in(process, &tree, None))
+            .map(|process| process.pid)
+            .collect()
+    };
+
+    assert_eq!(
+        tree.ancestors(&processes[3])
+            .iter()
+            .map(|process| process.pid)
+            .collect::<Vec<_>>(),
+        [3, 2, 1]
+    );
+    assert_eq!(matches("tmux > nvim"), [4]);
+    assert_eq!(matches("tmux > *"), [3, 4, 6]);
+    assert_eq!(matches("alacritty > tmux > exact:cargo"), [6]);
+    // The parts have to appear in this order on the way up
+    assert!(matches("tmux > alacritty > nvim").is_empty());
+    assert_eq!(matches("alacritty > cwd:/home/me/work"), [3, 4, 6]);
+    assert!(matches("alacritty > cwd:/home/me/wor").is_empty());
+
+    // Only `matches_in` looks at the ancestors
+    assert!(rule("tmux > nvim").matches(&processes[4], None));
+    assert!(rule("tmux > nvim").has_ancestors());
+    assert_eq!(rule("tmux > nvim@600").as_str(), "tmux > nvim");
+    assert_eq!(
+        matching_rules(
+            &processes,
+            &parse_app_rules(&["bash > cargo".to_string()]).unwrap(),
+            None
+        ),
+        ["bash > cargo"]
+    );
+}
+
+#[test]
+fn cwd_rules_expand_the_home_directory() {
+    let Some(home) = std::env::var_os("HOME") else {
+        return;
+    };
+    let work = std::path::Path::new(&home).join("work").join("app");
+    let process = ProcessInfo {
+        cwd: Some(work.to_string_lossy().to_string()),
+        ..ProcessInfo::new(1, "bash")
+    };
+
+    assert!(rule("cwd:~/work").matches(&process, None));
+    assert!(!rule("cwd:~/play").matches(&process, None));
+    assert!("cwd:".parse::<AppRule>().is_err());
+    assert!("tmux >  > nvim".parse::<AppRule>().is_err());
+}
+
+#[test]
+fn matching_rules_names_every_rule_with_a_process() {
+    let rules = parse_app_rules(&[
+        "Code".to_string(),
+        "Game@600".to_string(),
+        "Slack".to_string(),
+    ])
+    .unwrap();
+    let processes = [
+        process("Code Helper", None, "", None),
+        process("Game", None, "", None),
+        process("Code", None, "", None),
+    ];
+
+    assert_eq!(matching_rules(&processes, &rules, None), ["Code", "Game"]);
+    assert!(matching_rules(&[], &rules, None).is_empty());
+}
+
+#[test]
+fn scans_read_only_what_the_rule_looks_at() {
+    assert_eq!(rule("Code").scan_details(), ScanDetails::default());
+    assert_eq!(
+        rule("Game@600~linger=30").scan_details(),
+        ScanDetails::default()
+    );
+    assert_eq!(
+        rule("mine:exe:/usr/bin/python3 > cmd:train.py~cpu=50").scan_details(),
+        ScanDetails {
+            exe: true,
+            cmd: true,
+            user: true,
+            usage: true,
+            ..ScanDetails::default()
+        }
+    );
+    assert_eq!(
+        rule("cwd:~/work > *").scan_details(),
+        ScanDetails {
+            cwd: true,
+            ..ScanDetails::default()
+        }
+    );
+}
@@ -0,0 +1,248 @@
+#![cfg(unix)]
+
+use chrono::Local;
+use rest_reminder::core::lock::MonitorLock;
+use rest_reminder::daemon::client::send;
+use rest_reminder::daemon::protocol::{Request, Response, StartRequest};
+use rest_reminder::daemon::server::DaemonServer;
+use serde_json::json;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_daemon_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    dir
+}
+
+async fn spawn_daemon(dir: &std::path::Path) -> (Arc<DaemonServer>, PathBuf) {
+    let socket = dir.join("daemon.sock");
+    let server = Arc::new(DaemonServer::bind(&socket).await.unwrap());
+    let serving = server.clone();
+    tokio::spawn(async move { serving.serve().await });
+    (server, socket)
+}
+
+// Asks for the status until `check` holds, signals take a moment to arrive
+async fn wait_for_status(socket: &std::path::Path, check: impl Fn(&serde_json::Value) -> bool) {
+    for _ in 0..100 {
+        if let Ok(Response {
+            status: Some(status),
+            ..
+        }) = send(socket, &Request::Status).await
+            && check(&status)
+        {
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    }
+    panic!("the daemon on {} never got there", socket.display());
+}
+
+fn start_request(log_path: PathBuf) -> Request {
+    Request::Start(StartRequest {
+        log_path,
+        time: 3600,
+        apps: vec!["no-such-app-for-rest-reminder".to_string()],
+        task: Some("review".to_string()),
+        idle_threshold: None,
+        snooze_seconds: None,
+    })
+}
+
+#[test]
+fn requests_are_one_json_object_per_command() {
+    let request: Request = serde_json::from_str(r#"{"command":"snooze","seconds":300}"#).unwrap();
+    assert_eq!(request, Request::Snooze { seconds: Some(300) });
+    let request: Request = serde_json::from_str(r#"{"command":"set-task"}"#).unwrap();
+    assert_eq!(request, Request::SetTask { task: None });
+    assert_eq!(
+        serde_json::to_value(Request::Status).unwrap(),
+        json!({"command": "status"})
+    );
+    assert_eq!(
+        serde_json::to_value(Response::error("nope")).unwrap(),
+        json!({"ok": false, "error": "nope"})
+    );
+}
+
+#[tokio::test]
+async fn daemon_starts_controls_and_stops_a_monitor() {
+    let dir = temp_dir("session");
+    let (_server, socket) = spawn_daemon(&dir).await;
+    let log_path = dir.join("focus_log.txt");
+
+    let status = send(&socket, &Request::Status).await.unwrap();
+    assert!(status.ok);
+    assert_eq!(status.status.unwrap()["running"], json!(false));
+
+    // Nothing runs yet to pause
+    let paused = send(&socket, &Request::Pause).await.unwrap();
+    assert!(!paused.ok);
+
+    let started = send(&socket, &start_request(log_path.clone()))
+        .await
+        .unwrap();
+    assert!(started.ok, "{:?}", started.error);
+    let status = started.status.unwrap();
+    assert_eq!(status["running"], json!(true));
+    assert_eq!(status["log_path"], json!(log_path));
+
+    // The daemon's monitor holds the log like any other
+    assert!(MonitorLock::acquire(&log_path).is_err());
+    let again = send(&socket, &start_request(log_path.clone()))
+        .await
+        .unwrap();
+    assert!(!again.ok);
+
+    let paused = send(&socket, &Request::Pause).await.unwrap();
+    assert!(paused.ok);
+    assert_eq!(paused.status.unwrap()["paused"], json!(true));
+    let resumed = send(&socket, &Request::Resume).await.unwrap();
+    assert_eq!(resumed.status.unwrap()["paused"], json!(false));
+
+    let task = send(
+        &socket,
+        &Request::SetTask {
+            task: Some("docs".to_string()),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(task.ok);
+
+    // No reminder is due within the first hour
+    let snoozed = send(&socket, &Request::Snooze { seconds: None })
+        .await
+        .unwrap();
+    assert!(!snoozed.ok);
+    assert!(snoozed.error.unwrap().contains("no break reminder"));
+
+    let stopped = send(&socket, &Request::Stop).await.unwrap();
+    assert!(stopped.ok);
+    assert_eq!(stopped.status.unwrap()["running"], json!(false));
+    assert!(MonitorLock::acquire(&log_path).is_ok());
+}
+
+#[tokio::test]
+async fn invalid_lines_are_answered_and_the_connection_stays_open() {
+    let dir = temp_dir("invalid");
+    let (_server, socket) = spawn_daemon(&dir).await;
+
+    let stream = UnixStream::connect(&socket).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    writer
+        .write_all(b"{\"command\":\"dance\"}\n")
+        .await
+        .unwrap();
+    let answer: Response =
+        serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+    assert!(!answer.ok);
+    assert!(answer.error.unwrap().starts_with("Invalid request"));
+
+    writer
+        .write_all(b"{\"command\":\"status\"}\n")
+        .await
+        .unwrap();
+    let answer: Response =
+        serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+    assert!(answer.ok);
+}
+
+#[tokio::test]
+async fn a_second_daemon_cannot_take_over_a_live_socket() {
+    let dir = temp_dir("second");
+    let (server, socket) = spawn_daemon(&dir).await;
+
+    let err = DaemonServer::bind(&socket).await.err().unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+
+    // Only the owner may connect
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::metadata(server.socket())
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[tokio::test]
+async fn a_stale_socket_is_replaced() {
+    let dir = temp_dir("stale");
+    let socket = dir.join("daemon.sock");
+    // Left behind by a daemon that was killed: the file exists, nobody listens
+    drop(std::os::unix::net::UnixListener::bind(&socket).unwrap());
+    assert!(socket.exists());
+
+    let server = DaemonServer::bind(&socket).await.unwrap();
+    drop(server);
+    assert!(!socket.exists());
+}
+
+#[tokio::test]
+async fn signals_reach_the_monitor_of_a_daemon_process() {
+    let dir = temp_dir("signals");
+    let socket = dir.join("daemon.sock");
+    let log_path = dir.join("focus_log.txt");
+    let mut daemon = std::process::Command::new(env!("CARGO_BIN_EXE_rest-reminder"))
+        .arg("daemon")
+        .arg("--socket")
+        .arg(&socket)
+        .stdout(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let signal = |name: &str| {
+        let sent = std::process::Command::new("kill")
+            .arg(format!("-{}", name))
+            .arg(daemon.id().to_string())
+            .status()
+            .unwrap();
+        assert!(sent.success());
+    };
+    // Answered only once the daemon listens for signals
+    wait_for_status(&socket, |_| true).await;
+
+    // The daemon itself is the monitored app
+    let started = send(
+        &socket,
+        &Request::Start(StartRequest {
+            log_path: log_path.clone(),
+            time: 3600,
+            apps: vec!["exact:rest-reminder".to_string()],
+            task: None,
+            idle_threshold: None,
+            snooze_seconds: None,
+        }),
+    )
+    .await
+    .unwrap();
+    assert!(started.ok, "{:?}", started.error);
+    wait_for_status(&socket, |status| !status["session_started_at"].is_null()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+    signal("USR1");
+    wait_for_status(&socket, |status| status["paused"] == json!(true)).await;
+    signal("USR1");
+    wait_for_status(&socket, |status| status["paused"] == json!(false)).await;
+    signal("USR2");
+    wait_for_status(&socket, |status| status["running"] == json!(true)).await;
+
+    signal("TERM");
+    let exit = tokio::task::spawn_blocking(move || daemon.wait())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(exit.code(), Some(143));
+    let log = std::fs::read_to_string(&log_path).unwrap();
+    assert!(log.contains("exact:rest-reminder"), "{}", log);
+    assert!(!socket.exists());
+}
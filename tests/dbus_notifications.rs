@@ -0,0 +1,199 @@
+#![cfg(not(any(windows, target_os = "macos")))]
+
+use rest_reminder::core::dbus::{DbusNotifier, Notification};
+use rest_reminder::core::hooks::ReminderAction;
+use rest_reminder::core::notify::Urgency;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use tokio::time::Duration;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::OwnedValue;
+
+// A private session bus, so the tests neither need nor disturb a desktop session
+struct PrivateBus {
+    daemon: Child,
+    address: String,
+}
+
+impl PrivateBus {
+    fn start() -> Option<Self> {
+        let mut daemon = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address=1"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .ok()?;
+        let mut address = String::new();
+        BufReader::new(daemon.stdout.take()?)
+            .read_line(&mut address)
+            .ok()?;
+        Some(PrivateBus {
+            daemon,
+            address: address.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for PrivateBus {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+#[derive(Debug, Clone)]
+struct NotifyCall {
+    icon: String,
+    summary: String,
+    actions: Vec<String>,
+    urgency: Option<u8>,
+    expire_timeout: i32,
+}
+
+// Stands in for the desktop's notification server and clicks `click` on every notification
+struct FakeServer {
+    calls: Arc<Mutex<Vec<NotifyCall>>>,
+    click: Option<&'static str>,
+}
+
+#[zbus::interface(name = "org.freedesktop.Notifications")]
+impl FakeServer {
+    #[allow(clippy::too_many_arguments)]
+    async fn notify(
+        &self,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        _app_name: String,
+        _replaces_id: u32,
+        app_icon: String,
+        summary: String,
+        _body: String,
+        actions: Vec<String>,
+        hints: HashMap<String, OwnedValue>,
+        expire_timeout: i32,
+    ) -> u32 {
+        self.calls.lock().unwrap().push(NotifyCall {
+            icon: app_icon,
+            summary,
+            actions,
+            urgency: hints
+                .get("urgency")
+                .and_then(|urgency| u8::try_from(urgency).ok()),
+            expire_timeout,
+        });
+
+        let id = 7;
+        if let Some(key) = self.click {
+            // Clicked after the reply, like a user would, right after a click on
+            // another application's notification
+            let emitter = emitter.to_owned();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                let _ = FakeServer::action_invoked(&emitter, 6, "take_break").await;
+                let _ = FakeServer::action_invoked(&emitter, id, key).await;
+            });
+        }
+        id
+    }
+
+    #[zbus(signal)]
+    async fn action_invoked(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        action_key: &str,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    async fn notification_closed(
+        emitter: &SignalEmitter<'_>,
+        id: u32,
+        reason: u32,
+    ) -> zbus::Result<()>;
+}
+
+async fn serve(
+    bus: &PrivateBus,
+    click: Option<&'static str>,
+) -> (zbus::Connection, Arc<Mutex<Vec<NotifyCall>>>) {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let server = FakeServer {
+        calls: calls.clone(),
+        click,
+    };
+    let connection = zbus::connection::Builder::address(bus.address.as_str())
+        .unwrap()
+        .name("org.freedesktop.Notifications")
+        .unwrap()
+        .serve_at("/org/freedesktop/Notifications", server)
+        .unwrap()
+        .build()
+        .await
+        .expect("notification server should start");
+    (connection, calls)
+}
+
+fn reminder() -> Notification {
+    Notification {
+        summary: "Time to take a break".to_string(),
+        body: "3600 seconds NON-STOOOOOOOOOOOOP!".to_string(),
+        icon: "appointment-soon".to_string(),
+        urgency: Urgency::Critical,
+        timeout: None,
+        actions: vec![ReminderAction::TakeBreak, ReminderAction::Snooze],
+    }
+}
+
+#[tokio::test]
+async fn clicked_button_is_returned_for_its_own_notification() {
+    let Some(bus) = PrivateBus::start() else {
+        println!("dbus-daemon is not installed, skipping");
+        return;
+    };
+    let (_server, calls) = serve(&bus, Some("snooze")).await;
+
+    let notifier = DbusNotifier::with_address(&bus.address).await.unwrap();
+    let clicked = tokio::time::timeout(Duration::from_secs(10), notifier.notify(&reminder()))
+        .await
+        .expect("click should arrive")
+        .unwrap();
+
+    assert_eq!(clicked, Some(ReminderAction::Snooze));
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].summary, "Time to take a break");
+    assert_eq!(calls[0].icon, "appointment-soon");
+    assert_eq!(
+        calls[0].actions,
+        vec!["take_break", "Take break", "snooze", "Snooze"]
+    );
+    assert_eq!(calls[0].urgency, Some(2));
+    assert_eq!(calls[0].expire_timeout, 0);
+}
+
+#[tokio::test]
+async fn notification_without_buttons_returns_once_shown() {
+    let Some(bus) = PrivateBus::start() else {
+        println!("dbus-daemon is not installed, skipping");
+        return;
+    };
+    let (_server, calls) = serve(&bus, None).await;
+
+    let notifier = DbusNotifier::with_address(&bus.address).await.unwrap();
+    let notification = Notification {
+        summary: "100% of your daily budget".to_string(),
+        body: "8.0 HOURS TODAY IS 100% OF YOUR DAILY BUDGET!".to_string(),
+        icon: "dialog-information".to_string(),
+        urgency: Urgency::Normal,
+        timeout: Some(Duration::from_secs(10)),
+        actions: Vec::new(),
+    };
+    let clicked = notifier.notify(&notification).await.unwrap();
+
+    assert_eq!(clicked, None);
+    let calls = calls.lock().unwrap().clone();
+    assert_eq!(calls.len(), 1);
+    assert!(calls[0].actions.is_empty());
+    assert_eq!(calls[0].urgency, Some(1));
+    assert_eq!(calls[0].expire_timeout, 10_000);
+}
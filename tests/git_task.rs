@@ -0,0 +1,148 @@
+use chrono::Local;
+use rest_reminder::core::git_task::{
+    GitTaskConfig, GitTaskRule, find_repo, parse_git_task_rules, process_repo, read_branch,
+};
+use rest_reminder::core::matcher::AppRule;
+use rest_reminder::core::probe::ProcessInfo;
+use std::path::{Path, PathBuf};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_git_task_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    dir
+}
+
+// A checkout named `name` under `dir` with `head` as its HEAD
+fn checkout(dir: &Path, name: &str, head: &str) -> PathBuf {
+    let root = dir.join(name);
+    std::fs::create_dir_all(root.join(".git")).unwrap();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::write(root.join(".git/HEAD"), head).unwrap();
+    std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+    root
+}
+
+fn process(pid: u32, args: &[&str], cwd: Option<&Path>) -> ProcessInfo {
+    ProcessInfo {
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        cwd: cwd.map(|cwd| cwd.to_string_lossy().to_string()),
+        ..ProcessInfo::new(pid, "code")
+    }
+}
+
+#[test]
+fn head_names_the_branch_or_the_detached_commit() {
+    assert_eq!(
+        read_branch("ref: refs/heads/feature/login\n").as_deref(),
+        Some("feature/login")
+    );
+    assert_eq!(
+        read_branch("4b825dc642cb6eb9a060e54bf8d69288fbee4904").as_deref(),
+        Some("4b825dc")
+    );
+    assert_eq!(read_branch("ref: "), None);
+    assert_eq!(read_branch("garbage"), None);
+}
+
+#[test]
+fn repositories_are_found_from_nested_paths_and_worktrees() {
+    let dir = temp_dir("find");
+    let root = checkout(&dir, "app", "ref: refs/heads/main\n");
+
+    let repo = find_repo(&root.join("src/main.rs")).unwrap();
+    assert_eq!(repo.root, root);
+    assert_eq!(repo.key(), "app:main");
+
+    // A worktree points at its git directory from a `.git` file
+    let worktree = dir.join("app-hotfix");
+    let git_dir = root.join(".git/worktrees/app-hotfix");
+    std::fs::create_dir_all(&worktree).unwrap();
+    std::fs::create_dir_all(&git_dir).unwrap();
+    std::fs::write(git_dir.join("HEAD"), "ref: refs/heads/hotfix\n").unwrap();
+    std::fs::write(
+        worktree.join(".git"),
+        format!("gitdir: {}\n", git_dir.display()),
+    )
+    .unwrap();
+    assert_eq!(find_repo(&worktree).unwrap().key(), "app-hotfix:hotfix");
+
+    assert_eq!(find_repo(&dir.join("elsewhere")), None);
+}
+
+#[test]
+fn project_arguments_win_over_the_working_directory() {
+    let dir = temp_dir("process");
+    checkout(&dir, "app", "ref: refs/heads/main\n");
+    let docs = checkout(&dir, "docs", "ref: refs/heads/draft\n");
+
+    let opened = process(1, &["code", "--new-window", "../app/src"], Some(&docs));
+    assert_eq!(process_repo(&opened).unwrap().key(), "app:main");
+
+    let started_in = process(2, &["code", "--new-window"], Some(&docs));
+    assert_eq!(process_repo(&started_in).unwrap().key(), "docs:draft");
+
+    assert_eq!(process_repo(&process(3, &["code"], Some(&dir))), None);
+    assert_eq!(process_repo(&process(4, &["code"], None)), None);
+}
+
+#[test]
+fn rules_relabel_the_branch_and_the_first_match_wins() {
+    let rules = parse_git_task_rules(&[
+        r"^[^:]+:feature/([A-Z]+-[0-9]+).*=$1".to_string(),
+        "^dotfiles:=".to_string(),
+        "^(?P<repo>[^:]+):.*=${repo}".to_string(),
+    ])
+    .unwrap();
+    let config = GitTaskConfig::new(rules);
+    let dir = temp_dir("rules");
+
+    let label = |name: &str, branch: &str| {
+        let root = checkout(&dir, name, &format!("ref: refs/heads/{}\n", branch));
+        config.label(&find_repo(&root).unwrap())
+    };
+    assert_eq!(
+        label("app", "feature/PROJ-42-login").as_deref(),
+        Some("PROJ-42")
+    );
+    assert_eq!(label("dotfiles", "main"), None);
+    assert_eq!(label("site", "main").as_deref(), Some("site"));
+    assert_eq!(
+        GitTaskConfig::default()
+            .label(&find_repo(&dir.join("site")).unwrap())
+            .as_deref(),
+        Some("site:main")
+    );
+
+    assert!("no separator".parse::<GitTaskRule>().is_err());
+    let error = "(unclosed=label".parse::<GitTaskRule>().unwrap_err();
+    assert!(error.contains("Invalid regex"));
+}
+
+#[test]
+fn only_monitored_processes_are_resolved_lowest_pid_first() {
+    let dir = temp_dir("resolve");
+    let app = checkout(&dir, "app", "ref: refs/heads/main\n");
+    let docs = checkout(&dir, "docs", "ref: refs/heads/draft\n");
+    let rules: Vec<AppRule> = vec!["code".parse().unwrap()];
+    let shell = ProcessInfo {
+        cwd: Some(app.to_string_lossy().to_string()),
+        ..ProcessInfo::new(1, "bash")
+    };
+    let processes = [
+        process(20, &["code"], Some(&app)),
+        shell,
+        process(10, &["code"], Some(&docs)),
+    ];
+
+    let config = GitTaskConfig::default();
+    assert_eq!(
+        config.resolve(&processes, &rules, None),
+        Some(Some("docs:draft".to_string()))
+    );
+    assert_eq!(config.resolve(&processes[1..2], &rules, None), None);
+}
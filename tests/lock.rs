@@ -0,0 +1,51 @@
+use chrono::Local;
+use rest_reminder::core::lock::{LockError, MonitorLock, lock_path};
+use std::path::PathBuf;
+
+fn temp_log_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_lock_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    dir.join("focus_log.txt")
+}
+
+#[test]
+fn a_second_monitor_cannot_lock_the_same_log() {
+    let log_path = temp_log_path("second");
+    let lock = MonitorLock::acquire(&log_path).unwrap();
+    assert_eq!(lock.path(), lock_path(&log_path));
+    assert_eq!(
+        std::fs::read_to_string(lock.path()).unwrap().trim(),
+        std::process::id().to_string()
+    );
+
+    // The directory form of `--log-to` names the same file
+    match MonitorLock::acquire(log_path.parent().unwrap()) {
+        Err(LockError::Held { log, pid }) => {
+            assert_eq!(log, log_path);
+            assert_eq!(pid, Some(std::process::id()));
+        }
+        other => panic!("expected the log to be locked, got {:?}", other),
+    }
+
+    // Released locks are free again, the file stays but no longer names a monitor
+    drop(lock);
+    assert_eq!(std::fs::read_to_string(lock_path(&log_path)).unwrap(), "");
+    assert!(MonitorLock::acquire(&log_path).is_ok());
+}
+
+#[test]
+fn a_lock_file_left_by_a_dead_monitor_is_taken_over() {
+    let log_path = temp_log_path("stale");
+    std::fs::write(lock_path(&log_path), "4294967\n").unwrap();
+
+    let lock = MonitorLock::acquire(&log_path).unwrap();
+    assert_eq!(
+        std::fs::read_to_string(lock.path()).unwrap().trim(),
+        std::process::id().to_string()
+    );
+}
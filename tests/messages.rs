@@ -0,0 +1,141 @@
+use chrono::Local;
+use rest_reminder::core::messages::{
+    Catalog, MessageContext, Selection, Vars, catalog, normalize_locale, set_catalog,
+};
+use rest_reminder::core::notify::reminder_message;
+use std::path::PathBuf;
+
+fn catalog_file(name: &str, content: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_messages_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    let path = dir.join("messages.json");
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+#[test]
+fn locales_fall_back_to_their_language_and_then_english() {
+    assert_eq!(normalize_locale("zh_CN.UTF-8"), "zh_CN");
+    assert_eq!(normalize_locale("zh-CN"), "zh_CN");
+    assert_eq!(normalize_locale("de_DE@euro"), "de_DE");
+
+    let chinese = Catalog::built_in("zh_CN");
+    assert_eq!(chinese.locale(), "zh_CN");
+    assert_eq!(chinese.text("title.reminder"), "该休息了");
+    assert_eq!(Catalog::built_in("zh_TW").locale(), "zh_CN");
+    assert_eq!(Catalog::built_in("zh").locale(), "zh_CN");
+
+    let french = Catalog::built_in("fr_FR");
+    assert_eq!(french.locale(), "en");
+    assert_eq!(french.text("title.reminder"), "Time to take a break");
+    assert_eq!(french.text("no.such.key"), "no.such.key");
+}
+
+#[test]
+fn durations_are_spelled_out_in_the_catalog_language() {
+    let english = Catalog::english();
+    assert_eq!(english.duration(1), "1 second");
+    assert_eq!(english.duration(45), "45 seconds");
+    assert_eq!(english.duration(60), "1 minute");
+    assert_eq!(english.duration(25 * 60 + 59), "25 minutes");
+    assert_eq!(english.duration(7200), "2 hours");
+    assert_eq!(english.duration(3600 + 5 * 60), "1 hour 5 minutes");
+
+    let chinese = Catalog::built_in("zh_CN");
+    assert_eq!(chinese.duration(3600 + 5 * 60), "1小时5分钟");
+    assert_eq!(chinese.duration(30), "30秒");
+}
+
+#[test]
+fn templates_fill_known_placeholders_and_keep_the_rest() {
+    let path = catalog_file(
+        "template",
+        r#"{"strings": {"greeting": "{name} worked {duration} on {task}, {name}!"}}"#,
+    );
+    let catalog = Catalog::english().with_file(&path).unwrap();
+    let vars = Vars::new().set("name", "Ada").set("task", "{name}");
+
+    assert_eq!(
+        catalog.render("greeting", &vars),
+        "Ada worked {duration} on {name}, Ada!"
+    );
+}
+
+#[test]
+fn user_catalog_overrides_only_what_it_contains() {
+    let path = catalog_file(
+        "override",
+        r#"{
+            "selection": "rotate",
+            "pools": {"reminder": ["first {duration}", {"text": "second", "weight": 5}], "budget": []},
+            "strings": {"title.reminder": "Pause!"}
+        }"#,
+    );
+    let catalog = Catalog::built_in("zh_CN").with_file(&path).unwrap();
+
+    assert_eq!(catalog.selection(), Selection::Rotate);
+    assert_eq!(catalog.text("title.reminder"), "Pause!");
+    assert_eq!(catalog.text("title.budget"), "已达{period}预算的{percent}%");
+    let vars = Vars::new().set("duration", catalog.duration(3600));
+    let picked: Vec<String> = (0..3).map(|_| catalog.pick("reminder", &vars)).collect();
+    assert_eq!(picked, ["first 1小时", "second", "first 1小时"]);
+    // An empty pool keeps the built-in one
+    assert!(catalog.pick("budget", &vars).contains("该收工了"));
+}
+
+#[test]
+fn weights_steer_random_selection() {
+    let path = catalog_file(
+        "weights",
+        r#"{"pools": {"reminder": [{"text": "never", "weight": 0}, {"text": "often", "weight": 3}, "rarely"]}}"#,
+    );
+    let catalog = Catalog::english().with_file(&path).unwrap();
+
+    let picked: Vec<String> = (0..400)
+        .map(|_| catalog.pick("reminder", &Vars::new()))
+        .collect();
+    let often = picked.iter().filter(|text| *text == "often").count();
+    assert!(!picked.iter().any(|text| text == "never"));
+    assert!(picked.iter().any(|text| text == "rarely"));
+    assert!(often > 200, "picked `often` {often} times out of 400");
+}
+
+#[test]
+fn malformed_catalogs_are_rejected() {
+    let path = catalog_file("malformed", r#"{"pools": {"reminder": "not a list"}}"#);
+    let error = Catalog::english().with_file(&path).unwrap_err();
+    assert!(error.contains(&path.display().to_string()));
+
+    let path = catalog_file("unknown", r#"{"string": {}}"#);
+    assert!(Catalog::english().with_file(&path).is_err());
+}
+
+#[test]
+fn reminders_are_rendered_from_the_current_catalog() {
+    let path = catalog_file(
+        "reminder",
+        r#"{"pools": {"reminder": ["{duration} on {task} ({apps}), {today} today, {breaks} break(s)"]}}"#,
+    );
+    set_catalog(Catalog::english().with_file(&path).unwrap());
+    let context = MessageContext {
+        task: Some("report".to_string()),
+        apps: vec!["Code".to_string(), "Cursor".to_string()],
+        worked_today: 4 * 3600 + 30 * 60,
+        breaks_today: 2,
+    };
+
+    let message = reminder_message(3600, &context);
+    set_catalog(Catalog::english());
+
+    assert_eq!(message.title, "Time to take a break");
+    assert_eq!(
+        message.body,
+        "1 hour on report (Code, Cursor), 4 hours 30 minutes today, 2 break(s)"
+    );
+    assert_eq!(catalog().locale(), "en");
+}
@@ -0,0 +1,1447 @@
+use chrono::{DateTime, Duration as ChronoDuration, Local, NaiveDateTime, TimeZone};
+use rest_reminder::core::budget::{BudgetAlert, BudgetConfig};
+use rest_reminder::core::checkpoint::{checkpoint_path, last_seen_path, read_checkpoint};
+use rest_reminder::core::clock::VirtualClock;
+use rest_reminder::core::core::{
+    MonitorConfig, MonitorControl, MonitorDeps, MonitorStatus, run_rest_reminder_with,
+};
+use rest_reminder::core::engine::{MonitorState, PomodoroConfig, PomodoroPhase};
+use rest_reminder::core::git_task::GitTaskConfig;
+use rest_reminder::core::hooks::{MonitorHooks, ReminderAction};
+use rest_reminder::core::idle::NoIdleSource;
+use rest_reminder::core::messages::MessageContext;
+use rest_reminder::core::notify::NotifyConfig;
+use rest_reminder::core::poll::PollIntervals;
+use rest_reminder::core::probe::{ProcessInfo, ProcessProbe};
+use rest_reminder::core::schedule::Schedule;
+use rest_reminder::core::signals::{SignalRequest, StopSignal};
+use rest_reminder::core::snooze::reads_snooze_keys;
+use rest_reminder::core::task_rules::TaskRules;
+use rest_reminder::plugin::plugin::PluginContext;
+use rest_reminder::statistic::log_entry::LogEntry;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch};
+use tokio::time::{Duration, Instant};
+
+// Reports `name` as running between `from` and `until` seconds after creation
+struct ScriptedProbe {
+    started_at: Instant,
+    windows: Vec<(&'static str, u64, u64)>,
+    cwd: Option<String>,
+    busy: Option<(u64, u64)>,
+}
+
+impl ScriptedProbe {
+    fn new(windows: Vec<(&'static str, u64, u64)>) -> Self {
+        ScriptedProbe {
+            started_at: Instant::now(),
+            windows,
+            cwd: None,
+            busy: None,
+        }
+    }
+
+    // Every process keeps a core busy between `from` and `until` seconds
+    fn busy(mut self, from: u64, until: u64) -> Self {
+        self.busy = Some((from, until));
+        self
+    }
+
+    // Every process runs in `dir`
+    fn in_dir(mut self, dir: &Path) -> Self {
+        self.cwd = Some(dir.to_string_lossy().to_string());
+        self
+    }
+}
+
+impl ProcessProbe for ScriptedProbe {
+    fn processes(&mut self) -> Vec<ProcessInfo> {
+        let elapsed = self.started_at.elapsed().as_secs();
+        let cpu_time = self.busy.map_or(0, |(from, until)| {
+            let millis = self.started_at.elapsed().as_millis() as u64;
+            millis.clamp(from * 1000, until * 1000) - from * 1000
+        });
+        self.windows
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, from, until))| (*from..*until).contains(&elapsed))
+            .map(|(pid, (name, _, _))| ProcessInfo {
+                cwd: self.cwd.clone(),
+                cpu_time,
+                ..ProcessInfo::new(pid as u32, *name)
+            })
+            .collect()
+    }
+}
+
+#[derive(Clone, Default)]
+struct RecordingHooks {
+    calls: Arc<Mutex<Vec<String>>>,
+    actions: Arc<Mutex<Option<mpsc::UnboundedReceiver<ReminderAction>>>>,
+    reminder_contexts: Arc<Mutex<Vec<MessageContext>>>,
+}
+
+impl RecordingHooks {
+    fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn reminder_contexts(&self) -> Vec<MessageContext> {
+        self.reminder_contexts.lock().unwrap().clone()
+    }
+
+    // Buttons clicked on the reminders, as the notification server reports them
+    fn clicks(&self) -> mpsc::UnboundedSender<ReminderAction> {
+        let (action_tx, action_rx) = mpsc::unbounded_channel();
+        *self.actions.lock().unwrap() = Some(action_rx);
+        action_tx
+    }
+}
+
+impl MonitorHooks for RecordingHooks {
+    fn trigger(&mut self, hook_name: &str, context: &PluginContext) {
+        self.calls.lock().unwrap().push(format!(
+            "{} {} {}",
+            hook_name, context.timestamp, context.work_duration
+        ));
+    }
+
+    fn remind(&mut self, time: u64, context: &MessageContext) {
+        self.calls.lock().unwrap().push(format!("remind {time}"));
+        self.reminder_contexts.lock().unwrap().push(context.clone());
+    }
+
+    fn escalate(&mut self, overdue: u64, level: u32, _context: &MessageContext) {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("escalate {overdue} {level}"));
+    }
+
+    fn budget_reached(&mut self, alert: &BudgetAlert, _context: &MessageContext) {
+        self.calls.lock().unwrap().push(format!(
+            "budget {} {}",
+            alert.period.name(),
+            alert.percent
+        ));
+    }
+
+    fn after_hours(&mut self, _context: &MessageContext) {
+        self.calls.lock().unwrap().push("after_hours".to_string());
+    }
+
+    fn reminder_actions(&mut self) -> Option<mpsc::UnboundedReceiver<ReminderAction>> {
+        self.actions.lock().unwrap().take()
+    }
+}
+
+fn local_dt(s: &str) -> DateTime<Local> {
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
+        .expect("Format should be %Y-%m-%d %H:%M:%S");
+    Local
+        .from_local_datetime(&naive)
+        .single()
+        .expect("Should be a valid date")
+}
+
+fn temp_log_path(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_monitor_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    dir.join("focus_log.txt")
+}
+
+fn read_log_lines(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+// A session Code ran through from start to end
+fn expected_line(start: DateTime<Local>, end: DateTime<Local>, task: &str) -> String {
+    expected_entry(start, end, task).to_json_line().unwrap()
+}
+
+fn expected_entry(start: DateTime<Local>, end: DateTime<Local>, task: &str) -> LogEntry {
+    let seconds = (end - start).num_seconds() as u64;
+    LogEntry::new(start, end, vec!["Code".to_string()], Some(task.to_string()))
+        .with_app_seconds(BTreeMap::from([("Code".to_string(), seconds)]))
+}
+
+fn config(log_path: &Path, task: Option<String>) -> MonitorConfig {
+    MonitorConfig {
+        log_location: log_path.to_path_buf(),
+        time: 3600,
+        apps: vec!["Code".parse().unwrap()],
+        task,
+        git_task: None,
+        task_rules: None,
+        idle_threshold: None,
+        snooze: 600,
+        max_snoozes: None,
+        min_break: None,
+        pomodoro: None,
+        remind_every: None,
+        budget: None,
+        start_debounce: None,
+        end_grace: None,
+        schedule: None,
+        notify: NotifyConfig::default(),
+        // The scripted timelines are written against a check every second
+        poll: PollIntervals {
+            resting: 1,
+            working: 2,
+        },
+    }
+}
+
+fn deps(probe: ScriptedProbe, origin: DateTime<Local>, hooks: RecordingHooks) -> MonitorDeps {
+    deps_with_clock(probe, Arc::new(VirtualClock::starting_at(origin)), hooks)
+}
+
+// The test keeps the clock to move it past tokio's time
+fn deps_with_clock(
+    probe: ScriptedProbe,
+    clock: Arc<VirtualClock>,
+    hooks: RecordingHooks,
+) -> MonitorDeps {
+    MonitorDeps {
+        probe: Box::new(probe),
+        clock,
+        hooks: Box::new(hooks),
+        idle_source: Box::new(NoIdleSource),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn process_running_for_thirty_minutes_logs_one_session() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("thirty_minutes");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 30 * 60)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(31 * 60)).await;
+    monitor.abort();
+
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![expected_line(
+            origin,
+            origin + ChronoDuration::minutes(30),
+            "coding"
+        )]
+    );
+    assert!(!checkpoint_path(&log_path).exists());
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn reaching_the_threshold_reminds_and_starts_a_new_session() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("threshold");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 90 * 60)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(2 * 3600)).await;
+    monitor.abort();
+
+    let reminded_at = origin + ChronoDuration::hours(1);
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, reminded_at, "coding"),
+            expected_line(reminded_at, origin + ChronoDuration::minutes(90), "coding"),
+        ]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+            "on_break_reminder 2025-05-01 10:00:00 3600",
+            "remind 3600",
+            "on_work_start 2025-05-01 10:00:00 0",
+            "on_break_start 2025-05-01 10:30:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn slow_polling_still_reminds_on_time() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("slow_polling");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 5, 3600)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            time: 1000,
+            poll: PollIntervals {
+                resting: 10,
+                working: 300,
+            },
+            ..config(&log_path, None)
+        },
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(1100)).await;
+    monitor.abort();
+
+    // The app is found at the next idle scan, the reminder comes between two
+    // working checks, 1000 seconds later
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:10 0",
+            "on_break_reminder 2025-05-01 09:16:50 1000",
+            "remind 1000",
+            "on_work_start 2025-05-01 09:16:50 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn reminder_messages_know_the_task_and_the_day_so_far() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("message_context");
+    let earlier = [
+        expected_line(
+            origin - ChronoDuration::hours(2),
+            origin - ChronoDuration::hours(1),
+            "coding",
+        ),
+        LogEntry::new_break(
+            origin - ChronoDuration::hours(1),
+            origin - ChronoDuration::minutes(30),
+            vec!["Code".to_string()],
+            None,
+            false,
+        )
+        .to_json_line()
+        .unwrap(),
+    ];
+    std::fs::write(&log_path, earlier.join("\n") + "\n").unwrap();
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 90 * 60)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(61 * 60)).await;
+    monitor.abort();
+
+    assert_eq!(
+        hooks.reminder_contexts(),
+        vec![MessageContext {
+            task: Some("coding".to_string()),
+            apps: vec!["Code".to_string()],
+            worked_today: 2 * 3600,
+            breaks_today: 1,
+        }]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_app_reaching_its_own_threshold_reminds_early() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("app_threshold");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 60 * 60), ("Game", 20 * 60, 35 * 60)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            apps: vec!["Code".parse().unwrap(), "Game@600".parse().unwrap()],
+            ..config(&log_path, None)
+        },
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(61 * 60)).await;
+    monitor.abort();
+
+    // Ten minutes of the game use up its threshold long before the hour is over
+    let at = |minutes| origin + ChronoDuration::minutes(minutes);
+    let entry = |start, end, code, game| {
+        LogEntry::new(
+            start,
+            end,
+            vec!["Code".to_string(), "Game".to_string()],
+            None,
+        )
+        .with_app_seconds(BTreeMap::from([
+            ("Code".to_string(), code),
+            ("Game".to_string(), game),
+        ]))
+        .to_json_line()
+        .unwrap()
+    };
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            entry(at(0), at(30), 1800, 600),
+            entry(at(30), at(60), 1800, 300),
+        ]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+            "on_break_reminder 2025-05-01 09:30:00 600",
+            "remind 600",
+            "on_work_start 2025-05-01 09:30:00 0",
+            "on_break_start 2025-05-01 10:00:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn switching_branches_splits_the_session_but_not_the_work_towards_a_break() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("git_task");
+    let project = log_path.parent().unwrap().join("project");
+    std::fs::create_dir_all(project.join(".git")).unwrap();
+    std::fs::write(project.join(".git/HEAD"), "ref: refs/heads/main\n").unwrap();
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 50 * 60)]).in_dir(&project);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            time: 40 * 60,
+            git_task: Some(GitTaskConfig::default()),
+            ..config(&log_path, Some("fallback".to_string()))
+        },
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(20 * 60 + 1)).await;
+    std::fs::write(project.join(".git/HEAD"), "ref: refs/heads/feature/login\n").unwrap();
+    tokio::time::sleep(Duration::from_secs(31 * 60)).await;
+    monitor.abort();
+
+    // The next check after the checkout sees the new branch
+    let switched_at = origin + ChronoDuration::seconds(20 * 60 + 2);
+    let reminded_at = origin + ChronoDuration::minutes(40);
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, switched_at, "project:main"),
+            expected_line(switched_at, reminded_at, "project:feature/login"),
+            expected_line(
+                reminded_at,
+                origin + ChronoDuration::minutes(50),
+                "project:feature/login"
+            ),
+        ]
+    );
+    assert!(hooks.calls().contains(&"remind 2400".to_string()));
+    assert_eq!(
+        hooks.reminder_contexts()[0].task.as_deref(),
+        Some("project:feature/login")
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn task_rules_label_sessions_by_app_and_time_of_day() {
+    // A Thursday
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("task_rules");
+    let rules_path = log_path.with_extension("rules.json");
+    std::fs::write(
+        &rules_path,
+        r#"{"rules": [
+            {"name": "calls", "task": "meetings", "app": "zoom"},
+            {"name": "mornings", "task": "deep-work", "app": "Code", "weekdays": ["thu"], "hours": "09:00-09:45"}
+        ]}"#,
+    )
+    .unwrap();
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 55 * 60), ("zoom", 20 * 60, 30 * 60)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            task_rules: Some(TaskRules::load(&rules_path).unwrap()),
+            ..config(&log_path, Some("coding".to_string()))
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(25 * 60)).await;
+    assert_eq!(status_rx.borrow().task.as_deref(), Some("meetings"));
+    assert_eq!(status_rx.borrow().task_rule.as_deref(), Some("calls"));
+
+    tokio::time::sleep(Duration::from_secs(25 * 60)).await;
+    assert_eq!(status_rx.borrow().task.as_deref(), Some("coding"));
+    assert_eq!(status_rx.borrow().task_rule, None);
+
+    tokio::time::sleep(Duration::from_secs(10 * 60)).await;
+    monitor.abort();
+
+    let minute = |minutes| origin + ChronoDuration::minutes(minutes);
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, minute(20), "deep-work"),
+            expected_line(minute(20), minute(30), "meetings"),
+            expected_line(minute(30), minute(45), "deep-work"),
+            expected_line(minute(45), minute(55), "coding"),
+        ]
+    );
+}
+
+// A schedule file next to the log
+fn schedule(log_path: &Path, json: &str) -> Schedule {
+    let path = log_path.with_extension("schedule.json");
+    std::fs::write(&path, json).unwrap();
+    Schedule::load(&path).unwrap()
+}
+
+#[tokio::test(start_paused = true)]
+async fn working_after_hours_is_logged_as_off_schedule_and_nudged() {
+    // A Thursday
+    let origin = local_dt("2025-05-01 16:30:00");
+    let log_path = temp_log_path("after_hours");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 50 * 60)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            schedule: Some(schedule(
+                &log_path,
+                r#"{
+                    "working_hours": {"thu": ["09:00-17:00"]},
+                    "after_hours": "nudge",
+                    "after_hours_threshold": 2700
+                }"#,
+            )),
+            ..config(&log_path, Some("coding".to_string()))
+        },
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(3600)).await;
+    monitor.abort();
+
+    // The work before 17:00 counts towards the shorter after-hours threshold
+    let minute = |minutes| origin + ChronoDuration::minutes(minutes);
+    let off_schedule = |start, end| {
+        expected_entry(start, end, "coding")
+            .with_off_schedule(true)
+            .to_json_line()
+            .unwrap()
+    };
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, minute(30), "coding"),
+            off_schedule(minute(30), minute(45)),
+            off_schedule(minute(45), minute(50)),
+        ]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 16:30:00 0",
+            "on_work_start 2025-05-01 16:30:00 0",
+            "on_after_hours 2025-05-01 17:00:00 0",
+            "after_hours",
+            "on_break_reminder 2025-05-01 17:15:00 2700",
+            "remind 2700",
+            "on_work_start 2025-05-01 17:15:00 0",
+            "on_after_hours 2025-05-01 17:15:00 0",
+            "after_hours",
+            "on_break_start 2025-05-01 17:20:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn reminders_wait_for_the_end_of_quiet_hours() {
+    let origin = local_dt("2025-05-01 11:30:00");
+    let log_path = temp_log_path("quiet_hours");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 100 * 60)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            schedule: Some(schedule(&log_path, r#"{"quiet_hours": ["12:00-13:00"]}"#)),
+            ..config(&log_path, None)
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(60 * 60)).await;
+    assert!(status_rx.borrow().quiet);
+    assert!(!status_rx.borrow().off_schedule);
+    assert!(!status_rx.borrow().reminder_pending);
+
+    tokio::time::sleep(Duration::from_secs(50 * 60)).await;
+    monitor.abort();
+
+    let reminded_at = local_dt("2025-05-01 13:00:00");
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, reminded_at, ""),
+            expected_line(reminded_at, origin + ChronoDuration::minutes(100), ""),
+        ]
+    );
+    assert!(
+        hooks
+            .calls()
+            .contains(&"on_break_reminder 2025-05-01 13:00:00 3600".to_string())
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn no_session_is_logged_while_the_process_is_absent() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("absent");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Other", 0, 3600)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, None),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(3600)).await;
+    monitor.abort();
+
+    assert!(read_log_lines(&log_path).is_empty());
+    assert_eq!(hooks.calls(), vec!["on_init 2025-05-01 09:00:00 0"]);
+}
+
+#[tokio::test(start_paused = true)]
+async fn pausing_closes_the_session_and_resuming_starts_a_new_one() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("pause");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3 * 3600)]);
+    let (pause_tx, pause_rx) = watch::channel(false);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, None),
+        MonitorControl {
+            pause_rx: Some(pause_rx),
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(10 * 60)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Working);
+
+    pause_tx.send(true).unwrap();
+    tokio::time::sleep(Duration::from_secs(5 * 60)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Paused);
+
+    pause_tx.send(false).unwrap();
+    tokio::time::sleep(Duration::from_secs(5 * 60)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Working);
+    assert_eq!(
+        status_rx.borrow().session_started_at,
+        Some(origin + ChronoDuration::minutes(15))
+    );
+    monitor.abort();
+
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![expected_line(
+            origin,
+            origin + ChronoDuration::minutes(10),
+            ""
+        )]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn snooze_keys_are_only_read_while_the_reminder_is_up() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("snooze_keys");
+    let probe = ScriptedProbe::new(vec![("Code", 0, 80 * 60)]);
+    let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, RecordingHooks::default()),
+        config(&log_path, None),
+        MonitorControl {
+            snooze_rx: Some(snooze_rx),
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    tokio::time::sleep(Duration::from_secs(30 * 60)).await;
+    assert!(!reads_snooze_keys(&status_rx.borrow()));
+
+    // Working on past the reminder
+    tokio::time::sleep(Duration::from_secs(30 * 60 + 30)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Working);
+    assert!(reads_snooze_keys(&status_rx.borrow()));
+
+    // Still pending, but the terminal is left alone until the reminder is back
+    snooze_tx.send(Duration::from_secs(300)).unwrap();
+    tokio::time::sleep(Duration::from_secs(30)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Snoozed);
+    assert!(status_rx.borrow().reminder_pending);
+    assert!(!reads_snooze_keys(&status_rx.borrow()));
+
+    tokio::time::sleep(Duration::from_secs(300)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Working);
+    assert!(status_rx.borrow().reminder_pending);
+    assert!(reads_snooze_keys(&status_rx.borrow()));
+
+    tokio::time::sleep(Duration::from_secs(20 * 60)).await;
+    assert!(!status_rx.borrow().reminder_pending);
+    assert!(!reads_snooze_keys(&status_rx.borrow()));
+    monitor.abort();
+}
+
+#[tokio::test(start_paused = true)]
+async fn snoozing_postpones_the_reminder_and_is_logged() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("snooze");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 80 * 60)]);
+    let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, None),
+        MonitorControl {
+            snooze_rx: Some(snooze_rx),
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    // Nothing to snooze before the reminder
+    snooze_tx.send(Duration::from_secs(600)).unwrap();
+    tokio::time::sleep(Duration::from_secs(3600 + 30)).await;
+    assert!(status_rx.borrow().reminder_pending);
+    assert_eq!(status_rx.borrow().snoozes, 0);
+
+    snooze_tx.send(Duration::from_secs(600)).unwrap();
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Snoozed);
+    assert_eq!(status_rx.borrow().snoozes, 1);
+    assert_eq!(
+        status_rx.borrow().snoozed_until,
+        Some(origin + ChronoDuration::seconds(3600 + 30 + 600))
+    );
+
+    tokio::time::sleep(Duration::from_secs(20 * 60)).await;
+    monitor.abort();
+
+    let reminded_at = origin + ChronoDuration::hours(1);
+    let snooze_ended_at = origin + ChronoDuration::seconds(3600 + 30 + 600);
+    let snoozed = expected_entry(reminded_at, snooze_ended_at, "")
+        .with_snoozes(1)
+        .to_json_line()
+        .unwrap();
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, reminded_at, ""),
+            snoozed,
+            expected_line(snooze_ended_at, origin + ChronoDuration::minutes(80), ""),
+        ]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+            "on_break_reminder 2025-05-01 10:00:00 3600",
+            "remind 3600",
+            "on_work_start 2025-05-01 10:00:00 0",
+            "on_break_snoozed 2025-05-01 10:00:30 3600",
+            "on_break_reminder 2025-05-01 10:10:30 3600",
+            "remind 3600",
+            "on_work_start 2025-05-01 10:10:30 0",
+            "on_break_start 2025-05-01 10:20:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn snoozing_past_the_limit_escalates_the_reminder() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("snooze_limit");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3 * 3600)]);
+    let (snooze_tx, snooze_rx) = mpsc::unbounded_channel();
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            max_snoozes: Some(1),
+            ..config(&log_path, None)
+        },
+        MonitorControl {
+            snooze_rx: Some(snooze_rx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    tokio::time::sleep(Duration::from_secs(3600 + 30)).await;
+    snooze_tx.send(Duration::from_secs(300)).unwrap();
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    snooze_tx.send(Duration::from_secs(300)).unwrap();
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    monitor.abort();
+
+    let calls = hooks.calls();
+    assert_eq!(
+        calls[4..],
+        [
+            "on_work_start 2025-05-01 10:00:00 0",
+            "on_break_snoozed 2025-05-01 10:00:30 3600",
+            "on_break_reminder 2025-05-01 10:01:30 3600",
+            "on_snooze_limit 2025-05-01 10:01:30 3600",
+            "remind 3600",
+            "on_work_start 2025-05-01 10:01:30 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn coming_back_before_the_minimum_break_is_a_violation() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("short_break");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3660), ("Code", 3720, 4000)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            min_break: Some(300),
+            ..config(&log_path, None)
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(3690)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::OnBreak);
+    assert_eq!(
+        status_rx.borrow().break_started_at,
+        Some(origin + ChronoDuration::seconds(3660))
+    );
+
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    monitor.abort();
+
+    let reminded_at = origin + ChronoDuration::hours(1);
+    let left_at = origin + ChronoDuration::seconds(3660);
+    let back_at = origin + ChronoDuration::seconds(3720);
+    let short_break = LogEntry::new_break(left_at, back_at, vec!["Code".to_string()], None, true)
+        .to_json_line()
+        .unwrap();
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, reminded_at, ""),
+            expected_line(reminded_at, left_at, ""),
+            short_break,
+        ]
+    );
+    assert_eq!(
+        hooks.calls()[5..],
+        [
+            "on_break_start 2025-05-01 10:01:00 0",
+            "on_break_end 2025-05-01 10:02:00 60",
+            "on_work_start 2025-05-01 10:02:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn take_break_button_starts_the_break_while_the_process_keeps_running() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("take_break");
+    let hooks = RecordingHooks::default();
+    let clicks = hooks.clicks();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 90 * 60)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            min_break: Some(600),
+            ..config(&log_path, None)
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    // Nothing to take a break from before the reminder
+    clicks.send(ReminderAction::TakeBreak).unwrap();
+    tokio::time::sleep(Duration::from_secs(3600 + 30)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Working);
+    assert!(status_rx.borrow().reminder_pending);
+
+    clicks.send(ReminderAction::TakeBreak).unwrap();
+    tokio::time::sleep(Duration::from_secs(5 * 60)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::OnBreak);
+
+    tokio::time::sleep(Duration::from_secs(25 * 60)).await;
+    monitor.abort();
+
+    let reminded_at = origin + ChronoDuration::hours(1);
+    let clicked_at = origin + ChronoDuration::seconds(3600 + 30);
+    let back_at = clicked_at + ChronoDuration::minutes(10);
+    let taken_break =
+        LogEntry::new_break(clicked_at, back_at, vec!["Code".to_string()], None, false)
+            .to_json_line()
+            .unwrap();
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, reminded_at, ""),
+            expected_line(reminded_at, clicked_at, ""),
+            taken_break,
+            expected_line(back_at, origin + ChronoDuration::minutes(90), ""),
+        ]
+    );
+    assert_eq!(
+        hooks.calls()[4..],
+        [
+            "on_work_start 2025-05-01 10:00:00 0",
+            "on_break_start 2025-05-01 10:00:30 0",
+            "on_break_end 2025-05-01 10:10:30 600",
+            "on_work_start 2025-05-01 10:10:30 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn pomodoro_cycles_through_short_and_long_breaks() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("pomodoro");
+    let hooks = RecordingHooks::default();
+    // Works straight through every break
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3 * 3600)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            time: 1500,
+            pomodoro: Some(PomodoroConfig {
+                short_break: 300,
+                long_break: 900,
+                cycles: 2,
+            }),
+            ..config(&log_path, None)
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    // Status is published on each check, so look right after one
+    tokio::time::sleep(Duration::from_millis(600_500)).await;
+    let status = status_rx.borrow().pomodoro.unwrap();
+    assert_eq!(status.phase, PomodoroPhase::Work);
+    assert_eq!(status.cycle, 1);
+    assert_eq!(status.remaining_seconds, 900);
+
+    tokio::time::sleep(Duration::from_secs(1000)).await;
+    let status = status_rx.borrow().pomodoro.unwrap();
+    assert_eq!(status.phase, PomodoroPhase::ShortBreak);
+    assert_eq!(status.cycle, 1);
+    assert_eq!(status.remaining_seconds, 200);
+
+    // The second pomodoro runs from 09:30 to 09:55, then the long break runs to 10:10
+    tokio::time::sleep(Duration::from_secs(2000)).await;
+    let status = status_rx.borrow().pomodoro.unwrap();
+    assert_eq!(status.phase, PomodoroPhase::LongBreak);
+    assert_eq!(status.cycle, 2);
+    assert_eq!(status.remaining_seconds, 600);
+    monitor.abort();
+
+    let at = |minutes| origin + ChronoDuration::minutes(minutes);
+    let entry = |start, end, cycle| {
+        expected_entry(start, end, "")
+            .with_cycle(cycle)
+            .to_json_line()
+            .unwrap()
+    };
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            entry(at(0), at(25), Some(1)),
+            entry(at(25), at(30), None),
+            entry(at(30), at(55), Some(2)),
+        ]
+    );
+    assert!(
+        hooks
+            .calls()
+            .contains(&"on_break_over 2025-05-01 09:30:00 0".to_string())
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn ignored_reminder_is_repeated_and_the_session_is_not_split() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("escalation");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 85 * 60)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            remind_every: Some(600),
+            ..config(&log_path, Some("coding".to_string()))
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    tokio::time::sleep(Duration::from_millis(75 * 60 * 1000 + 500)).await;
+    let status = status_rx.borrow().clone();
+    assert_eq!(status.state, MonitorState::BreakDue);
+    assert_eq!(status.session_started_at, Some(origin));
+    assert_eq!(status.escalations, 1);
+    assert_eq!(status.overdue_seconds, 15 * 60);
+
+    tokio::time::sleep(Duration::from_secs(15 * 60)).await;
+    monitor.abort();
+
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![expected_line(
+            origin,
+            origin + ChronoDuration::minutes(85),
+            "coding"
+        )]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+            "on_break_reminder 2025-05-01 10:00:00 3600",
+            "remind 3600",
+            "on_reminder_escalated 2025-05-01 10:10:00 600",
+            "escalate 600 1",
+            "on_reminder_escalated 2025-05-01 10:20:00 1200",
+            "escalate 1200 2",
+            "on_break_start 2025-05-01 10:25:00 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn daily_budget_counts_logged_work_and_the_open_session() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("budget");
+    // Seven hours were logged earlier today, one the day before
+    let earlier = [
+        expected_line(
+            local_dt("2025-04-30 20:00:00"),
+            local_dt("2025-04-30 21:00:00"),
+            "coding",
+        ),
+        expected_line(
+            local_dt("2025-05-01 01:00:00"),
+            local_dt("2025-05-01 08:00:00"),
+            "coding",
+        ),
+    ];
+    std::fs::write(&log_path, format!("{}\n", earlier.join("\n"))).unwrap();
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3 * 3600)]);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            time: 4 * 3600,
+            budget: BudgetConfig::new(Some(8 * 3600), None, vec![80, 100]),
+            ..config(&log_path, Some("coding".to_string()))
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+
+    tokio::time::sleep(Duration::from_millis(30 * 60 * 1000 + 500)).await;
+    let budget = status_rx.borrow().budget.unwrap();
+    assert_eq!(budget.worked_today, 7 * 3600 + 30 * 60);
+    assert_eq!(budget.worked_this_week, 8 * 3600 + 30 * 60);
+
+    tokio::time::sleep(Duration::from_secs(3600)).await;
+    monitor.abort();
+
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+            "on_budget_reached 2025-05-01 09:00:00 25200",
+            "budget daily 80",
+            "on_budget_reached 2025-05-01 10:00:00 28800",
+            "budget daily 100",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn short_gaps_continue_the_session_and_blips_do_not_start_one() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("gaps");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![
+        // Restarted after 10 minutes
+        ("Code", 0, 600),
+        ("Code", 630, 1800),
+        // Opened for a few seconds only
+        ("Code", 2000, 2004),
+        ("Code", 2400, 3000),
+    ]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            start_debounce: Some(10),
+            end_grace: Some(60),
+            ..config(&log_path, None)
+        },
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(3200)).await;
+    monitor.abort();
+
+    let at = |seconds| origin + ChronoDuration::seconds(seconds);
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            // Code was gone for the gap
+            LogEntry::new(at(0), at(1800), vec!["Code".to_string()], None)
+                .with_app_seconds(BTreeMap::from([("Code".to_string(), 1770)]))
+                .with_gap_seconds(30)
+                .to_json_line()
+                .unwrap(),
+            expected_line(at(2400), at(3000), ""),
+        ]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:10 0",
+            "on_work_start 2025-05-01 09:40:10 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn sleeping_through_the_night_ends_the_session_at_the_last_check() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("suspend");
+    let hooks = RecordingHooks::default();
+    let clock = Arc::new(VirtualClock::starting_at(origin));
+    let probe = ScriptedProbe::new(vec![("Code", 0, 40 * 60)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps_with_clock(probe, clock.clone(), hooks.clone()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    // The lid is closed right after the check at 09:20 and opened 8 hours later
+    tokio::time::sleep(Duration::from_secs(20 * 60 + 1)).await;
+    clock.skip(ChronoDuration::hours(8));
+    tokio::time::sleep(Duration::from_secs(25 * 60)).await;
+    monitor.abort();
+
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, local_dt("2025-05-01 09:20:00"), "coding"),
+            expected_line(
+                local_dt("2025-05-01 17:20:02"),
+                local_dt("2025-05-01 17:40:00"),
+                "coding"
+            ),
+        ]
+    );
+    assert_eq!(
+        hooks.calls(),
+        vec![
+            "on_init 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 09:00:00 0",
+            "on_work_start 2025-05-01 17:20:02 0",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn logind_sleep_signal_ends_the_session_before_the_machine_sleeps() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("prepare_for_sleep");
+    let hooks = RecordingHooks::default();
+    let clock = Arc::new(VirtualClock::starting_at(origin));
+    let probe = ScriptedProbe::new(vec![("Code", 0, 40 * 60)]);
+    let (sleep_tx, sleep_rx) = mpsc::unbounded_channel();
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps_with_clock(probe, clock.clone(), hooks.clone()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl {
+            sleep_rx: Some(sleep_rx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(20 * 60 + 1)).await;
+    sleep_tx.send(true).unwrap();
+    // Nothing is checked until the machine wakes up
+    tokio::time::sleep(Duration::from_millis(59_500)).await;
+    clock.skip(ChronoDuration::hours(8));
+    sleep_tx.send(false).unwrap();
+    tokio::time::sleep(Duration::from_secs(25 * 60)).await;
+    monitor.abort();
+
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(origin, local_dt("2025-05-01 09:20:01"), "coding"),
+            expected_line(
+                local_dt("2025-05-01 17:21:01"),
+                // The first check after Code left
+                local_dt("2025-05-01 17:40:01"),
+                "coding"
+            ),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_app_idling_in_the_background_is_not_work() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("activity");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3600)]).busy(600, 1800);
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        MonitorConfig {
+            apps: vec!["Code~cpu=50,window=10".parse().unwrap()],
+            ..config(&log_path, None)
+        },
+        MonitorControl {
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(1000)).await;
+    let activity = status_rx.borrow().activity["Code"];
+    assert_eq!(activity.cpu_percent, 100.0);
+    assert!(activity.active);
+
+    tokio::time::sleep(Duration::from_secs(1000)).await;
+    let status = status_rx.borrow().clone();
+    assert_eq!(status.state, MonitorState::Resting);
+    assert_eq!(status.activity["Code"].cpu_percent, 0.0);
+    assert!(!status.activity["Code"].active);
+    tokio::time::sleep(Duration::from_secs(1700)).await;
+    monitor.abort();
+
+    // Busy for over half of the last 10 seconds
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![expected_line(
+            local_dt("2025-05-01 09:10:06"),
+            local_dt("2025-05-01 09:30:06"),
+            ""
+        )]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn session_of_a_killed_monitor_is_recovered_from_its_checkpoint() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("checkpoint");
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3600)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, RecordingHooks::default()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_millis(5 * 60 * 1000 + 500)).await;
+    // Killed without a chance to log the session
+    monitor.abort();
+    let _ = monitor.await;
+
+    let checkpoint = read_checkpoint(&checkpoint_path(&log_path))
+        .unwrap()
+        .expect("the open session should be checkpointed");
+    assert_eq!(checkpoint.entry.start, origin);
+    assert_eq!(checkpoint.entry.end, origin + ChronoDuration::minutes(5));
+    assert!(read_log_lines(&log_path).is_empty());
+
+    let restarted_at = origin + ChronoDuration::minutes(20);
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(
+            ScriptedProbe::new(Vec::new()),
+            restarted_at,
+            RecordingHooks::default(),
+        ),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    monitor.abort();
+
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![expected_line(
+            origin,
+            origin + ChronoDuration::minutes(5),
+            "coding"
+        )]
+    );
+    assert!(!checkpoint_path(&log_path).exists());
+}
+
+#[tokio::test(start_paused = true)]
+async fn killed_session_ends_at_the_last_heartbeat_between_checkpoints() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("heartbeat");
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3600)]);
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, RecordingHooks::default()),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    // Killed 25 seconds after the last checkpoint, 5 after the last heartbeat
+    tokio::time::sleep(Duration::from_millis((5 * 60 + 25) * 1000 + 500)).await;
+    monitor.abort();
+    let _ = monitor.await;
+
+    let checkpoint = read_checkpoint(&checkpoint_path(&log_path))
+        .unwrap()
+        .expect("the open session should be checkpointed");
+    assert_eq!(checkpoint.entry.end, origin + ChronoDuration::minutes(5));
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(
+            ScriptedProbe::new(Vec::new()),
+            origin + ChronoDuration::minutes(20),
+            RecordingHooks::default(),
+        ),
+        config(&log_path, Some("coding".to_string())),
+        MonitorControl::default(),
+    ));
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    monitor.abort();
+
+    let lines = read_log_lines(&log_path);
+    assert_eq!(lines.len(), 1);
+    let entry: LogEntry = serde_json::from_str(&lines[0]).unwrap();
+    assert_eq!(entry.start, origin);
+    assert_eq!(entry.end, origin + ChronoDuration::seconds(5 * 60 + 20));
+    assert_eq!(entry.duration_seconds, 5 * 60 + 20);
+    assert!(!checkpoint_path(&log_path).exists());
+    assert!(!last_seen_path(&checkpoint_path(&log_path)).exists());
+}
+
+#[tokio::test(start_paused = true)]
+async fn termination_signal_logs_the_session_and_reports_the_signal() {
+    let origin = local_dt("2025-05-01 09:00:00");
+    let log_path = temp_log_path("signals");
+    let hooks = RecordingHooks::default();
+    let probe = ScriptedProbe::new(vec![("Code", 0, 3 * 3600)]);
+    let (signal_tx, signal_rx) = mpsc::unbounded_channel();
+    let (status_tx, status_rx) = watch::channel(MonitorStatus::default());
+
+    let monitor = tokio::spawn(run_rest_reminder_with(
+        deps(probe, origin, hooks.clone()),
+        config(&log_path, None),
+        MonitorControl {
+            signal_rx: Some(signal_rx),
+            status_tx: Some(status_tx),
+            ..MonitorControl::default()
+        },
+    ));
+    tokio::time::sleep(Duration::from_secs(10 * 60)).await;
+    signal_tx.send(SignalRequest::TogglePause).unwrap();
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert_eq!(status_rx.borrow().state, MonitorState::Paused);
+
+    signal_tx.send(SignalRequest::TogglePause).unwrap();
+    tokio::time::sleep(Duration::from_secs(20 * 60)).await;
+    signal_tx
+        .send(SignalRequest::Stop(StopSignal::Terminate))
+        .unwrap();
+    let stopped_by = monitor.await.unwrap();
+
+    assert_eq!(stopped_by, StopSignal::Terminate);
+    assert_eq!(stopped_by.exit_code(), 143);
+    let at = |minutes| origin + ChronoDuration::minutes(minutes);
+    assert_eq!(
+        read_log_lines(&log_path),
+        vec![
+            expected_line(at(0), at(10), ""),
+            expected_line(at(11), at(31), ""),
+        ]
+    );
+    assert_eq!(
+        hooks.calls().last().map(String::as_str),
+        Some("on_shutdown 2025-05-01 09:31:00 1200")
+    );
+}
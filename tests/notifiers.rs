@@ -0,0 +1,212 @@
+use chrono::Local;
+use rest_reminder::core::budget::{BudgetAlert, BudgetPeriod};
+use rest_reminder::core::messages::MessageContext;
+use rest_reminder::core::notify::{
+    FileNotifier, Notifier, Notifiers, NotifyConfig, NotifyEvent, NotifyRoute, Sink,
+    budget_message, parse_notify_routes, reminder_message,
+};
+use std::path::PathBuf;
+use tokio::time::{Duration, Instant};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_notify_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    dir
+}
+
+fn read_lines(path: &PathBuf) -> Vec<serde_json::Value> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| serde_json::from_str(line).expect("each line should be JSON"))
+        .collect()
+}
+
+// Sinks run in the background, give them a moment to write
+async fn wait_for_lines(path: &PathBuf, count: usize) -> Vec<serde_json::Value> {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while read_lines(path).len() < count && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    read_lines(path)
+}
+
+fn budget_alert() -> BudgetAlert {
+    BudgetAlert {
+        period: BudgetPeriod::Daily,
+        percent: 100,
+        worked_seconds: 8 * 3600,
+        budget_seconds: 8 * 3600,
+    }
+}
+
+#[test]
+fn routes_replace_the_default_sinks_of_their_event() {
+    let routes = parse_notify_routes(&[
+        "reminder=bell,command:notify-send Break".to_string(),
+        "work_start=file:/tmp/rest.fifo".to_string(),
+        "budget=none".to_string(),
+    ])
+    .unwrap();
+    let config = NotifyConfig::default().with_routes(&routes);
+
+    assert_eq!(
+        config.sinks(NotifyEvent::Reminder),
+        [Sink::Bell, Sink::Command("notify-send Break".to_string())]
+    );
+    assert_eq!(
+        config.sinks(NotifyEvent::WorkStart),
+        [Sink::File(PathBuf::from("/tmp/rest.fifo"))]
+    );
+    assert!(config.sinks(NotifyEvent::Budget).is_empty());
+    assert_eq!(
+        config.sinks(NotifyEvent::Escalation),
+        NotifyConfig::default().sinks(NotifyEvent::Escalation)
+    );
+
+    let silenced = NotifyConfig::default().with_routes(&["all=none".parse().unwrap()]);
+    for event in NotifyEvent::ALL {
+        assert!(silenced.sinks(event).is_empty());
+    }
+}
+
+#[test]
+fn malformed_routes_are_rejected() {
+    assert!("reminder".parse::<NotifyRoute>().is_err());
+    assert!("lunch=bell".parse::<NotifyRoute>().is_err());
+    assert!("reminder=popup".parse::<NotifyRoute>().is_err());
+    assert!("reminder=command:".parse::<NotifyRoute>().is_err());
+}
+
+#[cfg(not(any(windows, target_os = "macos")))]
+#[test]
+fn desktop_notifications_are_only_a_default_with_a_session_bus() {
+    let session_bus = std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some();
+    let config = NotifyConfig::default();
+    for event in [NotifyEvent::Reminder, NotifyEvent::Escalation] {
+        assert!(config.sinks(event).contains(&Sink::Terminal));
+        assert_eq!(config.sinks(event).contains(&Sink::Desktop), session_bus);
+    }
+}
+
+#[tokio::test]
+async fn file_sink_appends_a_json_line_per_message() {
+    let path = temp_dir("file").join("notifications.jsonl");
+    let notifier = FileNotifier::new(path.clone());
+
+    notifier
+        .notify(reminder_message(3600, &MessageContext::default()))
+        .await
+        .unwrap();
+    notifier
+        .notify(budget_message(&budget_alert(), &MessageContext::default()))
+        .await
+        .unwrap();
+
+    let lines = read_lines(&path);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["event"], "reminder");
+    assert_eq!(lines[0]["title"], "Time to take a break");
+    assert!(lines[0]["body"].as_str().unwrap().starts_with("1 hour"));
+    assert_eq!(lines[0]["urgency"], "normal");
+    assert_eq!(lines[1]["event"], "budget");
+    assert_eq!(lines[1]["urgency"], "critical");
+}
+
+#[tokio::test]
+async fn every_sink_of_the_event_gets_the_message() {
+    let dir = temp_dir("routes");
+    let first = dir.join("first.jsonl");
+    let second = dir.join("second.jsonl");
+    let routes = [format!(
+        "reminder=file:{},file:{}",
+        first.display(),
+        second.display()
+    )];
+    let config = NotifyConfig::default()
+        .with_routes(&["all=none".parse().unwrap()])
+        .with_routes(&parse_notify_routes(&routes).unwrap());
+    let notifiers = Notifiers::new(&config);
+
+    notifiers.send(
+        budget_message(&budget_alert(), &MessageContext::default()),
+        None,
+    );
+    notifiers.send(reminder_message(1800, &MessageContext::default()), None);
+
+    assert_eq!(wait_for_lines(&first, 1).await.len(), 1);
+    assert_eq!(wait_for_lines(&second, 1).await[0]["event"], "reminder");
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(read_lines(&first).len(), 1);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn command_sink_gets_the_message_in_its_environment() {
+    let output = temp_dir("command").join("output.txt");
+    let notifier = Sink::Command(format!(
+        "printf '%s|%s|%s' \"$REST_REMINDER_EVENT\" \"$REST_REMINDER_URGENCY\" \"$REST_REMINDER_TITLE\" > '{}'",
+        output.display()
+    ))
+    .notifier();
+
+    notifier
+        .notify(budget_message(&budget_alert(), &MessageContext::default()))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        std::fs::read_to_string(&output).unwrap(),
+        "budget|critical|100% of your daily budget"
+    );
+    assert!(
+        Sink::Command("exit 3".to_string())
+            .notifier()
+            .notify(reminder_message(60, &MessageContext::default()))
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn fifo_sink_fails_instead_of_blocking_without_a_reader() {
+    let fifo = temp_dir("fifo").join("notifications.fifo");
+    let created = std::process::Command::new("mkfifo").arg(&fifo).status();
+    if !created.is_ok_and(|status| status.success()) {
+        println!("mkfifo is not available, skipping");
+        return;
+    }
+    let notifier = FileNotifier::new(fifo.clone());
+
+    let unread = tokio::time::timeout(
+        Duration::from_secs(5),
+        notifier.notify(reminder_message(60, &MessageContext::default())),
+    )
+    .await
+    .expect("writing to a FIFO nobody reads should not block");
+    assert!(unread.is_err());
+
+    let reader = std::thread::spawn(move || std::fs::read_to_string(fifo).unwrap());
+    // The reader only has the FIFO open once its `open` returns
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut written = notifier
+        .notify(reminder_message(60, &MessageContext::default()))
+        .await;
+    while written.is_err() && Instant::now() < deadline {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        written = notifier
+            .notify(reminder_message(60, &MessageContext::default()))
+            .await;
+    }
+    written.unwrap();
+
+    let line = reader.join().unwrap();
+    let message: serde_json::Value = serde_json::from_str(line.trim()).unwrap();
+    assert_eq!(message["event"], "reminder");
+}
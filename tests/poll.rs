@@ -0,0 +1,69 @@
+use rest_reminder::core::engine::MonitorState;
+use rest_reminder::core::poll::PollIntervals;
+use tokio::time::Duration;
+
+fn secs(seconds: u64) -> Duration {
+    Duration::from_secs(seconds)
+}
+
+#[test]
+fn resting_is_the_slow_default() {
+    let poll = PollIntervals::default();
+    assert!(poll.resting > poll.working);
+    assert_eq!(poll.next_check(MonitorState::Resting, None), secs(5));
+    assert_eq!(poll.next_check(MonitorState::Working, None), secs(2));
+}
+
+#[test]
+fn working_checks_come_closer_together_near_the_reminder() {
+    let poll = PollIntervals {
+        resting: 10,
+        working: 60,
+    };
+    let working = |left: u64| poll.next_check(MonitorState::Working, Some(secs(left)));
+
+    // Far away the working interval holds
+    assert_eq!(working(3600), secs(60));
+    assert_eq!(working(240), secs(60));
+    // Then a quarter of the time left
+    assert_eq!(working(120), secs(30));
+    assert_eq!(working(40), secs(10));
+    assert_eq!(working(8), secs(2));
+    // Never below a second, and the last check lands when the reminder is due
+    assert_eq!(working(3), secs(1));
+    assert_eq!(working(2), secs(2));
+    assert_eq!(
+        poll.next_check(MonitorState::Working, Some(Duration::from_millis(1500))),
+        Duration::from_millis(1500)
+    );
+    assert_eq!(working(0), secs(1));
+}
+
+#[test]
+fn the_reminder_is_reached_exactly_from_any_distance() {
+    let poll = PollIntervals {
+        resting: 5,
+        working: 30,
+    };
+    for start in [1, 7, 59, 600, 3601] {
+        let mut left = secs(start);
+        let mut checks = 0;
+        while !left.is_zero() {
+            let step = poll.next_check(MonitorState::Working, Some(left));
+            assert!(step <= left, "check after {:?} overshoots {:?}", step, left);
+            left -= step;
+            checks += 1;
+        }
+        assert!(checks <= start as usize);
+    }
+}
+
+#[test]
+fn resting_ignores_the_reminder_distance_beyond_its_interval() {
+    let poll = PollIntervals::default();
+    assert_eq!(
+        poll.next_check(MonitorState::Resting, Some(secs(3600))),
+        secs(5)
+    );
+    assert_eq!(poll.longest(), secs(5));
+}
@@ -0,0 +1,150 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use rest_reminder::core::schedule::{AfterHours, Hours, Schedule, read_holidays};
+use std::path::PathBuf;
+
+fn local_dt(s: &str) -> DateTime<Local> {
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+    Local.from_local_datetime(&naive).single().unwrap()
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "rest_reminder_schedule_{}_{}_{}",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::create_dir_all(&dir).expect("temp dir should be writable");
+    dir
+}
+
+fn load_schedule(name: &str, json: &str) -> Result<Schedule, String> {
+    let path = temp_dir(name).join("schedule.json");
+    std::fs::write(&path, json).unwrap();
+    Schedule::load(&path)
+}
+
+fn time(s: &str) -> NaiveTime {
+    NaiveTime::parse_from_str(s, "%H:%M").unwrap()
+}
+
+#[test]
+fn hours_may_run_over_midnight() {
+    let day: Hours = "09:00-17:30".parse().unwrap();
+    assert!(day.contains(time("09:00")));
+    assert!(!day.contains(time("17:30")));
+    assert!(!day.wraps());
+
+    let night: Hours = " 22:00 - 07:00 ".parse().unwrap();
+    assert!(night.wraps());
+    assert!(night.contains(time("23:00")));
+    assert!(night.contains(time("06:59")));
+    assert!(!night.contains(time("07:00")));
+
+    assert!("09:00".parse::<Hours>().is_err());
+    assert!("09:00-09:00".parse::<Hours>().is_err());
+    assert!("9am-5pm".parse::<Hours>().is_err());
+}
+
+#[test]
+fn working_hours_follow_the_weekday_and_skip_holidays() {
+    let dir = temp_dir("holidays");
+    std::fs::write(
+        dir.join("holidays.txt"),
+        "# public holidays\n2025-05-01 # labour day\n\n2025-05-02\n",
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("schedule.json"),
+        r#"{
+            "working_hours": {
+                "mon": ["09:00-12:00", "13:00-17:30"],
+                "tue": ["09:00-17:30"],
+                "fri": ["22:00-02:00"]
+            },
+            "holidays": ["2025-05-06"],
+            "holiday_file": "holidays.txt"
+        }"#,
+    )
+    .unwrap();
+    let schedule = Schedule::load(&dir.join("schedule.json")).unwrap();
+
+    // 2025-05-05 is a Monday
+    assert!(schedule.is_working_time(local_dt("2025-05-05 09:00:00")));
+    assert!(!schedule.is_working_time(local_dt("2025-05-05 12:30:00")));
+    assert!(!schedule.is_working_time(local_dt("2025-05-05 17:30:00")));
+    // A holiday from the list and one from the file
+    assert!(!schedule.is_working_time(local_dt("2025-05-06 10:00:00")));
+    assert!(!schedule.is_working_time(local_dt("2025-05-02 23:00:00")));
+    // Days missing from the week are days off
+    assert!(!schedule.is_working_time(local_dt("2025-05-07 10:00:00")));
+
+    // The Friday night shift runs into Saturday, but does not start on Saturday night
+    assert!(schedule.is_working_time(local_dt("2025-05-09 23:00:00")));
+    assert!(schedule.is_working_time(local_dt("2025-05-10 01:59:00")));
+    assert!(!schedule.is_working_time(local_dt("2025-05-10 02:00:00")));
+    assert!(!schedule.is_working_time(local_dt("2025-05-10 23:00:00")));
+    assert!(!schedule.is_working_time(local_dt("2025-05-09 01:00:00")));
+
+    assert_eq!(
+        read_holidays(&dir.join("holidays.txt")).unwrap(),
+        vec![
+            NaiveDate::from_ymd_opt(2025, 5, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 5, 2).unwrap(),
+        ]
+    );
+}
+
+#[test]
+fn quiet_hours_and_silent_evenings_mute_reminders() {
+    let schedule = load_schedule(
+        "quiet",
+        r#"{
+            "working_hours": {"thu": ["09:00-17:00"]},
+            "quiet_hours": ["12:00-13:00"],
+            "after_hours": "silent",
+            "after_hours_threshold": 1800
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(schedule.after_hours, AfterHours::Silent);
+    assert_eq!(schedule.after_hours_threshold, Some(1800));
+
+    // 2025-05-01 is a Thursday
+    assert!(!schedule.reminders_muted(local_dt("2025-05-01 10:00:00")));
+    assert!(schedule.is_quiet(local_dt("2025-05-01 12:30:00")));
+    assert!(schedule.reminders_muted(local_dt("2025-05-01 12:30:00")));
+    assert!(!schedule.is_quiet(local_dt("2025-05-01 18:00:00")));
+    assert!(schedule.reminders_muted(local_dt("2025-05-01 18:00:00")));
+
+    // Without working hours every day is a working day
+    let always = load_schedule("always", r#"{"after_hours": "nudge"}"#).unwrap();
+    assert!(always.is_working_time(local_dt("2025-05-04 03:00:00")));
+    assert!(!always.reminders_muted(local_dt("2025-05-04 03:00:00")));
+}
+
+#[test]
+fn invalid_schedules_are_rejected() {
+    let cases = [
+        (r#"{"working_hours": {"someday": []}}"#, "Invalid weekday"),
+        (
+            r#"{"working_hours": {"mon": ["09:00"]}}"#,
+            "Invalid hours '09:00'",
+        ),
+        (r#"{"holidays": ["25/12/2025"]}"#, "Invalid holiday"),
+        (r#"{"quiet_hours": ["22:00-22:00"]}"#, "Invalid hours"),
+        (r#"{"after_hours": "ignore"}"#, "Invalid schedule file"),
+        (r#"{"after_hours_threshold": 0}"#, "greater than 0"),
+        (r#"{"holiday_file": "missing.txt"}"#, "Cannot read"),
+        (r#"{"overtime": true}"#, "Invalid schedule file"),
+    ];
+    for (json, expected) in cases {
+        let error = load_schedule("invalid", json).unwrap_err();
+        assert!(
+            error.contains(expected),
+            "{} should contain {}",
+            error,
+            expected
+        );
+    }
+}
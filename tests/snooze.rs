@@ -0,0 +1,19 @@
+use rest_reminder::core::snooze::parse_snooze_key;
+use tokio::time::Duration;
+
+#[test]
+fn s_snoozes_for_the_default_and_digits_for_multiples_of_five_minutes() {
+    let default = Duration::from_secs(600);
+    assert_eq!(parse_snooze_key('s', default), Some(default));
+    assert_eq!(parse_snooze_key('S', default), Some(default));
+    assert_eq!(
+        parse_snooze_key('1', default),
+        Some(Duration::from_secs(5 * 60))
+    );
+    assert_eq!(
+        parse_snooze_key('9', default),
+        Some(Duration::from_secs(45 * 60))
+    );
+    assert_eq!(parse_snooze_key('0', default), None);
+    assert_eq!(parse_snooze_key('q', default), None);
+}
@@ -0,0 +1,175 @@
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use rest_reminder::core::probe::ProcessInfo;
+use rest_reminder::core::task_rules::TaskRules;
+use std::path::PathBuf;
+
+fn local_dt(s: &str) -> DateTime<Local> {
+    let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap();
+    Local.from_local_datetime(&naive).single().unwrap()
+}
+
+fn rules_file(name: &str, json: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "rest_reminder_task_rules_{}_{}_{}.json",
+        name,
+        std::process::id(),
+        Local::now().timestamp_nanos_opt().unwrap()
+    ));
+    std::fs::write(&path, json).expect("temp file should be writable");
+    path
+}
+
+fn process(pid: u32, name: &str, cmd: &str) -> ProcessInfo {
+    ProcessInfo {
+        cmd: cmd.to_string(),
+        ..ProcessInfo::new(pid, name)
+    }
+}
+
+fn fired(rules: &TaskRules, processes: &[ProcessInfo], now: &str) -> Option<String> {
+    rules
+        .fired(processes, local_dt(now), None)
+        .map(|rule| rule.name.clone())
+}
+
+#[test]
+fn the_first_rule_that_fires_wins() {
+    let path = rules_file(
+        "order",
+        r#"{"rules": [
+            {"name": "calls", "task": "meetings", "app": "zoom"},
+            {"name": "editing", "task": "coding", "app": "Code"},
+            {"task": "", "app": "steam"}
+        ]}"#,
+    );
+    let rules = TaskRules::load(&path).unwrap();
+    let code = process(1, "Code", "/usr/bin/code");
+    let zoom = process(2, "zoom", "/opt/zoom/zoom");
+
+    assert_eq!(rules.rules().len(), 3);
+    assert_eq!(rules.path(), path.as_path());
+    assert_eq!(
+        fired(&rules, std::slice::from_ref(&code), "2025-05-01 10:00:00").as_deref(),
+        Some("editing")
+    );
+    assert_eq!(
+        fired(&rules, &[code, zoom], "2025-05-01 10:00:00").as_deref(),
+        Some("calls")
+    );
+    assert_eq!(fired(&rules, &[], "2025-05-01 10:00:00"), None);
+
+    // Unnamed rules are numbered, an empty task leaves sessions unlabeled
+    let steam = rules
+        .fired(
+            &[process(3, "steam", "steam")],
+            local_dt("2025-05-01 10:00:00"),
+            None,
+        )
+        .unwrap();
+    assert_eq!(steam.name, "rule 3");
+    assert_eq!(steam.task, None);
+}
+
+#[test]
+fn weekdays_and_hours_limit_when_a_rule_fires() {
+    let path = rules_file(
+        "time",
+        r#"{"rules": [
+            {"name": "standup", "task": "meetings", "weekdays": ["mon", "Wednesday"], "hours": "09:30-09:45"},
+            {"name": "on call", "task": "ops", "hours": "22:00-02:00"}
+        ]}"#,
+    );
+    let rules = TaskRules::load(&path).unwrap();
+
+    // 2025-05-05 is a Monday
+    assert_eq!(
+        fired(&rules, &[], "2025-05-05 09:30:00").as_deref(),
+        Some("standup")
+    );
+    assert_eq!(fired(&rules, &[], "2025-05-05 09:45:00"), None);
+    assert_eq!(
+        fired(&rules, &[], "2025-05-07 09:40:00").as_deref(),
+        Some("standup")
+    );
+    assert_eq!(fired(&rules, &[], "2025-05-06 09:40:00"), None);
+
+    // The window runs over midnight
+    assert_eq!(
+        fired(&rules, &[], "2025-05-06 23:00:00").as_deref(),
+        Some("on call")
+    );
+    assert_eq!(
+        fired(&rules, &[], "2025-05-07 01:59:00").as_deref(),
+        Some("on call")
+    );
+    assert_eq!(fired(&rules, &[], "2025-05-07 02:00:00"), None);
+}
+
+#[test]
+fn app_and_command_line_have_to_match_the_same_process() {
+    let path = rules_file(
+        "cmd",
+        r#"{"rules": [
+            {"name": "client", "task": "acme", "app": "exact:python", "cmd": "acme_[a-z]+\\.py"}
+        ]}"#,
+    );
+    let rules = TaskRules::load(&path).unwrap();
+    let python = process(1, "python", "python tools/build.py");
+    let script = process(2, "bash", "bash acme_deploy.py");
+
+    assert_eq!(
+        fired(&rules, &[python.clone(), script], "2025-05-01 10:00:00"),
+        None
+    );
+    assert_eq!(
+        fired(
+            &rules,
+            &[python, process(3, "python", "python acme_sync.py")],
+            "2025-05-01 10:00:00"
+        )
+        .as_deref(),
+        Some("client")
+    );
+}
+
+#[test]
+fn invalid_rules_name_the_rule_and_reloading_keeps_the_old_rules() {
+    let cases = [
+        (
+            r#"{"rules": [{"task": "x", "hours": "09:00"}]}"#,
+            "Invalid task rule 'rule 1'",
+        ),
+        (
+            r#"{"rules": [{"name": "late", "task": "x", "hours": "10:00-10:00"}]}"#,
+            "Invalid task rule 'late'",
+        ),
+        (
+            r#"{"rules": [{"task": "x", "weekdays": ["someday"]}]}"#,
+            "Invalid weekday",
+        ),
+        (r#"{"rules": [{"task": "x", "cmd": "("}]}"#, "Invalid regex"),
+        (
+            r#"{"rules": [{"task": "x", "when": "always"}]}"#,
+            "Invalid task rules file",
+        ),
+    ];
+    for (json, expected) in cases {
+        let error = TaskRules::load(&rules_file("invalid", json)).unwrap_err();
+        assert!(
+            error.contains(expected),
+            "{} should contain {}",
+            error,
+            expected
+        );
+    }
+
+    let path = rules_file("reload", r#"{"rules": [{"task": "x"}]}"#);
+    let mut rules = TaskRules::load(&path).unwrap();
+    std::fs::write(&path, r#"{"rules": [{"task": "x"}, {"task": "y"}]}"#).unwrap();
+    rules.reload().unwrap();
+    assert_eq!(rules.rules().len(), 2);
+
+    std::fs::write(&path, "not json").unwrap();
+    assert!(rules.reload().is_err());
+    assert_eq!(rules.rules().len(), 2);
+}
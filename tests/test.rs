@@ -2,9 +2,10 @@
 mod test_first {
     use chrono::{DateTime, Local, LocalResult, NaiveDateTime, TimeZone};
     use rest_reminder::statistic::statistics::{
-        UNLABELED_TASK, acc_work_time, acc_work_time_for_task, acc_work_time_precise,
-        acc_work_time_precise_for_task, single_day_work_time, single_day_work_time_for_task,
-        task_work_time_summary,
+        UNATTRIBUTED_APP, UNLABELED_TASK, acc_work_time, acc_work_time_for_task,
+        acc_work_time_precise, acc_work_time_precise_for_task, app_work_time_summary,
+        break_summary, overtime_work_time_for_task, overtime_work_time_precise_for_task,
+        single_day_work_time, single_day_work_time_for_task, task_work_time_summary,
     };
     use std::path::PathBuf;
 
@@ -217,6 +218,116 @@ mod test_first {
         assert_eq!(UNLABELED_TASK, "Unlabeled");
     }
 
+    #[test]
+    fn test_app_summary_splits_sessions_by_app_time() {
+        let mut late = serde_json::from_str::<serde_json::Value>(&json_log(
+            "2025-04-19 23:00:00",
+            "2025-04-20 01:00:00",
+            "coding",
+        ))
+        .unwrap();
+        late["app_seconds"] = serde_json::json!({"Code": 7200, "Game": 3600});
+        let mut morning = serde_json::from_str::<serde_json::Value>(&json_log(
+            "2025-04-19 10:00:00",
+            "2025-04-19 10:30:00",
+            "coding",
+        ))
+        .unwrap();
+        morning["app_seconds"] = serde_json::json!({"Code": 1800, "Game": 600});
+        let path = write_temp_log(&[
+            &morning.to_string(),
+            &late.to_string(),
+            "[2025-04-19 12:00:00 ~ 2025-04-19 12:02:00] You worked for 2.00 minutes",
+            &break_log("2025-04-19 12:02:00", "2025-04-19 12:10:00", false),
+        ]);
+        let day = local_date("2025-04-19");
+
+        let summary = app_work_time_summary(path, day, day).unwrap();
+
+        // The late session only counts its share before midnight
+        assert_eq!(summary.len(), 3);
+        assert_eq!(summary[0].app, "Code");
+        assert_eq!(summary[0].seconds, 1800 + 3599);
+        assert_eq!(summary[1].app, "Game");
+        assert_eq!(summary[1].seconds, 600 + 1799);
+        assert_eq!(summary[2].app, UNATTRIBUTED_APP);
+        assert_eq!(summary[2].seconds, 120);
+    }
+
+    #[test]
+    fn test_breaks_are_summarized_and_not_counted_as_work() {
+        let path = write_temp_log(&[
+            &json_log("2025-04-19 10:00:00", "2025-04-19 11:00:00", "coding"),
+            &break_log("2025-04-19 11:00:00", "2025-04-19 11:10:00", false),
+            &json_log("2025-04-19 11:10:00", "2025-04-19 12:10:00", "coding"),
+            &break_log("2025-04-19 12:10:00", "2025-04-19 12:11:00", true),
+        ]);
+        let day = local_date("2025-04-19");
+
+        assert_eq!(single_day_work_time(path.clone(), day).unwrap(), 7200);
+        let summary = task_work_time_summary(path.clone(), day, day).unwrap();
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0].seconds, 7200);
+
+        let breaks = break_summary(path, day, day).unwrap();
+        assert_eq!(breaks.breaks, 2);
+        assert_eq!(breaks.total_seconds, 660);
+        assert_eq!(breaks.average_seconds, 330);
+        assert_eq!(breaks.longest_seconds, 600);
+        assert_eq!(breaks.violations, 1);
+    }
+
+    #[test]
+    fn test_overtime_counts_off_schedule_work_only() {
+        let mut evening = serde_json::from_str::<serde_json::Value>(&json_log(
+            "2025-04-19 18:00:00",
+            "2025-04-19 19:30:00",
+            "coding",
+        ))
+        .unwrap();
+        evening["off_schedule"] = serde_json::json!(true);
+        let mut night = serde_json::from_str::<serde_json::Value>(&json_log(
+            "2025-04-19 23:30:00",
+            "2025-04-20 00:30:00",
+            "ops",
+        ))
+        .unwrap();
+        night["off_schedule"] = serde_json::json!(true);
+        let path = write_temp_log(&[
+            &json_log("2025-04-19 09:00:00", "2025-04-19 12:00:00", "coding"),
+            &evening.to_string(),
+            &night.to_string(),
+        ]);
+        let day = local_date("2025-04-19");
+
+        assert_eq!(
+            single_day_work_time(path.clone(), day).unwrap(),
+            10800 + 5400 + 1799
+        );
+        assert_eq!(
+            overtime_work_time_for_task(path.clone(), day, day, None).unwrap(),
+            5400 + 1799
+        );
+        assert_eq!(
+            overtime_work_time_for_task(path.clone(), day, local_date("2025-04-20"), None).unwrap(),
+            5400 + 3600
+        );
+        assert_eq!(
+            overtime_work_time_for_task(path.clone(), day, day, Some("coding")).unwrap(),
+            5400
+        );
+        assert_eq!(
+            overtime_work_time_precise_for_task(
+                path,
+                local_dt("2025-04-19 11:00:00"),
+                local_dt("2025-04-19 18:30:00"),
+                None
+            )
+            .unwrap(),
+            1800
+        );
+    }
+
     fn local_date(date_str: &str) -> DateTime<Local> {
         let datetime_str = format!("{} 00:00:00", date_str);
         let naive = NaiveDateTime::parse_from_str(&datetime_str, "%Y-%m-%d %H:%M:%S")
@@ -258,4 +369,18 @@ mod test_first {
         })
         .to_string()
     }
+
+    fn break_log(start: &str, end: &str, violation: bool) -> String {
+        let start = local_dt(start);
+        let end = local_dt(end);
+        serde_json::json!({
+            "start": start,
+            "end": end,
+            "duration_seconds": (end - start).num_seconds(),
+            "apps": ["Cursor"],
+            "kind": "break",
+            "violation": violation,
+        })
+        .to_string()
+    }
 }